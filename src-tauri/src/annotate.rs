@@ -0,0 +1,229 @@
+use tauri::{AppHandle, Emitter};
+use image::{imageops, Rgba, RgbaImage};
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+use crate::{app_data_dir, images, relations, DbState};
+
+const DEFAULT_COLOR: [u8; 4] = [255, 59, 48, 255];
+const DEFAULT_THICKNESS: u32 = 4;
+const MAX_THICKNESS: u32 = 64;
+const DEFAULT_BLUR_SIGMA: f32 = 12.0;
+const MAX_BLUR_SIGMA: f32 = 64.0;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Annotation {
+    Rectangle { x: u32, y: u32, width: u32, height: u32, color: Option<[u8; 4]>, thickness: Option<u32> },
+    Highlight { x: u32, y: u32, width: u32, height: u32, color: Option<[u8; 4]> },
+    Redact { x: u32, y: u32, width: u32, height: u32 },
+    Blur { x: u32, y: u32, width: u32, height: u32, sigma: Option<f32> },
+    Arrow { from_x: i32, from_y: i32, to_x: i32, to_y: i32, color: Option<[u8; 4]>, thickness: Option<u32> },
+}
+
+/// Clamp a rectangle to the image bounds, returning (x0, y0, x1, y1) with exclusive ends.
+fn clip_rect(img: &RgbaImage, x: u32, y: u32, width: u32, height: u32) -> (u32, u32, u32, u32) {
+    let x0 = x.min(img.width());
+    let y0 = y.min(img.height());
+    let x1 = x.saturating_add(width).min(img.width());
+    let y1 = y.saturating_add(height).min(img.height());
+    (x0, y0, x1, y1)
+}
+
+fn blend(dst: &mut Rgba<u8>, src: Rgba<u8>) {
+    let alpha = src[3] as u32;
+    for i in 0..3 {
+        dst[i] = ((src[i] as u32 * alpha + dst[i] as u32 * (255 - alpha)) / 255) as u8;
+    }
+    dst[3] = dst[3].max(src[3]);
+}
+
+fn fill_rect(img: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, color: Rgba<u8>) {
+    let (x0, y0, x1, y1) = clip_rect(img, x, y, width, height);
+    for py in y0..y1 {
+        for px in x0..x1 {
+            blend(img.get_pixel_mut(px, py), color);
+        }
+    }
+}
+
+fn stroke_rect(img: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, color: Rgba<u8>, thickness: u32) {
+    let t = thickness.min(width / 2).min(height / 2).max(1);
+    fill_rect(img, x, y, width, t, color);
+    fill_rect(img, x, y.saturating_add(height).saturating_sub(t), width, t, color);
+    fill_rect(img, x, y, t, height, color);
+    fill_rect(img, x.saturating_add(width).saturating_sub(t), y, t, height, color);
+}
+
+fn plot(img: &mut RgbaImage, cx: i32, cy: i32, color: Rgba<u8>, thickness: u32) {
+    let r = (thickness / 2) as i32;
+    for py in (cy - r)..=(cy + r) {
+        for px in (cx - r)..=(cx + r) {
+            if px >= 0 && py >= 0 && (px as u32) < img.width() && (py as u32) < img.height() {
+                img.put_pixel(px as u32, py as u32, color);
+            }
+        }
+    }
+}
+
+// Bresenham with a square brush; good enough for annotation strokes.
+fn draw_line(img: &mut RgbaImage, from: (i32, i32), to: (i32, i32), color: Rgba<u8>, thickness: u32) {
+    let (mut x, mut y) = from;
+    let dx = (to.0 - x).abs();
+    let dy = -(to.1 - y).abs();
+    let sx = if x < to.0 { 1 } else { -1 };
+    let sy = if y < to.1 { 1 } else { -1 };
+    let mut err = dx + dy;
+
+    loop {
+        plot(img, x, y, color, thickness);
+        if x == to.0 && y == to.1 {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+}
+
+fn draw_arrow(img: &mut RgbaImage, from: (i32, i32), to: (i32, i32), color: Rgba<u8>, thickness: u32) {
+    draw_line(img, from, to, color, thickness);
+
+    let angle = ((to.1 - from.1) as f64).atan2((to.0 - from.0) as f64);
+    let head_len = (thickness as f64 * 4.0).max(12.0);
+    for spread in [0.45_f64, -0.45_f64] {
+        let a = angle + std::f64::consts::PI + spread;
+        let end = (
+            to.0 + (head_len * a.cos()).round() as i32,
+            to.1 + (head_len * a.sin()).round() as i32,
+        );
+        draw_line(img, to, end, color, thickness);
+    }
+}
+
+fn blur_region(img: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, sigma: f32) {
+    let (x0, y0, x1, y1) = clip_rect(img, x, y, width, height);
+    if x1 <= x0 || y1 <= y0 {
+        return;
+    }
+    let region = imageops::crop_imm(img, x0, y0, x1 - x0, y1 - y0).to_image();
+    let blurred = imageops::blur(&region, sigma);
+    imageops::replace(img, &blurred, x0 as i64, y0 as i64);
+}
+
+/// Strokes are drawn pixel by pixel, so an oversized thickness would stall the command.
+fn stroke_width(thickness: &Option<u32>) -> u32 {
+    thickness.unwrap_or(DEFAULT_THICKNESS).clamp(1, MAX_THICKNESS)
+}
+
+/// `imageops::blur` panics on a zero, negative or non-finite sigma, and a huge one builds a huge kernel.
+fn blur_sigma(sigma: &Option<f32>) -> Result<f32, String> {
+    match sigma.unwrap_or(DEFAULT_BLUR_SIGMA) {
+        s if s.is_finite() && s > 0.0 => Ok(s.clamp(0.1, MAX_BLUR_SIGMA)),
+        s => Err(format!("Invalid blur sigma: {}", s)),
+    }
+}
+
+fn apply(img: &mut RgbaImage, annotation: &Annotation) -> Result<(), String> {
+    match annotation {
+        Annotation::Rectangle { x, y, width, height, color, thickness } => {
+            stroke_rect(img, *x, *y, *width, *height, Rgba(color.unwrap_or(DEFAULT_COLOR)), stroke_width(thickness));
+        }
+        Annotation::Highlight { x, y, width, height, color } => {
+            fill_rect(img, *x, *y, *width, *height, Rgba(color.unwrap_or([255, 230, 0, 96])));
+        }
+        Annotation::Redact { x, y, width, height } => {
+            fill_rect(img, *x, *y, *width, *height, Rgba([0, 0, 0, 255]));
+        }
+        Annotation::Blur { x, y, width, height, sigma } => {
+            blur_region(img, *x, *y, *width, *height, blur_sigma(sigma)?);
+        }
+        Annotation::Arrow { from_x, from_y, to_x, to_y, color, thickness } => {
+            draw_arrow(img, (*from_x, *from_y), (*to_x, *to_y), Rgba(color.unwrap_or(DEFAULT_COLOR)), stroke_width(thickness));
+        }
+    }
+    Ok(())
+}
+
+/// Applies annotations to an image clip and stores the result as a new clip,
+/// leaving the original untouched. Returns the new clip id.
+#[tauri::command]
+pub async fn annotate_image_clip(app_handle: AppHandle, state: tauri::State<'_, DbState>, id: String, annotations: Vec<Annotation>) -> Result<String, String> {
+    let row: Option<(Option<String>,)> = sqlx::query_as("SELECT image_path FROM clips WHERE id = ? AND clip_type = 'image'")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let source_path = row
+        .and_then(|(p,)| p)
+        .ok_or_else(|| "Clip is not an image".to_string())?;
+
+    let mut img = images::open_rgba(&source_path)?;
+    for annotation in &annotations {
+        apply(&mut img, annotation)?;
+    }
+
+    let new_id = Uuid::new_v4().to_string();
+    let app_dir = app_data_dir(&app_handle);
+    let file_path = app_dir.join("images").join(format!("{}.png", new_id));
+    images::save_png(&file_path, &img)?;
+    let meta = images::image_meta(&img);
+    let hash = images::content_hash(img.width(), img.height(), img.as_raw());
+
+    sqlx::query("INSERT INTO clips (id, content, created_at, is_favorite, search_content, clip_type, image_path, image_width, image_height, dominant_color, content_hash) VALUES (?, '', ?, ?, NULL, 'image', ?, ?, ?, ?, ?)")
+        .bind(&new_id)
+        .bind(Utc::now().to_rfc3339())
+        .bind(false)
+        .bind(file_path.to_string_lossy().to_string())
+        .bind(meta.width)
+        .bind(meta.height)
+        .bind(meta.dominant_color)
+        .bind(hash)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    relations::record_relation(&state.pool, &new_id, &id, relations::ANNOTATED_FROM).await?;
+    state.recent.invalidate();
+    let _ = app_handle.emit("clipboard-changed", ());
+    Ok(new_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamps_stroke_thickness() {
+        assert_eq!(stroke_width(&None), DEFAULT_THICKNESS);
+        assert_eq!(stroke_width(&Some(0)), 1);
+        assert_eq!(stroke_width(&Some(u32::MAX)), MAX_THICKNESS);
+    }
+
+    #[test]
+    fn checks_blur_sigma() {
+        assert_eq!(blur_sigma(&None), Ok(DEFAULT_BLUR_SIGMA));
+        assert_eq!(blur_sigma(&Some(1e9)), Ok(MAX_BLUR_SIGMA));
+        assert_eq!(blur_sigma(&Some(f32::MIN_POSITIVE / 2.0)), Ok(0.1));
+        for sigma in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert!(blur_sigma(&Some(sigma)).is_err(), "{}", sigma);
+        }
+    }
+
+    #[test]
+    fn blurs_without_panicking() {
+        let mut img = RgbaImage::from_pixel(8, 8, Rgba([10, 20, 30, 255]));
+        let blur = Annotation::Blur { x: 0, y: 0, width: 8, height: 8, sigma: Some(f32::MIN_POSITIVE) };
+        assert!(apply(&mut img, &blur).is_ok());
+        let blur = Annotation::Blur { x: 0, y: 0, width: 8, height: 8, sigma: Some(-3.0) };
+        assert!(apply(&mut img, &blur).is_err());
+    }
+}
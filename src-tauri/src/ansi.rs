@@ -0,0 +1,110 @@
+// ANSI escape sequences in copies from terminals (colors, cursor movement,
+// hyperlinks). Captured text is stored without them so it pastes cleanly
+// into documents; `strip_ansi` turns this off and `keep_ansi_original`
+// keeps the raw text alongside, e.g. to paste colored output back into a
+// terminal.
+
+use sqlx::{Pool, Sqlite};
+
+use crate::{setting_enabled, DbState};
+
+const ESC: char = '\u{1b}';
+const BEL: char = '\u{7}';
+
+pub fn contains_escapes(text: &str) -> bool {
+    text.contains(ESC) || text.contains('\u{9b}')
+}
+
+/// Removes CSI (`ESC [ ... m`, also the 8-bit `\u{9b}` form), OSC
+/// (`ESC ] ... BEL` or `ESC ] ... ESC \`) and two-character escapes.
+pub fn strip(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{9b}' => {}
+            ESC => match chars.next() {
+                Some('[') => {}
+                Some(']') => {
+                    // Operating system command, ends with BEL or ST (ESC \)
+                    while let Some(c) = chars.next() {
+                        if c == BEL || (c == ESC && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                    continue;
+                }
+                // Charset selection takes one more character
+                Some('(' | ')' | '*' | '+') => {
+                    chars.next();
+                    continue;
+                }
+                _ => continue,
+            },
+            _ => {
+                result.push(c);
+                continue;
+            }
+        }
+        // Control sequence: parameters and intermediates up to the final byte (@ to ~)
+        for c in chars.by_ref() {
+            if ('@'..='~').contains(&c) {
+                break;
+            }
+        }
+    }
+    result
+}
+
+/// Text to store for a capture, and the raw original when it had escapes
+/// and `keep_ansi_original` is on.
+pub async fn clean_capture(pool: &Pool<Sqlite>, text: &str) -> (String, Option<String>) {
+    if !contains_escapes(text) || !setting_enabled(pool, "strip_ansi", true).await {
+        return (text.to_string(), None);
+    }
+    let raw = if setting_enabled(pool, "keep_ansi_original", false).await { Some(text.to_string()) } else { None };
+    (strip(text), raw)
+}
+
+/// The raw text of a clip captured with escape sequences, when it was kept.
+#[tauri::command]
+pub async fn get_ansi_original(state: tauri::State<'_, DbState>, id: String) -> Result<Option<String>, String> {
+    let row: Option<(Option<String>,)> = sqlx::query_as("SELECT ansi_original FROM clips WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(row.and_then(|(raw,)| raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_escapes() {
+        assert!(contains_escapes("\u{1b}[31mred"));
+        assert!(contains_escapes("\u{9b}1m"));
+        assert!(!contains_escapes("plain [31m"));
+    }
+
+    #[test]
+    fn strips_colors_and_cursor_movement() {
+        assert_eq!(strip("\u{1b}[1;31merror\u{1b}[0m: failed"), "error: failed");
+        assert_eq!(strip("\u{9b}32mok\u{9b}0m"), "ok");
+        assert_eq!(strip("line\u{1b}[2K\u{1b}[1Gdone"), "linedone");
+    }
+
+    #[test]
+    fn strips_hyperlinks_and_charset_selection() {
+        assert_eq!(strip("\u{1b}]8;;https://a.com\u{7}link\u{1b}]8;;\u{1b}\\"), "link");
+        assert_eq!(strip("\u{1b}(Bbox\u{1b}="), "box");
+    }
+
+    #[test]
+    fn keeps_plain_text_and_survives_truncation() {
+        assert_eq!(strip("naïve [text]"), "naïve [text]");
+        assert_eq!(strip("cut\u{1b}[31"), "cut");
+        assert_eq!(strip("cut\u{1b}"), "cut");
+    }
+}
@@ -0,0 +1,127 @@
+// Icons of the apps clips were copied from, cached once per app as a small
+// PNG under `icons/` so the list can show them next to the app name.
+// Available on macOS and Windows; Linux has no reliable way to get them.
+
+use tauri::AppHandle;
+use image::{imageops, RgbaImage};
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use crate::foreground::ForegroundWindow;
+use crate::{app_data_dir, images};
+
+const ICON_SIZE: u32 = 32;
+
+// Apps whose icon couldn't be read, so it isn't retried on every copy
+static MISSING: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// File name for an app's cached icon, from its bundle id or executable name.
+fn icon_key(window: &ForegroundWindow) -> Option<String> {
+    let id = window.app_id.as_deref().or(window.app_name.as_deref())?;
+    let key: String = id
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    (!key.is_empty()).then_some(key)
+}
+
+/// Path of the cached icon for the app behind `window`, extracting it on
+/// first use. None when the platform can't provide one.
+pub fn icon_path(app: &AppHandle, window: &ForegroundWindow) -> Option<String> {
+    let key = icon_key(window)?;
+    let path = app_data_dir(app).join("icons").join(format!("{}.png", key));
+    if path.exists() {
+        return Some(path.to_string_lossy().to_string());
+    }
+    if MISSING.lock().unwrap().as_ref().is_some_and(|missing| missing.contains(&key)) {
+        return None;
+    }
+
+    let saved = extract_icon(window).and_then(|icon| {
+        let icon = imageops::resize(&icon, ICON_SIZE, ICON_SIZE, imageops::FilterType::Triangle);
+        std::fs::create_dir_all(path.parent()?).ok()?;
+        images::save_png(&path, &icon).ok()
+    });
+    if saved.is_none() {
+        let _ = std::fs::remove_file(&path);
+        MISSING.lock().unwrap().get_or_insert_with(HashSet::new).insert(key);
+        return None;
+    }
+    Some(path.to_string_lossy().to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn extract_icon(_window: &ForegroundWindow) -> Option<RgbaImage> {
+    use objc2_app_kit::NSWorkspace;
+
+    let app = NSWorkspace::sharedWorkspace().frontmostApplication()?;
+    let tiff = app.icon()?.TIFFRepresentation()?;
+    image::load_from_memory(&tiff.to_vec()).ok().map(|img| img.into_rgba8())
+}
+
+#[cfg(target_os = "windows")]
+fn extract_icon(window: &ForegroundWindow) -> Option<RgbaImage> {
+    use windows::core::HSTRING;
+    use windows::Win32::Graphics::Gdi::{DeleteObject, GetDC, GetDIBits, GetObjectW, ReleaseDC, BITMAP, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS};
+    use windows::Win32::Storage::FileSystem::FILE_FLAGS_AND_ATTRIBUTES;
+    use windows::Win32::UI::Shell::{SHGetFileInfoW, SHFILEINFOW, SHGFI_ICON, SHGFI_LARGEICON};
+    use windows::Win32::UI::WindowsAndMessaging::{DestroyIcon, GetIconInfo, ICONINFO};
+
+    let path = window.process_path.as_ref()?;
+    unsafe {
+        let mut file_info = SHFILEINFOW::default();
+        let found = SHGetFileInfoW(
+            &HSTRING::from(path.as_path()),
+            FILE_FLAGS_AND_ATTRIBUTES(0),
+            Some(&mut file_info),
+            std::mem::size_of::<SHFILEINFOW>() as u32,
+            SHGFI_ICON | SHGFI_LARGEICON,
+        );
+        if found == 0 || file_info.hIcon.is_invalid() {
+            return None;
+        }
+        let mut icon_info = ICONINFO::default();
+        let got_info = GetIconInfo(file_info.hIcon, &mut icon_info).is_ok();
+        let _ = DestroyIcon(file_info.hIcon);
+        if !got_info {
+            return None;
+        }
+
+        let mut bitmap = BITMAP::default();
+        GetObjectW(icon_info.hbmColor.into(), std::mem::size_of::<BITMAP>() as i32, Some(&mut bitmap as *mut BITMAP as *mut _));
+        let (width, height) = (bitmap.bmWidth, bitmap.bmHeight);
+        let mut pixels = vec![0u8; (width.max(0) * height.max(0) * 4) as usize];
+        let mut bitmap_info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width,
+                biHeight: -height, // top-down rows
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let hdc = GetDC(None);
+        let lines = GetDIBits(hdc, icon_info.hbmColor, 0, height.max(0) as u32, Some(pixels.as_mut_ptr() as *mut _), &mut bitmap_info, DIB_RGB_COLORS);
+        ReleaseDC(None, hdc);
+        let _ = DeleteObject(icon_info.hbmColor.into());
+        let _ = DeleteObject(icon_info.hbmMask.into());
+        if lines == 0 || width <= 0 || height <= 0 {
+            return None;
+        }
+
+        // BGRA to RGBA
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+        RgbaImage::from_raw(width as u32, height as u32, pixels)
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn extract_icon(_window: &ForegroundWindow) -> Option<RgbaImage> {
+    None
+}
@@ -0,0 +1,325 @@
+// JSON archive of the history, for backups and moving to another machine.
+// Image clips are either embedded as base64 PNG or copied into a folder next
+// to the JSON file ("<name>_images/") and referenced by relative path.
+// Importing skips clips whose id, text or image is already in history.
+
+use tauri::{AppHandle, Emitter, Manager};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use base64::Engine;
+use uuid::Uuid;
+
+use crate::export::{fetch_clips, ExportFilter};
+use crate::importers::ImportReport;
+use crate::{app_data_dir, capture, images, normalize_text, tags, DbState};
+
+pub const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArchivedClip {
+    pub id: String,
+    pub content: String,
+    pub created_at: String, // ISO 8601
+    pub clip_type: String,
+    #[serde(default)]
+    pub is_favorite: bool,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub locked: bool,
+    #[serde(default)]
+    pub source_url: Option<String>,
+    #[serde(default)]
+    pub window_title: Option<String>,
+    #[serde(default)]
+    pub collection: Option<String>, // collection name
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // Image clips carry exactly one of these
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_base64: Option<String>, // PNG bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_file: Option<String>, // relative to the JSON file
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Archive {
+    pub version: u32,
+    pub exported_at: String, // ISO 8601
+    pub clips: Vec<ArchivedClip>,
+}
+
+/// Folder bundled images go into: "history.json" → "history_images".
+pub fn images_folder(path: &Path) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_else(|| "klip".to_string());
+    path.with_file_name(format!("{}_images", stem))
+}
+
+/// Archive of the clips matching `filter`. Images are copied into
+/// `image_folder` when given, otherwise embedded.
+pub async fn build_archive(pool: &Pool<Sqlite>, filter: &ExportFilter, image_folder: Option<&Path>) -> Result<Archive, String> {
+    let clips = fetch_clips(pool, filter).await?;
+
+    let tag_rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT ct.clip_id, t.name FROM clip_tags ct JOIN tags t ON t.id = ct.tag_id ORDER BY t.name COLLATE NOCASE"
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for (clip_id, name) in tag_rows {
+        tags.entry(clip_id).or_default().push(name);
+    }
+
+    let collections: HashMap<String, String> = sqlx::query_as::<_, (String, String)>("SELECT id, name FROM collections")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .collect();
+
+    let mut archived = Vec::with_capacity(clips.len());
+    for clip in clips {
+        let (mut image_base64, mut image_file) = (None, None);
+        if let Some(image_path) = clip.image_path.as_deref().filter(|_| clip.clip_type == "image") {
+            if let Some(folder) = image_folder {
+                std::fs::create_dir_all(folder).map_err(|e| e.to_string())?;
+                let file_name = format!("{}.png", clip.id);
+                std::fs::copy(image_path, folder.join(&file_name)).map_err(|e| e.to_string())?;
+                let folder_name = folder.file_name().unwrap_or_default().to_string_lossy();
+                image_file = Some(format!("{}/{}", folder_name, file_name));
+            } else {
+                let bytes = std::fs::read(image_path).map_err(|e| e.to_string())?;
+                image_base64 = Some(base64::engine::general_purpose::STANDARD.encode(bytes));
+            }
+        }
+
+        archived.push(ArchivedClip {
+            tags: tags.remove(&clip.id).unwrap_or_default(),
+            collection: clip.collection_id.as_ref().and_then(|id| collections.get(id).cloned()),
+            id: clip.id,
+            content: clip.content,
+            created_at: clip.created_at,
+            clip_type: clip.clip_type,
+            is_favorite: clip.is_favorite,
+            pinned: clip.pinned,
+            locked: clip.locked,
+            source_url: clip.source_url,
+            window_title: clip.window_title,
+            image_base64,
+            image_file,
+        });
+    }
+
+    Ok(Archive { version: ARCHIVE_VERSION, exported_at: Utc::now().to_rfc3339(), clips: archived })
+}
+
+/// Writes the matching clips as a JSON archive to `path`. `images` is
+/// "embed" (default, base64 inside the JSON) or "folder". Returns the clip count.
+#[tauri::command]
+pub async fn export_clips(state: tauri::State<'_, DbState>, path: String, filter: Option<ExportFilter>, images: Option<String>) -> Result<usize, String> {
+    let path = Path::new(&path);
+    let image_folder = match images.as_deref().unwrap_or("embed") {
+        "embed" => None,
+        "folder" => Some(images_folder(path)),
+        other => return Err(format!("Unknown image mode: {}", other)),
+    };
+
+    let archive = build_archive(&state.pool, &filter.unwrap_or_default(), image_folder.as_deref()).await?;
+    let json = serde_json::to_vec_pretty(&archive).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())?;
+    Ok(archive.clips.len())
+}
+
+pub fn parse_archive(data: &[u8]) -> Result<Archive, String> {
+    let archive: Archive = serde_json::from_slice(data).map_err(|e| format!("Not a Klip export: {}", e))?;
+    if archive.version > ARCHIVE_VERSION {
+        return Err(format!("Export version {} is newer than this version of Klip supports", archive.version));
+    }
+    Ok(archive)
+}
+
+/// PNG bytes of an archived image clip, from either representation.
+fn archived_image(clip: &ArchivedClip, base_dir: &Path) -> Result<Vec<u8>, String> {
+    if let Some(data) = &clip.image_base64 {
+        return base64::engine::general_purpose::STANDARD.decode(data).map_err(|e| e.to_string());
+    }
+    let file = clip.image_file.as_ref().ok_or_else(|| format!("Image clip {} has no image", clip.id))?;
+    if !is_relative_file(file) {
+        return Err(format!("{}: image files must be inside the export folder", file));
+    }
+    std::fs::read(base_dir.join(file)).map_err(|e| format!("{}: {}", file, e))
+}
+
+/// True for a plain relative path that can't leave the folder it's joined to.
+fn is_relative_file(file: &str) -> bool {
+    let path = Path::new(file);
+    !file.is_empty() && path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+async fn collection_id(conn: &mut SqliteConnection, name: &str) -> Result<String, String> {
+    let existing: Option<(String,)> = sqlx::query_as("SELECT id FROM collections WHERE name = ? COLLATE NOCASE")
+        .bind(name)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Some((id,)) = existing {
+        return Ok(id);
+    }
+
+    let id = Uuid::new_v4().to_string();
+    sqlx::query("INSERT INTO collections (id, name, created_at) VALUES (?, ?, ?)")
+        .bind(&id)
+        .bind(name)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+async fn exists(conn: &mut SqliteConnection, condition: &str, value: &str) -> Result<bool, String> {
+    let found: Option<(i32,)> = sqlx::query_as(&format!("SELECT 1 FROM clips WHERE {} LIMIT 1", condition))
+        .bind(value)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(found.is_some())
+}
+
+/// Inserts one archived clip unless it's already in history. Image files
+/// written are pushed onto `written` so a failed import can remove them.
+async fn import_clip(conn: &mut SqliteConnection, clip: &ArchivedClip, base_dir: &Path, images_dir: &Path, written: &mut Vec<PathBuf>) -> Result<bool, String> {
+    if exists(conn, "id = ?", &clip.id).await? {
+        return Ok(false);
+    }
+
+    let mut image = None;
+    if clip.clip_type == "image" {
+        let bytes = archived_image(clip, base_dir)?;
+        let img = image::load_from_memory(&bytes).map_err(|e| e.to_string())?.into_rgba8();
+        let hash = images::content_hash(img.width(), img.height(), img.as_raw());
+        if exists(conn, "content_hash = ?", &hash).await? {
+            return Ok(false);
+        }
+        let file_path = images_dir.join(format!("{}.png", Uuid::new_v4()));
+        written.push(file_path.clone());
+        images::save_png(&file_path, &img)?;
+        image = Some((file_path, images::image_meta(&img), hash));
+    } else if clip.content.trim().is_empty() || exists(conn, "content = ?", &clip.content).await? {
+        return Ok(false);
+    }
+
+    let collection = match clip.collection.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+        Some(name) => Some(collection_id(conn, name).await?),
+        None => None,
+    };
+    let search_content = (image.is_none()).then(|| normalize_text(&clip.content));
+
+    sqlx::query(
+        "INSERT INTO clips (id, content, created_at, is_favorite, search_content, clip_type, image_path, source_url, window_title,
+                            image_width, image_height, dominant_color, content_hash, collection_id, pinned, sort_order, locked)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                 CASE WHEN ? THEN (SELECT COALESCE(MAX(sort_order), -1) + 1 FROM clips WHERE pinned = 1) END, ?)"
+    )
+    .bind(&clip.id)
+    .bind(&clip.content)
+    .bind(&clip.created_at)
+    .bind(clip.is_favorite)
+    .bind(search_content)
+    .bind(&clip.clip_type)
+    .bind(image.as_ref().map(|(path, _, _)| path.to_string_lossy().to_string()))
+    .bind(&clip.source_url)
+    .bind(&clip.window_title)
+    .bind(image.as_ref().map(|(_, meta, _)| meta.width))
+    .bind(image.as_ref().map(|(_, meta, _)| meta.height))
+    .bind(image.as_ref().map(|(_, meta, _)| meta.dominant_color.clone()))
+    .bind(match &image {
+        Some((_, _, hash)) => hash.clone(),
+        None => capture::text_hash(&clip.content),
+    })
+    .bind(collection)
+    .bind(clip.pinned)
+    .bind(clip.pinned)
+    .bind(clip.locked)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    for name in clip.tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        let tag_id = tags::tag_id(conn, name).await?;
+        sqlx::query("INSERT OR IGNORE INTO clip_tags (clip_id, tag_id) VALUES (?, ?)")
+            .bind(&clip.id)
+            .bind(&tag_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(true)
+}
+
+/// Adds an archive's clips to history, all or nothing. Clips already in
+/// history (same id, same text, or same image) are skipped. Bundled image
+/// files are looked up relative to `base_dir`.
+pub async fn import_archive(app: &AppHandle, archive: &Archive, base_dir: &Path) -> Result<ImportReport, String> {
+    let state = app.try_state::<DbState>().ok_or_else(|| "Database is not available".to_string())?;
+    let images_dir = app_data_dir(app).join("images");
+    std::fs::create_dir_all(&images_dir).map_err(|e| e.to_string())?;
+
+    let mut report = ImportReport { total: archive.clips.len(), ..Default::default() };
+    let mut written = Vec::new();
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    let mut result = Ok(());
+    for clip in &archive.clips {
+        match import_clip(&mut tx, clip, base_dir, &images_dir, &mut written).await {
+            Ok(true) => report.imported += 1,
+            Ok(false) => report.skipped += 1,
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        }
+    }
+    if result.is_ok() {
+        result = tx.commit().await.map_err(|e| e.to_string());
+    }
+    if let Err(e) = result {
+        for file in &written {
+            let _ = std::fs::remove_file(file);
+        }
+        return Err(e);
+    }
+
+    state.recent.invalidate();
+    let _ = app.emit("clipboard-changed", ());
+    Ok(report)
+}
+
+/// Imports a JSON archive written by `export_clips`.
+#[tauri::command]
+pub async fn import_clips(app_handle: AppHandle, path: String) -> Result<ImportReport, String> {
+    let data = std::fs::read(&path).map_err(|e| e.to_string())?;
+    let base_dir = Path::new(&path).parent().map(Path::to_path_buf).unwrap_or_default();
+    import_archive(&app_handle, &parse_archive(&data)?, &base_dir).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_plain_relative_files() {
+        assert!(is_relative_file("image.png"));
+        assert!(is_relative_file("images/image.png"));
+    }
+
+    #[test]
+    fn rejects_paths_leaving_the_folder() {
+        for file in ["", "/etc/passwd", "../image.png", "images/../../image.png", "./image.png"] {
+            assert!(!is_relative_file(file), "{}", file);
+        }
+    }
+}
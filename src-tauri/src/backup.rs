@@ -0,0 +1,436 @@
+// Nightly off-device backups: the whole history as a JSON archive (images
+// embedded), encrypted with the backup passphrase from the keychain, uploaded
+// to a folder, a WebDAV collection or an S3 bucket. Only the newest
+// `backup_keep` backups are kept at the destination. Single clips can be
+// browsed and restored from a backup, or from a plain JSON export.
+
+use tauri::AppHandle;
+use sqlx::{Pool, Sqlite};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use hmac::{Hmac, Mac};
+use regex::Regex;
+
+use crate::archive::{build_archive, import_archive, parse_archive, Archive};
+use crate::export::ExportFilter;
+use crate::importers::ImportReport;
+use crate::{crypto, keychain, setting_enabled, setting_value, DbState};
+
+pub const DESTINATIONS: &[&str] = &["folder", "webdav", "s3"];
+pub const DEFAULT_KEEP: usize = 7;
+const FILE_PREFIX: &str = "klip-backup-";
+const FILE_SUFFIX: &str = ".klipbak";
+const NAME_TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+// A stalled server must not hold up the scheduler; the whole-request limit
+// leaves room for uploading a large history
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+// Keychain entries
+pub const PASSPHRASE_SECRET: &str = "backup_passphrase";
+pub const PASSWORD_SECRET: &str = "backup_password"; // WebDAV password or S3 secret key
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BackupConfig {
+    pub enabled: bool,
+    pub destination: Option<String>, // one of DESTINATIONS
+    pub location: Option<String>,    // folder path, WebDAV collection URL or S3 bucket
+    pub username: Option<String>,    // WebDAV user or S3 access key id
+    pub region: Option<String>,      // S3 only
+    pub endpoint: Option<String>,    // S3-compatible services; AWS when empty
+    pub keep: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupEntry {
+    name: String,
+    created_at: Option<String>, // ISO 8601, from the file name
+}
+
+/// A clip inside a backup, without its image data.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupClip {
+    id: String,
+    content: String,
+    created_at: String, // ISO 8601
+    clip_type: String,
+    is_favorite: bool,
+    tags: Vec<String>,
+    in_history: bool, // still present, so restoring it would be skipped
+}
+
+pub async fn load_config(pool: &Pool<Sqlite>) -> BackupConfig {
+    let value = |key: &'static str| async move { setting_value(pool, key).await.filter(|v| !v.is_empty()) };
+    BackupConfig {
+        enabled: setting_enabled(pool, "backup_enabled", false).await,
+        destination: value("backup_destination").await,
+        location: value("backup_location").await,
+        username: value("backup_username").await,
+        region: value("backup_s3_region").await,
+        endpoint: value("backup_s3_endpoint").await,
+        keep: value("backup_keep").await.and_then(|v| v.parse().ok()),
+    }
+}
+
+pub struct S3Bucket {
+    endpoint: reqwest::Url,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+}
+
+/// Where files go; shared with sync.rs, which keeps its changelogs in a
+/// folder or WebDAV collection the same way.
+pub enum Destination {
+    Folder(PathBuf),
+    WebDav { url: String, username: Option<String>, password: Option<String> },
+    S3(S3Bucket),
+}
+
+fn destination(config: &BackupConfig) -> Result<Destination, String> {
+    let location = config.location.clone().ok_or_else(|| "No backup location configured".to_string())?;
+    match config.destination.as_deref() {
+        Some("folder") => Ok(Destination::Folder(PathBuf::from(location))),
+        Some("webdav") => Ok(Destination::WebDav {
+            url: location.trim_end_matches('/').to_string(),
+            username: config.username.clone(),
+            password: keychain::get_secret(PASSWORD_SECRET)?,
+        }),
+        Some("s3") => {
+            let region = config.region.clone().unwrap_or_else(|| "us-east-1".to_string());
+            let endpoint = config.endpoint.clone().unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", region));
+            Ok(Destination::S3(S3Bucket {
+                endpoint: reqwest::Url::parse(&endpoint).map_err(|e| e.to_string())?,
+                bucket: location,
+                region,
+                access_key: config.username.clone().ok_or_else(|| "No S3 access key configured".to_string())?,
+                secret_key: keychain::get_secret(PASSWORD_SECRET)?.ok_or_else(|| "No S3 secret key configured".to_string())?,
+            }))
+        }
+        Some(other) => Err(format!("Unknown backup destination: {}", other)),
+        None => Err("No backup destination configured".to_string()),
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+impl S3Bucket {
+    /// Path-style request signed with AWS Signature Version 4. `query` must
+    /// already be in canonical form (sorted, encoded).
+    fn request(&self, method: reqwest::Method, key: &str, query: &str, body: Vec<u8>) -> Result<reqwest::RequestBuilder, String> {
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let host = match self.endpoint.port() {
+            Some(port) => format!("{}:{}", self.endpoint.host_str().unwrap_or_default(), port),
+            None => self.endpoint.host_str().unwrap_or_default().to_string(),
+        };
+        let path = if key.is_empty() { format!("/{}", self.bucket) } else { format!("/{}/{}", self.bucket, key) };
+        let payload_hash = hex_sha256(&body);
+
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, path, query, host, payload_hash, amz_date, signed_headers, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, hex_sha256(canonical_request.as_bytes()));
+
+        let mut signing_key = hmac_sha256(format!("AWS4{}", self.secret_key).as_bytes(), &date);
+        for part in [self.region.as_str(), "s3", "aws4_request"] {
+            signing_key = hmac_sha256(&signing_key, part);
+        }
+        let signature: String = hmac_sha256(&signing_key, &string_to_sign).iter().map(|b| format!("{:02x}", b)).collect();
+
+        let mut url = self.endpoint.join(&path).map_err(|e| e.to_string())?;
+        url.set_query(Some(query).filter(|q| !q.is_empty()));
+        Ok(client()?
+            .request(method, url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header(
+                "Authorization",
+                format!("AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}", self.access_key, scope, signed_headers, signature),
+            )
+            .body(body))
+    }
+}
+
+async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, String> {
+    request.send().await.map_err(|e| e.to_string())?.error_for_status().map_err(|e| e.to_string())
+}
+
+fn client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+fn webdav_request(method: reqwest::Method, url: String, username: &Option<String>, password: &Option<String>) -> Result<reqwest::RequestBuilder, String> {
+    let request = client()?.request(method, url);
+    Ok(match username {
+        Some(user) => request.basic_auth(user, password.as_ref()),
+        None => request,
+    })
+}
+
+/// Names of our files (`prefix...suffix`) found in an XML listing (WebDAV
+/// hrefs or S3 keys).
+fn names_in_listing(xml: &str, prefix: &str, suffix: &str) -> Vec<String> {
+    let pattern = Regex::new(r"<(?:[A-Za-z]+:)?(?:href|Key)>([^<]+)</").unwrap();
+    pattern
+        .captures_iter(xml)
+        .filter_map(|c| c[1].trim_end_matches('/').rsplit('/').next().map(str::to_string))
+        .filter(|name| is_named(name, prefix, suffix))
+        .collect()
+}
+
+fn is_named(name: &str, prefix: &str, suffix: &str) -> bool {
+    name.starts_with(prefix) && name.ends_with(suffix) && !name.contains(['/', '\\'])
+}
+
+fn is_backup_name(name: &str) -> bool {
+    is_named(name, FILE_PREFIX, FILE_SUFFIX)
+}
+
+impl Destination {
+    pub async fn put(&self, name: &str, data: Vec<u8>) -> Result<(), String> {
+        match self {
+            Destination::Folder(folder) => {
+                std::fs::create_dir_all(folder).map_err(|e| e.to_string())?;
+                std::fs::write(folder.join(name), data).map_err(|e| e.to_string())
+            }
+            Destination::WebDav { url, username, password } => {
+                send(webdav_request(reqwest::Method::PUT, format!("{}/{}", url, name), username, password)?.body(data)).await?;
+                Ok(())
+            }
+            Destination::S3(bucket) => {
+                send(bucket.request(reqwest::Method::PUT, name, "", data)?).await?;
+                Ok(())
+            }
+        }
+    }
+
+    pub async fn get(&self, name: &str) -> Result<Vec<u8>, String> {
+        let response = match self {
+            Destination::Folder(folder) => return std::fs::read(folder.join(name)).map_err(|e| e.to_string()),
+            Destination::WebDav { url, username, password } => {
+                send(webdav_request(reqwest::Method::GET, format!("{}/{}", url, name), username, password)?).await?
+            }
+            Destination::S3(bucket) => send(bucket.request(reqwest::Method::GET, name, "", Vec::new())?).await?,
+        };
+        response.bytes().await.map(|b| b.to_vec()).map_err(|e| e.to_string())
+    }
+
+    pub async fn delete(&self, name: &str) -> Result<(), String> {
+        match self {
+            Destination::Folder(folder) => std::fs::remove_file(folder.join(name)).map_err(|e| e.to_string()),
+            Destination::WebDav { url, username, password } => {
+                send(webdav_request(reqwest::Method::DELETE, format!("{}/{}", url, name), username, password)?).await?;
+                Ok(())
+            }
+            Destination::S3(bucket) => {
+                send(bucket.request(reqwest::Method::DELETE, name, "", Vec::new())?).await?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Names at the destination that start with `prefix` and end with
+    /// `suffix`, sorted (oldest first for our timestamped names).
+    pub async fn list(&self, prefix: &str, suffix: &str) -> Result<Vec<String>, String> {
+        let mut names = match self {
+            Destination::Folder(folder) => {
+                if !folder.exists() {
+                    return Ok(Vec::new());
+                }
+                std::fs::read_dir(folder)
+                    .map_err(|e| e.to_string())?
+                    .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                    .filter(|name| is_named(name, prefix, suffix))
+                    .collect()
+            }
+            Destination::WebDav { url, username, password } => {
+                let propfind = reqwest::Method::from_bytes(b"PROPFIND").map_err(|e| e.to_string())?;
+                let request = webdav_request(propfind, format!("{}/", url), username, password)?.header("Depth", "1");
+                names_in_listing(&send(request).await?.text().await.map_err(|e| e.to_string())?, prefix, suffix)
+            }
+            Destination::S3(bucket) => {
+                // A single page (1000 keys) is plenty for a handful of kept backups
+                let query = format!("list-type=2&prefix={}", prefix);
+                let response = send(bucket.request(reqwest::Method::GET, "", &query, Vec::new())?).await?;
+                names_in_listing(&response.text().await.map_err(|e| e.to_string())?, prefix, suffix)
+            }
+        };
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+}
+
+fn passphrase() -> Result<String, String> {
+    keychain::get_secret(PASSPHRASE_SECRET)?
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| "Set a backup passphrase first".to_string())
+}
+
+fn created_at(name: &str) -> Option<String> {
+    let stamp = name.strip_prefix(FILE_PREFIX)?.strip_suffix(FILE_SUFFIX)?;
+    NaiveDateTime::parse_from_str(stamp, NAME_TIME_FORMAT).ok().map(|t| t.and_utc().to_rfc3339())
+}
+
+/// Uploads a new backup and prunes the oldest beyond `backup_keep`.
+/// Returns the backup's name.
+pub async fn run_backup(pool: &Pool<Sqlite>) -> Result<String, String> {
+    let config = load_config(pool).await;
+    let destination = destination(&config)?;
+    let passphrase = passphrase()?;
+
+    let archive = build_archive(pool, &ExportFilter::default(), None).await?;
+    let json = serde_json::to_vec(&archive).map_err(|e| e.to_string())?;
+    let name = format!("{}{}{}", FILE_PREFIX, Utc::now().format(NAME_TIME_FORMAT), FILE_SUFFIX);
+    destination.put(&name, crypto::encrypt(&passphrase, &json)?).await?;
+
+    let names = destination.list(FILE_PREFIX, FILE_SUFFIX).await?;
+    let keep = config.keep.unwrap_or(DEFAULT_KEEP).max(1);
+    for old in names.iter().take(names.len().saturating_sub(keep)) {
+        if let Err(e) = destination.delete(old).await {
+            eprintln!("Failed to remove old backup {}: {}", old, e);
+        }
+    }
+    Ok(name)
+}
+
+#[tauri::command]
+pub async fn get_backup_config(state: tauri::State<'_, DbState>) -> Result<BackupConfig, String> {
+    Ok(load_config(&state.pool).await)
+}
+
+/// Saves the backup settings. The passphrase and WebDAV password / S3 secret
+/// key are stored separately through `set_secret` ("backup_passphrase",
+/// "backup_password").
+#[tauri::command]
+pub async fn set_backup_config(state: tauri::State<'_, DbState>, config: BackupConfig) -> Result<(), String> {
+    let clean = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let destination = clean(config.destination);
+    if let Some(bad) = destination.as_deref().filter(|d| !DESTINATIONS.contains(d)) {
+        return Err(format!("Unknown backup destination: {}", bad));
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    for (key, value) in [
+        ("backup_enabled", Some(config.enabled.to_string())),
+        ("backup_destination", destination),
+        ("backup_location", clean(config.location)),
+        ("backup_username", clean(config.username)),
+        ("backup_s3_region", clean(config.region)),
+        ("backup_s3_endpoint", clean(config.endpoint)),
+        ("backup_keep", config.keep.filter(|k| *k > 0).map(|k| k.to_string())),
+    ] {
+        sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
+            .bind(key)
+            .bind(value.unwrap_or_default())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+/// Backs up right away, whether or not nightly backups are on.
+#[tauri::command]
+pub async fn run_backup_now(state: tauri::State<'_, DbState>) -> Result<String, String> {
+    run_backup(&state.pool).await
+}
+
+/// Backups at the configured destination, newest first.
+#[tauri::command]
+pub async fn list_backups(state: tauri::State<'_, DbState>) -> Result<Vec<BackupEntry>, String> {
+    let destination = destination(&load_config(&state.pool).await)?;
+    let mut names = destination.list(FILE_PREFIX, FILE_SUFFIX).await?;
+    names.reverse();
+    Ok(names
+        .into_iter()
+        .map(|name| BackupEntry { created_at: created_at(&name), name })
+        .collect())
+}
+
+/// Adds the clips of a backup back into history. Clips that are still there
+/// are skipped, so nothing current is overwritten.
+#[tauri::command]
+pub async fn restore_backup(app_handle: AppHandle, state: tauri::State<'_, DbState>, name: String) -> Result<ImportReport, String> {
+    if !is_backup_name(&name) {
+        return Err(format!("Not a backup: {}", name));
+    }
+    let destination = destination(&load_config(&state.pool).await)?;
+    let data = destination.get(&name).await?;
+    let archive = parse_archive(&crypto::decrypt(&passphrase()?, &data)?)?;
+    import_archive(&app_handle, &archive, Path::new("")).await
+}
+
+/// Reads a backup from a local file (a `.klipbak` or a plain JSON export),
+/// or by name from the configured destination. Returns the archive and the
+/// folder bundled images are relative to.
+async fn open_backup(pool: &Pool<Sqlite>, path: &str) -> Result<(Archive, PathBuf), String> {
+    let local = Path::new(path);
+    let (data, base_dir) = if local.is_file() {
+        let data = std::fs::read(local).map_err(|e| e.to_string())?;
+        (data, local.parent().map(Path::to_path_buf).unwrap_or_default())
+    } else if is_backup_name(path) {
+        let destination = destination(&load_config(pool).await)?;
+        (destination.get(path).await?, PathBuf::new())
+    } else {
+        return Err(format!("Backup not found: {}", path));
+    };
+
+    let data = if crypto::is_encrypted(&data) { crypto::decrypt(&passphrase()?, &data)? } else { data };
+    Ok((parse_archive(&data)?, base_dir))
+}
+
+/// Lists the clips in a backup, newest first, marking those still in history.
+#[tauri::command]
+pub async fn browse_backup(state: tauri::State<'_, DbState>, path: String) -> Result<Vec<BackupClip>, String> {
+    let (archive, _) = open_backup(&state.pool, &path).await?;
+    let mut clips = Vec::with_capacity(archive.clips.len());
+    for clip in archive.clips.into_iter().rev() {
+        let in_history: Option<(i32,)> = sqlx::query_as("SELECT 1 FROM clips WHERE id = ?")
+            .bind(&clip.id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        clips.push(BackupClip {
+            id: clip.id,
+            content: clip.content,
+            created_at: clip.created_at,
+            clip_type: clip.clip_type,
+            is_favorite: clip.is_favorite,
+            tags: clip.tags,
+            in_history: in_history.is_some(),
+        });
+    }
+    Ok(clips)
+}
+
+/// Adds just the chosen clips of a backup back into history.
+#[tauri::command]
+pub async fn restore_clips_from_backup(app_handle: AppHandle, state: tauri::State<'_, DbState>, path: String, ids: Vec<String>) -> Result<ImportReport, String> {
+    let (mut archive, base_dir) = open_backup(&state.pool, &path).await?;
+    archive.clips.retain(|clip| ids.contains(&clip.id));
+    if archive.clips.is_empty() {
+        return Err("None of the clips are in this backup".to_string());
+    }
+    import_archive(&app_handle, &archive, &base_dir).await
+}
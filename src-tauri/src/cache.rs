@@ -0,0 +1,186 @@
+// In-memory window of the most recent clips, newest first.
+// Serves get_recent_clips and dedup checks without touching SQLite in the common case.
+// Paths that insert clips in bulk or in unusual ways just call `invalidate`;
+// the window is reloaded lazily on next use. Updates and deletes are caught
+// by a hook on every pooled connection, so no writer can leave it stale.
+
+use sqlx::sqlite::{SqliteConnection, SqliteOperation};
+use sqlx::{Pool, Sqlite};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use chrono::prelude::*;
+
+use crate::{Clip, CLIP_COLUMNS};
+
+pub const DEFAULT_CAPACITY: usize = 200;
+
+// Bumped whenever a transaction that updated or deleted clips commits
+static DATA_VERSION: AtomicU64 = AtomicU64::new(0);
+
+/// Installs the hooks that bump the data version; run on each new connection.
+pub async fn watch_changes(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    let mut handle = conn.lock_handle().await?;
+    let changed = Arc::new(AtomicBool::new(false));
+
+    let on_change = changed.clone();
+    handle.set_update_hook(move |change| {
+        if change.table == "clips" && !matches!(change.operation, SqliteOperation::Insert) {
+            on_change.store(true, Ordering::Relaxed);
+        }
+    });
+    let on_commit = changed.clone();
+    handle.set_commit_hook(move || {
+        if on_commit.swap(false, Ordering::Relaxed) {
+            DATA_VERSION.fetch_add(1, Ordering::Relaxed);
+        }
+        false
+    });
+    handle.set_rollback_hook(move || changed.store(false, Ordering::Relaxed));
+    Ok(())
+}
+
+#[derive(Default)]
+struct Inner {
+    capacity: usize,
+    clips: VecDeque<Clip>,
+    loaded: bool,
+    // DATA_VERSION the window was loaded at
+    version: u64,
+    // True when the whole table fit in the window at load time
+    complete: bool,
+}
+
+pub struct RecentCache {
+    inner: Mutex<Inner>,
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value).ok().map(|d| d.with_timezone(&Utc))
+}
+
+impl RecentCache {
+    pub fn new(capacity: usize) -> Self {
+        RecentCache { inner: Mutex::new(Inner { capacity, ..Default::default() }) }
+    }
+
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.capacity = capacity;
+        inner.loaded = false;
+        inner.clips.clear();
+    }
+
+    pub fn invalidate(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.loaded = false;
+        inner.clips.clear();
+    }
+
+    async fn ensure_loaded(&self, pool: &Pool<Sqlite>) -> Result<(), String> {
+        let capacity = {
+            let inner = self.inner.lock().unwrap();
+            if inner.capacity == 0 || (inner.loaded && inner.version == DATA_VERSION.load(Ordering::Relaxed)) {
+                return Ok(());
+            }
+            inner.capacity
+        };
+
+        let version = DATA_VERSION.load(Ordering::Relaxed);
+        let query = format!("SELECT {} FROM clips WHERE deleted_at IS NULL ORDER BY created_at DESC, id DESC LIMIT ?", CLIP_COLUMNS);
+        let rows = sqlx::query_as::<_, Clip>(&query)
+            .bind(capacity as i64)
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.complete = rows.len() < capacity;
+        inner.clips = rows.into();
+        inner.loaded = true;
+        inner.version = version;
+        Ok(())
+    }
+
+    pub fn insert(&self, clip: Clip) {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.loaded {
+            return;
+        }
+        inner.clips.push_front(clip);
+        if inner.clips.len() > inner.capacity {
+            inner.clips.pop_back();
+            inner.complete = false;
+        }
+    }
+
+    pub fn remove(&self, id: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(pos) = inner.clips.iter().position(|c| c.id == id) {
+            inner.clips.remove(pos);
+            // One fewer row than the window can hold; refill on next use
+            if !inner.complete {
+                inner.loaded = false;
+                inner.clips.clear();
+            }
+        }
+    }
+
+    pub fn update(&self, id: &str, apply: impl FnOnce(&mut Clip)) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(clip) = inner.clips.iter_mut().find(|c| c.id == id) {
+            apply(clip);
+        }
+    }
+
+    /// Newest `limit` clips, or None when the cache can't answer (disabled or too small).
+    pub async fn recent(&self, pool: &Pool<Sqlite>, limit: usize) -> Result<Option<Vec<Clip>>, String> {
+        self.ensure_loaded(pool).await?;
+        let inner = self.inner.lock().unwrap();
+        if !inner.loaded || (limit > inner.clips.len() && !inner.complete) {
+            return Ok(None);
+        }
+        Ok(Some(inner.clips.iter().take(limit).cloned().collect()))
+    }
+
+    /// Dedup lookup: Some(found) when the cached window covers everything
+    /// since `since` (None = all time), otherwise None and the caller asks SQLite.
+    pub async fn contains_content(&self, pool: &Pool<Sqlite>, content: &str, since: Option<DateTime<Utc>>) -> Result<Option<bool>, String> {
+        self.ensure_loaded(pool).await?;
+        let inner = self.inner.lock().unwrap();
+        if !inner.loaded {
+            return Ok(None);
+        }
+
+        let covered = inner.complete
+            || match (since, inner.clips.back().and_then(|c| parse_time(&c.created_at))) {
+                (Some(since), Some(oldest)) => oldest <= since,
+                _ => false,
+            };
+        if !covered {
+            return Ok(None);
+        }
+
+        Ok(Some(inner.clips.iter().any(|c| {
+            c.content == content && match since {
+                Some(since) => parse_time(&c.created_at).is_none_or(|t| t >= since),
+                None => true,
+            }
+        })))
+    }
+}
+
+#[tauri::command]
+pub async fn get_recent_clips(state: tauri::State<'_, crate::DbState>, limit: Option<usize>) -> Result<Vec<Clip>, String> {
+    let limit = limit.unwrap_or(20);
+    if let Some(clips) = state.recent.recent(&state.pool, limit).await? {
+        return Ok(clips);
+    }
+
+    let query = format!("SELECT {} FROM clips WHERE deleted_at IS NULL ORDER BY created_at DESC, id DESC LIMIT ?", CLIP_COLUMNS);
+    sqlx::query_as::<_, Clip>(&query)
+        .bind(limit as i64)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,279 @@
+// Capture policy: decides whether a piece of clipboard content should be stored.
+// Dedup applies to every insert; the filters and the ignored-apps list only
+// to automatic captures. Dedup compares SHA-256 content hashes, of the text or
+// of the image pixels (see `images::content_hash`).
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager};
+use sqlx::{Pool, Sqlite};
+use chrono::prelude::*;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+use crate::foreground::ForegroundWindow;
+use crate::{diagnostics, setting_enabled, setting_value, DbState};
+
+const DEFAULT_DEDUP_WINDOW: DedupWindow = DedupWindow::Within(chrono::Duration::days(1));
+// Bound to (hash, content). Rows whose hash isn't filled in yet (edited since,
+// or stored by an import) are compared by content.
+pub const TEXT_DUPLICATE: &str = "(content_hash = ? OR (content_hash IS NULL AND content = ?))";
+
+// While paused the monitor keeps watching but stores nothing; persisted as `capture_paused`
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+pub fn restore_paused(paused: bool) {
+    PAUSED.store(paused, Ordering::Relaxed);
+}
+
+/// Pauses or resumes automatic capture and tells the UI via "capture-paused".
+pub async fn set_paused(app: &AppHandle, paused: bool) -> Result<(), String> {
+    let state = app.try_state::<DbState>().ok_or_else(|| "Database is not available".to_string())?;
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES ('capture_paused', ?)")
+        .bind(paused.to_string())
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    PAUSED.store(paused, Ordering::Relaxed);
+    let _ = app.emit("capture-paused", paused);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_capture_paused(app_handle: AppHandle, paused: bool) -> Result<(), String> {
+    set_paused(&app_handle, paused).await
+}
+
+#[tauri::command]
+pub fn is_capture_paused() -> bool {
+    is_paused()
+}
+
+/// How far back to look for identical content before storing a new clip.
+/// Stored in settings as `dedup_window`: "30m", "12h", "7d" or "forever".
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DedupWindow {
+    Within(chrono::Duration),
+    Forever,
+}
+
+impl DedupWindow {
+    pub fn parse(value: &str) -> Option<DedupWindow> {
+        let value = value.trim().to_lowercase();
+        if value == "forever" {
+            return Some(DedupWindow::Forever);
+        }
+
+        let (split, _) = value.char_indices().last()?;
+        let (amount, unit) = value.split_at(split);
+        let amount: i64 = amount.parse().ok().filter(|n| *n > 0)?;
+        let duration = match unit {
+            "m" => chrono::Duration::try_minutes(amount),
+            "h" => chrono::Duration::try_hours(amount),
+            "d" => chrono::Duration::try_days(amount),
+            _ => None,
+        }?;
+        Some(DedupWindow::Within(duration))
+    }
+
+    /// Start of the window; None for "forever" or a window reaching past the earliest date.
+    pub fn since(&self) -> Option<DateTime<Utc>> {
+        match self {
+            DedupWindow::Within(duration) => Utc::now().checked_sub_signed(*duration),
+            DedupWindow::Forever => None,
+        }
+    }
+}
+
+pub async fn dedup_window(pool: &Pool<Sqlite>) -> DedupWindow {
+    setting_value(pool, "dedup_window")
+        .await
+        .and_then(|v| DedupWindow::parse(&v))
+        .unwrap_or(DEFAULT_DEDUP_WINDOW)
+}
+
+/// True if the same content was already stored inside the configured window.
+/// Uses julianday() so legacy "YYYY-MM-DD HH:MM:SS" rows compare correctly with RFC 3339 ones.
+pub async fn is_duplicate(state: &DbState, content: &str) -> Result<bool, String> {
+    let since = dedup_window(&state.pool).await.since();
+    if let Some(found) = state.recent.contains_content(&state.pool, content, since).await? {
+        return Ok(found);
+    }
+    let hash = text_hash(content);
+    diagnostics::timed("is_duplicate", find_duplicate(&state.pool, TEXT_DUPLICATE, &[&hash, content])).await
+}
+
+/// Same as `is_duplicate`, for images compared by content hash.
+pub async fn is_duplicate_image(pool: &Pool<Sqlite>, hash: &str) -> Result<bool, String> {
+    diagnostics::timed("is_duplicate_image", find_duplicate(pool, "content_hash = ?", &[hash])).await
+}
+
+async fn find_duplicate(pool: &Pool<Sqlite>, condition: &str, values: &[&str]) -> Result<bool, String> {
+    let since = dedup_window(pool).await.since();
+    let sql = match since {
+        None => format!("SELECT 1 FROM clips WHERE {} AND deleted_at IS NULL LIMIT 1", condition),
+        Some(_) => format!("SELECT 1 FROM clips WHERE {} AND deleted_at IS NULL AND julianday(created_at) >= julianday(?) LIMIT 1", condition),
+    };
+    let mut query = sqlx::query_as::<_, (i32,)>(&sql);
+    for value in values {
+        query = query.bind(*value);
+    }
+    if let Some(since) = since {
+        query = query.bind(since.to_rfc3339());
+    }
+    let exists = query.fetch_optional(pool).await.map_err(|e| e.to_string())?;
+
+    Ok(exists.is_some())
+}
+
+/// SHA-256 of the text, hex encoded; stored in `content_hash` for text clips.
+pub fn text_hash(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+/// Fills in `content_hash` for text clips that don't have one: clips from
+/// before text was hashed, edited since (a trigger clears the hash) or
+/// inserted without it. Run at startup.
+pub async fn backfill_text_hashes(pool: &Pool<Sqlite>) -> Result<(), String> {
+    loop {
+        let rows: Vec<(String, String)> = sqlx::query_as("SELECT id, content FROM clips WHERE clip_type IN ('text', 'url') AND content_hash IS NULL LIMIT 500")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+        for (id, content) in &rows {
+            sqlx::query("UPDATE clips SET content_hash = ? WHERE id = ?")
+                .bind(text_hash(content))
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        tx.commit().await.map_err(|e| e.to_string())?;
+    }
+}
+
+/// Captures shorter than `min_clip_length` characters (trimmed) are ignored,
+/// unless they match the optional `min_length_allow_pattern` regex.
+async fn is_too_short(pool: &Pool<Sqlite>, content: &str) -> bool {
+    let min_length: usize = match setting_value(pool, "min_clip_length").await.and_then(|v| v.parse().ok()) {
+        Some(n) if n > 0 => n,
+        _ => return false,
+    };
+
+    if content.trim().chars().count() >= min_length {
+        return false;
+    }
+
+    let allowed = setting_value(pool, "min_length_allow_pattern")
+        .await
+        .filter(|p| !p.is_empty())
+        .and_then(|p| Regex::new(&p).ok())
+        .is_some_and(|re| re.is_match(content.trim()));
+    !allowed
+}
+
+/// Noise categories produced by selection-copy habits, each toggleable in settings.
+async fn is_noise(pool: &Pool<Sqlite>, content: &str) -> bool {
+    let trimmed = content.trim();
+
+    // Whitespace-only captures were always dropped; keep that as the default
+    if trimmed.is_empty() {
+        return setting_enabled(pool, "ignore_whitespace_only", true).await;
+    }
+    if trimmed.chars().all(|c| c.is_ascii_digit() || c.is_whitespace())
+        && setting_enabled(pool, "ignore_numeric_only", false).await
+    {
+        return true;
+    }
+    if trimmed.chars().all(|c| c.is_ascii_punctuation() || c.is_whitespace())
+        && setting_enabled(pool, "ignore_punctuation_only", false).await
+    {
+        return true;
+    }
+    false
+}
+
+/// Capture filters applied by the clipboard monitor. Manual `add_clip` bypasses these.
+pub async fn should_capture(pool: &Pool<Sqlite>, content: &str) -> bool {
+    !is_noise(pool, content).await && !is_too_short(pool, content).await
+}
+
+/// Apps whose copies are never recorded, stored as a JSON array in `ignored_apps`.
+pub async fn ignored_apps(pool: &Pool<Sqlite>) -> Vec<String> {
+    setting_value(pool, "ignored_apps")
+        .await
+        .and_then(|v| serde_json::from_str(&v).ok())
+        .unwrap_or_default()
+}
+
+/// ".exe" is optional so the same entry works for "KeePass" and "KeePass.exe".
+fn app_key(app: &str) -> String {
+    let app = app.trim().to_lowercase();
+    app.strip_suffix(".exe").map(str::to_string).unwrap_or(app)
+}
+
+/// Whether the foreground app matches an entry by bundle id, executable or
+/// display name, case-insensitively.
+pub async fn is_ignored_app(pool: &Pool<Sqlite>, window: Option<&ForegroundWindow>) -> bool {
+    let Some(window) = window else { return false };
+    let ignored = ignored_apps(pool).await;
+    if ignored.is_empty() {
+        return false;
+    }
+    [&window.app_id, &window.app_name]
+        .into_iter()
+        .flatten()
+        .map(|app| app_key(app))
+        .any(|app| ignored.iter().any(|entry| app_key(entry) == app))
+}
+
+#[tauri::command]
+pub async fn get_ignored_apps(state: tauri::State<'_, DbState>) -> Result<Vec<String>, String> {
+    Ok(ignored_apps(&state.pool).await)
+}
+
+/// Replaces the ignored-apps list: bundle ids ("com.agilebits.onepassword7"),
+/// executable names ("KeePass.exe") or app names as shown by the OS.
+#[tauri::command]
+pub async fn set_ignored_apps(state: tauri::State<'_, DbState>, apps: Vec<String>) -> Result<Vec<String>, String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for app in apps.iter().map(|a| a.trim()).filter(|a| !a.is_empty()) {
+        if !cleaned.iter().any(|c| app_key(c) == app_key(app)) {
+            cleaned.push(app.to_string());
+        }
+    }
+
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES ('ignored_apps', ?)")
+        .bind(serde_json::to_string(&cleaned).map_err(|e| e.to_string())?)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(cleaned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dedup_windows() {
+        assert_eq!(DedupWindow::parse("30m"), Some(DedupWindow::Within(chrono::Duration::minutes(30))));
+        assert_eq!(DedupWindow::parse(" 12H "), Some(DedupWindow::Within(chrono::Duration::hours(12))));
+        assert_eq!(DedupWindow::parse("7d"), Some(DedupWindow::Within(chrono::Duration::days(7))));
+        assert_eq!(DedupWindow::parse("Forever"), Some(DedupWindow::Forever));
+    }
+
+    #[test]
+    fn rejects_odd_dedup_windows() {
+        for value in ["", "d", "0d", "-1h", "5", "5w", "1é", "é", "9999999999999999d"] {
+            assert_eq!(DedupWindow::parse(value), None, "{:?}", value);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+// Debug recording of clipboard capture events, for "Klip missed my copy" reports.
+// Events are anonymized: sizes, formats and outcomes only, never content.
+// Off by default; enabled with the `capture_debug` setting.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::DbState;
+
+const CAPACITY: usize = 1000;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static EVENTS: Mutex<VecDeque<CaptureEvent>> = Mutex::new(VecDeque::new());
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CaptureEvent {
+    at: String, // ISO 8601
+    format: String, // "text", "image" or "files"
+    bytes: usize,
+    lines: usize,
+    outcome: String, // "saved", "duplicate", "filtered", "own_window", "paused", "outside_schedule", "rate_limited" or "insert_failed"
+    elapsed_ms: u64, // time from seeing the change to the outcome
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Records a text capture event; a no-op unless debug recording is on.
+pub fn record_text(text: &str, outcome: &str, elapsed_ms: u64) {
+    record("text", text.len(), text.lines().count(), outcome, elapsed_ms);
+}
+
+/// Records an image capture event; `bytes` is the raw RGBA size.
+pub fn record_image(bytes: usize, outcome: &str, elapsed_ms: u64) {
+    record("image", bytes, 0, outcome, elapsed_ms);
+}
+
+/// Records a copied-files event; `count` is the number of files.
+pub fn record_files(count: usize, outcome: &str, elapsed_ms: u64) {
+    record("files", 0, count, outcome, elapsed_ms);
+}
+
+fn record(format: &str, bytes: usize, lines: usize, outcome: &str, elapsed_ms: u64) {
+    if !is_enabled() {
+        return;
+    }
+
+    let event = CaptureEvent {
+        at: Utc::now().to_rfc3339(),
+        format: format.to_string(),
+        bytes,
+        lines,
+        outcome: outcome.to_string(),
+        elapsed_ms,
+    };
+
+    let mut events = EVENTS.lock().unwrap();
+    if events.len() == CAPACITY {
+        events.pop_front();
+    }
+    events.push_back(event);
+}
+
+#[tauri::command]
+pub async fn set_capture_debug(state: tauri::State<'_, DbState>, enabled: bool) -> Result<(), String> {
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES ('capture_debug', ?)")
+        .bind(enabled.to_string())
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    set_enabled(enabled);
+    if !enabled {
+        EVENTS.lock().unwrap().clear();
+    }
+    Ok(())
+}
+
+/// Recorded events, oldest first.
+#[tauri::command]
+pub fn get_capture_events() -> Vec<CaptureEvent> {
+    EVENTS.lock().unwrap().iter().cloned().collect()
+}
+
+/// Writes the recorded events to `path` as JSON. Returns the event count.
+#[tauri::command]
+pub fn export_capture_events(path: String) -> Result<usize, String> {
+    let events = get_capture_events();
+    let json = serde_json::to_string_pretty(&events).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())?;
+    Ok(events.len())
+}
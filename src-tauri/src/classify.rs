@@ -0,0 +1,195 @@
+// Lightweight, heuristic content detection for text clips.
+// Nothing here should be expensive: it runs on every capture.
+
+use sqlx::{Pool, Sqlite};
+
+// Longest clip checked for single-value kinds (an address, a number, a color)
+const MAX_VALUE_LENGTH: usize = 2048;
+
+const CODE_MARKERS: &[&str] = &[
+    "fn ", "let ", "const ", "function ", "def ", "class ", "import ", "#include",
+    "return ", "=> ", "->", "SELECT ", "INSERT ", "UPDATE ", "</", "/>",
+];
+
+pub fn is_url(text: &str) -> bool {
+    let trimmed = text.trim();
+    (trimmed.starts_with("http://") || trimmed.starts_with("https://"))
+        && !trimmed.chars().any(|c| c.is_whitespace() || c == '"')
+}
+
+pub fn looks_like_code(text: &str) -> bool {
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    if lines.is_empty() {
+        return false;
+    }
+
+    let structural = lines
+        .iter()
+        .filter(|l| {
+            let t = l.trim_end();
+            t.ends_with(';') || t.ends_with('{') || t.ends_with('}') || (t.ends_with(':') && l.starts_with(char::is_whitespace))
+        })
+        .count();
+    let markers = CODE_MARKERS.iter().filter(|m| text.contains(*m)).count();
+
+    // A single marker in prose ("return to sender") is common; require more evidence.
+    (structural * 2 >= lines.len() && (lines.len() > 1 || markers > 0))
+        || (markers >= 2 && (structural > 0 || lines.len() > 1))
+}
+
+/// Type a text capture is stored as: "url" for a bare link, "text" otherwise.
+pub fn text_clip_type(content: &str) -> &'static str {
+    if is_url(content) { "url" } else { "text" }
+}
+
+/// Whether clips of this type hold plain text (links included).
+pub fn is_text_type(clip_type: &str) -> bool {
+    matches!(clip_type, "text" | "url")
+}
+
+/// Broad bucket used by the "today at a glance" summary.
+pub fn summary_kind(clip_type: &str, content: &str) -> &'static str {
+    if clip_type == "image" {
+        "image"
+    } else if clip_type == "files" {
+        "files"
+    } else if clip_type == "url" || is_url(content) {
+        "link"
+    } else if looks_like_code(content) {
+        "code"
+    } else {
+        "text"
+    }
+}
+
+fn is_email(text: &str) -> bool {
+    let Some((local, domain)) = text.split_once('@') else { return false };
+    !local.is_empty()
+        && !domain.contains('@')
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !text.chars().any(|c| c.is_whitespace() || matches!(c, '<' | '>' | '"' | ',' | ';' | '/'))
+}
+
+fn is_phone(text: &str) -> bool {
+    let digits = text.chars().filter(|c| c.is_ascii_digit()).count();
+    // A bare run of digits is as likely an order number; dates have the same shape
+    let formatted = text.starts_with('+') || text.contains(['-', '(', '.', ' ']);
+    let date = text.split(['-', '.', '/']).count() == 3 && text.split(['-', '.', '/']).any(|part| part.len() == 4);
+    (7..=15).contains(&digits)
+        && formatted
+        && !date
+        && text.starts_with(|c: char| c == '+' || c == '(' || c.is_ascii_digit())
+        && text.chars().all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '(' | ')' | '.' | ' '))
+}
+
+fn is_hex_color(text: &str) -> bool {
+    text.strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn is_json(text: &str) -> bool {
+    let structured = (text.starts_with('{') && text.ends_with('}')) || (text.starts_with('[') && text.ends_with(']'));
+    structured && serde_json::from_str::<serde_json::Value>(text).is_ok()
+}
+
+fn is_path(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    let unix = (text.starts_with('/') && !text.starts_with("//") && text.len() > 1) || text.starts_with("~/");
+    let windows = (bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && matches!(bytes[2], b'\\' | b'/'))
+        || text.starts_with("\\\\");
+    (unix || windows) && !text.contains('\n') && !text.contains(['<', '>', '"', '|', '*', '?'])
+}
+
+/// Kind stored in `detected_kind` for a text clip: "email", "phone", "url",
+/// "json", "color", "path", "code" or "text". Also the `get_clips` filter values.
+pub fn detect_kind(text: &str) -> &'static str {
+    let trimmed = text.trim();
+    if trimmed.len() <= MAX_VALUE_LENGTH && !trimmed.contains('\n') {
+        if is_url(trimmed) {
+            return "url";
+        }
+        if is_email(trimmed) {
+            return "email";
+        }
+        if is_hex_color(trimmed) {
+            return "color";
+        }
+        if is_phone(trimmed) {
+            return "phone";
+        }
+        if is_path(trimmed) {
+            return "path";
+        }
+    }
+    if is_json(trimmed) {
+        "json"
+    } else if looks_like_code(text) {
+        "code"
+    } else {
+        "text"
+    }
+}
+
+// Languages guessed for code fences, first match wins: (language, markers
+// any of which identify it). Order matters where markers overlap.
+const LANGUAGE_MARKERS: &[(&str, &[&str])] = &[
+    ("rust", &["fn main(", "let mut ", "impl ", "pub fn ", "use std::", "#[derive("]),
+    ("go", &["package main", "func ", ":= "]),
+    ("python", &["def ", "elif ", "import numpy", "print(", "self."]),
+    ("typescript", &["interface ", ": string", ": number", "export type "]),
+    ("javascript", &["function ", "const ", "=> ", "console.log(", "require("]),
+    ("java", &["public class ", "public static void ", "System.out."]),
+    ("c", &["#include", "int main("]),
+    ("sql", &["SELECT ", "INSERT INTO ", "CREATE TABLE ", "UPDATE ", "select ", "insert into "]),
+    ("html", &["<!DOCTYPE", "<div", "</"]),
+    ("bash", &["#!/bin/", "#!/usr/bin/env bash", "$ ", "sudo ", "echo "]),
+];
+
+/// Best guess at the language of a code clip, for the code fence info
+/// string. None when nothing matches; JSON is recognized exactly.
+pub fn guess_language(text: &str) -> Option<&'static str> {
+    let trimmed = text.trim();
+    if is_json(trimmed) {
+        return Some("json");
+    }
+    LANGUAGE_MARKERS
+        .iter()
+        .find(|(_, markers)| markers.iter().any(|m| trimmed.contains(m)))
+        .map(|(language, _)| *language)
+}
+
+/// Fills in `detected_kind` for text clips that don't have one: clips from
+/// before detection existed or inserted without it. Run at startup.
+pub async fn backfill_detected_kinds(pool: &Pool<Sqlite>) -> Result<(), String> {
+    loop {
+        let rows: Vec<(String, String)> = sqlx::query_as("SELECT id, content FROM clips WHERE clip_type IN ('text', 'url') AND detected_kind IS NULL LIMIT 500")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+        for (id, content) in &rows {
+            sqlx::query("UPDATE clips SET detected_kind = ? WHERE id = ?")
+                .bind(detect_kind(content))
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        tx.commit().await.map_err(|e| e.to_string())?;
+    }
+}
+
+/// Host of an http(s) URL without a leading "www.", lowercased.
+pub fn domain_of(url: &str) -> Option<String> {
+    let rest = url.trim().split_once("://")?.1;
+    let host = rest.split(['/', '?', '#']).next()?;
+    let host = host.rsplit_once('@').map(|(_, h)| h).unwrap_or(host);
+    let host = host.split(':').next()?.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host).to_string();
+    if host.is_empty() { None } else { Some(host) }
+}
@@ -0,0 +1,86 @@
+// Subcommands that run without the UI and exit, for scripting:
+//
+//   klip export-jsonl [--output FILE] [--from DATE] [--to DATE]
+//                     [--search TEXT] [--tag NAME]... [--favorites]
+//
+// They read the database directly (read-only), so they work while the app
+// itself is running.
+
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::{Pool, Sqlite};
+use std::fs::File;
+use std::io::BufWriter;
+
+use crate::export::ExportFilter;
+use crate::{encryption, jsonl, keychain, DB_FILENAME};
+
+// Must match `identifier` in tauri.conf.json, which names the app data folder
+const APP_IDENTIFIER: &str = "com.miguelcuevas.klip";
+
+/// Runs the subcommand named in `args` (without the program name) and returns
+/// its exit code, or None to start the app normally.
+pub fn run(args: &[String]) -> Option<i32> {
+    let result = match args.first().map(String::as_str) {
+        Some("export-jsonl") => export_jsonl(&args[1..]),
+        _ => return None,
+    };
+    match result {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("klip: {}", e);
+            Some(1)
+        }
+    }
+}
+
+async fn open_database() -> Result<Pool<Sqlite>, String> {
+    let db_path = dirs::data_dir()
+        .ok_or_else(|| "No data directory on this system".to_string())?
+        .join(APP_IDENTIFIER)
+        .join(DB_FILENAME);
+    if !db_path.exists() {
+        return Err(format!("No history found at {}", db_path.display()));
+    }
+
+    // A pending switch to encryption is only applied by the app, at its next start
+    let key = if encryption::is_encrypted(&db_path) { keychain::get_secret(encryption::DB_ENCRYPTION_KEY)? } else { None };
+    let options = encryption::connect_options(&db_path, key.as_deref())
+        .create_if_missing(false)
+        .read_only(true);
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn export_jsonl(args: &[String]) -> Result<(), String> {
+    let mut filter = ExportFilter::default();
+    let mut output = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--output" | "-o" => output = Some(value()?),
+            "--from" => filter.date_from = Some(value()?),
+            "--to" => filter.date_to = Some(value()?),
+            "--search" => filter.search_text = Some(value()?),
+            "--tag" => filter.tags.get_or_insert_with(Vec::new).push(value()?),
+            "--favorites" => filter.favorites_only = Some(true),
+            other => return Err(format!("Unknown option for export-jsonl: {}", other)),
+        }
+    }
+
+    tauri::async_runtime::block_on(async {
+        let pool = open_database().await?;
+        let result = match output.as_deref() {
+            None | Some("-") => jsonl::write_jsonl(&pool, &filter, &mut BufWriter::new(std::io::stdout().lock())).await,
+            Some(path) => {
+                let file = File::create(path).map_err(|e| e.to_string())?;
+                jsonl::write_jsonl(&pool, &filter, &mut BufWriter::new(file)).await
+            }
+        };
+        pool.close().await;
+        result.map(|_| ())
+    })
+}
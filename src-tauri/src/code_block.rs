@@ -0,0 +1,51 @@
+// Copying a clip as a Markdown code block for Slack, GitHub or Notion: the
+// text is wrapped in a fence tagged with the language, guessed from the
+// content unless one is given.
+
+use arboard::Clipboard;
+
+use crate::classify::{guess_language, is_text_type};
+use crate::{restore, usage, DbState};
+
+/// Fenced block long enough not to be closed by backticks in the content.
+pub fn fenced(content: &str, language: &str) -> String {
+    let mut fence = "```".to_string();
+    while content.contains(fence.as_str()) {
+        fence.push('`');
+    }
+    format!("{}{}\n{}\n{}", fence, language, content.trim_end(), fence)
+}
+
+/// Copies a text clip wrapped in a code fence. `lang` overrides the guessed
+/// language; "none" leaves the fence untagged. Returns the copied text.
+#[tauri::command]
+pub async fn copy_as_code_block(state: tauri::State<'_, DbState>, id: String, lang: Option<String>) -> Result<String, String> {
+    let clip: Option<(String, String)> = sqlx::query_as("SELECT content, clip_type FROM clips WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (content, clip_type) = clip.ok_or_else(|| "Clip not found".to_string())?;
+    if !is_text_type(&clip_type) {
+        return Err("Only text clips can be copied as a code block".to_string());
+    }
+
+    let language = match lang.as_deref().map(str::trim).filter(|l| !l.is_empty()) {
+        Some("none") => String::new(),
+        Some(lang) => {
+            // The info string ends at the line break and can't hold backticks
+            if !lang.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '#' | '-' | '_' | '.')) {
+                return Err(format!("Invalid language: {}", lang));
+            }
+            lang.to_lowercase()
+        }
+        None => guess_language(&content).unwrap_or_default().to_string(),
+    };
+
+    let block = fenced(&content, &language);
+    let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
+    restore::remember_current(&mut clipboard);
+    clipboard.set_text(block.clone()).map_err(|e| e.to_string())?;
+    usage::record_usage(&state.pool, &id, "copy_text").await?;
+    Ok(block)
+}
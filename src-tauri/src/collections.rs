@@ -0,0 +1,139 @@
+// Named collections ("Email templates", "SQL snippets") for clips worth
+// keeping. A clip sits in at most one collection, like a folder, and clips in
+// a collection are kept by retention just like favorites.
+
+use tauri::{AppHandle, Emitter};
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+use crate::{images, DbState};
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Collection {
+    id: String,
+    name: String,
+    created_at: String, // ISO 8601
+    clip_count: i64,
+}
+
+fn clean_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Collection name can't be empty".to_string());
+    }
+    Ok(name)
+}
+
+#[tauri::command]
+pub async fn list_collections(state: tauri::State<'_, DbState>) -> Result<Vec<Collection>, String> {
+    sqlx::query_as::<_, Collection>(
+        "SELECT c.id, c.name, c.created_at, (SELECT COUNT(*) FROM clips WHERE clips.collection_id = c.id AND clips.deleted_at IS NULL) AS clip_count
+         FROM collections c ORDER BY c.name COLLATE NOCASE"
+    )
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_collection(state: tauri::State<'_, DbState>, name: String) -> Result<String, String> {
+    let id = Uuid::new_v4().to_string();
+    sqlx::query("INSERT INTO collections (id, name, created_at) VALUES (?, ?, ?)")
+        .bind(&id)
+        .bind(clean_name(&name)?)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+#[tauri::command]
+pub async fn rename_collection(state: tauri::State<'_, DbState>, id: String, name: String) -> Result<(), String> {
+    let result = sqlx::query("UPDATE collections SET name = ? WHERE id = ?")
+        .bind(clean_name(&name)?)
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if result.rows_affected() == 0 {
+        return Err("Collection not found".to_string());
+    }
+    Ok(())
+}
+
+/// Deletes a collection. Its clips go back to plain history (where retention
+/// applies to them again), or are deleted with it when `delete_clips` is set;
+/// locked clips always stay. Returns how many clips were deleted.
+#[tauri::command]
+pub async fn delete_collection(app_handle: AppHandle, state: tauri::State<'_, DbState>, id: String, delete_clips: Option<bool>) -> Result<usize, String> {
+    let doomed: Vec<(String, Option<String>)> = if delete_clips.unwrap_or(false) {
+        sqlx::query_as("SELECT id, image_path FROM clips WHERE collection_id = ? AND locked = 0")
+            .bind(&id)
+            .fetch_all(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?
+    } else {
+        Vec::new()
+    };
+
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    for (clip_id, _) in &doomed {
+        for statement in [
+            "DELETE FROM clip_usage WHERE clip_id = ?",
+            "DELETE FROM clip_relations WHERE from_id = ?1 OR to_id = ?1",
+            "DELETE FROM clips WHERE id = ?",
+        ] {
+            sqlx::query(statement)
+                .bind(clip_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    for statement in [
+        "UPDATE clips SET collection_id = NULL WHERE collection_id = ?",
+        "DELETE FROM collections WHERE id = ?",
+    ] {
+        sqlx::query(statement)
+            .bind(&id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    for path in doomed.iter().filter_map(|(_, path)| path.as_ref()) {
+        let _ = images::remove_image(path);
+    }
+    state.recent.invalidate();
+    let _ = app_handle.emit("clipboard-changed", ());
+    Ok(doomed.len())
+}
+
+/// Files a clip into a collection, or takes it out of any with `None`.
+#[tauri::command]
+pub async fn set_clip_collection(state: tauri::State<'_, DbState>, id: String, collection_id: Option<String>) -> Result<(), String> {
+    let collection_id = collection_id.filter(|c| !c.is_empty());
+    if let Some(collection_id) = &collection_id {
+        let exists: Option<(i32,)> = sqlx::query_as("SELECT 1 FROM collections WHERE id = ?")
+            .bind(collection_id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        if exists.is_none() {
+            return Err("Collection not found".to_string());
+        }
+    }
+
+    sqlx::query("UPDATE clips SET collection_id = ? WHERE id = ?")
+        .bind(&collection_id)
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    state.recent.update(&id, |clip| clip.collection_id = collection_id);
+    Ok(())
+}
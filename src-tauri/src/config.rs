@@ -0,0 +1,125 @@
+// Configuration as one JSON file, for setting up Klip on another machine:
+// every setting (capture rules, exclusions, focus hours, shortcut, ...) plus
+// projects with their app rules. Per-machine state such as job timestamps
+// and the pause switch stays behind, and secrets stay in the keychain.
+
+use tauri::AppHandle;
+use std::collections::BTreeMap;
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+use crate::{focus, hotkey, settings, window, DbState};
+
+pub const CONFIG_VERSION: u32 = 1;
+
+// Settings that describe this machine's current state rather than preferences
+const LOCAL_KEYS: &[&str] = &["capture_paused", "active_project", "sync_collections", "sync_device_id", "sync_device_name", "journal_written_until"];
+const LOCAL_PREFIXES: &[&str] = &["job_last_run:", "job_last_failure:"];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectConfig {
+    pub name: String,
+    pub apps: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConfigFile {
+    pub version: u32,
+    pub exported_at: String, // ISO 8601
+    pub settings: BTreeMap<String, String>,
+    #[serde(default)]
+    pub projects: Vec<ProjectConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ConfigImportReport {
+    settings: usize,
+    projects: usize,
+    rejected: Vec<String>, // setting keys whose values failed validation
+}
+
+fn is_local(key: &str) -> bool {
+    LOCAL_KEYS.contains(&key) || LOCAL_PREFIXES.iter().any(|prefix| key.starts_with(prefix))
+}
+
+/// Writes the configuration to `path`.
+#[tauri::command]
+pub async fn export_config(state: tauri::State<'_, DbState>, path: String) -> Result<(), String> {
+    let settings: BTreeMap<String, String> = sqlx::query_as::<_, (String, String)>("SELECT key, value FROM settings")
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|(key, _)| !is_local(key))
+        .collect();
+
+    let projects = sqlx::query_as::<_, (String, String)>("SELECT name, apps FROM projects ORDER BY created_at ASC")
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(name, apps)| ProjectConfig { name, apps: serde_json::from_str(&apps).unwrap_or_default() })
+        .collect();
+
+    let config = ConfigFile { version: CONFIG_VERSION, exported_at: Utc::now().to_rfc3339(), settings, projects };
+    let json = serde_json::to_vec_pretty(&config).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())
+}
+
+/// Applies a configuration file. Settings in the file replace the current
+/// values, except ones that fail validation, which are listed in the report
+/// and left as they were; projects are matched by name, taking the file's app rules.
+#[tauri::command]
+pub async fn import_config(app_handle: AppHandle, state: tauri::State<'_, DbState>, path: String) -> Result<ConfigImportReport, String> {
+    let data = std::fs::read(&path).map_err(|e| e.to_string())?;
+    let config: ConfigFile = serde_json::from_slice(&data).map_err(|e| format!("Not a Klip configuration: {}", e))?;
+    if config.version > CONFIG_VERSION {
+        return Err(format!("Configuration version {} is newer than this version of Klip supports", config.version));
+    }
+
+    let mut report = ConfigImportReport::default();
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    for (key, value) in config.settings.iter().filter(|(key, _)| !is_local(key)) {
+        if let Err(e) = settings::validate_value(key, value) {
+            eprintln!("Skipping imported setting: {}", e);
+            report.rejected.push(key.clone());
+            continue;
+        }
+        sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
+            .bind(key)
+            .bind(value)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        report.settings += 1;
+    }
+    for project in config.projects.iter().filter(|p| !p.name.trim().is_empty()) {
+        let apps = serde_json::to_string(&project.apps).map_err(|e| e.to_string())?;
+        let updated = sqlx::query("UPDATE projects SET apps = ? WHERE name = ? COLLATE NOCASE")
+            .bind(&apps)
+            .bind(project.name.trim())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        if updated.rows_affected() == 0 {
+            sqlx::query("INSERT INTO projects (id, name, apps, created_at) VALUES (?, ?, ?, ?)")
+                .bind(Uuid::new_v4().to_string())
+                .bind(project.name.trim())
+                .bind(&apps)
+                .bind(Utc::now().to_rfc3339())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        report.projects += 1;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    // Apply what's cached or registered outside the settings table
+    settings::restore(&state.pool).await;
+    hotkey::restore(&app_handle).await;
+    window::restore_always_on_top(&app_handle).await;
+    focus::evaluate(&app_handle, &state.pool).await;
+    Ok(report)
+}
@@ -0,0 +1,53 @@
+// Opt-in context stored with each capture (capture_context setting): the app,
+// window title and page URL it came from, as a small JSON object per clip.
+// Nothing beyond these fields is ever recorded.
+
+use serde::{Serialize, Deserialize};
+
+use crate::{foreground::ForegroundWindow, DbState};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ClipContext {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Serialized context for a capture, or None when there is nothing to store.
+pub fn context_json(window: Option<&ForegroundWindow>, url: Option<&str>) -> Option<String> {
+    let context = ClipContext {
+        app: window.and_then(|w| w.app_name.clone()),
+        window_title: window.and_then(|w| w.title.clone()),
+        url: url.map(str::to_string),
+    };
+    if context.app.is_none() && context.window_title.is_none() && context.url.is_none() {
+        return None;
+    }
+    serde_json::to_string(&context).ok()
+}
+
+#[tauri::command]
+pub async fn get_clip_context(state: tauri::State<'_, DbState>, id: String) -> Result<Option<ClipContext>, String> {
+    let row: Option<(Option<String>,)> = sqlx::query_as("SELECT context FROM clips WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(row
+        .and_then(|(context,)| context)
+        .and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+/// Drops stored context from every clip, e.g. after turning the option off.
+#[tauri::command]
+pub async fn clear_clip_context(state: tauri::State<'_, DbState>) -> Result<u64, String> {
+    let result = sqlx::query("UPDATE clips SET context = NULL WHERE context IS NOT NULL")
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,76 @@
+// Passphrase encryption for exported files.
+// Layout: MAGIC | salt (16) | nonce (12) | AES-256-GCM ciphertext. The key is
+// derived from the passphrase with Argon2id, so the file is self-contained.
+
+use aes_gcm::aead::{rand_core::RngCore, Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+
+const MAGIC: &[u8] = b"KLIPENC1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key<Aes256Gcm>, String> {
+    let mut key = Key::<Aes256Gcm>::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+pub fn encrypt(passphrase: &str, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    if passphrase.is_empty() {
+        return Err("Passphrase must not be empty".to_string());
+    }
+
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, &salt)?);
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|e| e.to_string())?;
+
+    let mut out = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+pub fn decrypt(passphrase: &str, data: &[u8]) -> Result<Vec<u8>, String> {
+    if !is_encrypted(data) || data.len() < MAGIC.len() + SALT_LEN + NONCE_LEN {
+        return Err("Not an encrypted Klip export".to_string());
+    }
+
+    let (salt, rest) = data[MAGIC.len()..].split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, salt)?);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Wrong passphrase or corrupted file".to_string())
+}
+
+/// Writes an export, encrypted when a non-empty passphrase is given.
+pub fn write_export(path: &str, contents: &[u8], passphrase: Option<&str>) -> Result<(), String> {
+    let bytes = match passphrase.filter(|p| !p.is_empty()) {
+        Some(passphrase) => encrypt(passphrase, contents)?,
+        None => contents.to_vec(),
+    };
+    std::fs::write(path, bytes).map_err(|e| e.to_string())
+}
+
+/// Decrypts an encrypted export from `path` into `output_path`.
+#[tauri::command]
+pub fn decrypt_export(path: String, passphrase: String, output_path: String) -> Result<(), String> {
+    let data = std::fs::read(&path).map_err(|e| e.to_string())?;
+    let plaintext = decrypt(&passphrase, &data)?;
+    std::fs::write(output_path, plaintext).map_err(|e| e.to_string())
+}
@@ -0,0 +1,143 @@
+// Short plain-text descriptions of clips for screen-reader labels, built from
+// the detection the backend already does (kind, domain, OCR text) so the
+// frontend doesn't have to repeat it. Never includes the text of a text clip;
+// a label is read aloud and the content is one keypress away.
+
+use serde::{Serialize, Deserialize};
+
+use crate::classify::{domain_of, summary_kind};
+use crate::{files, Clip, DbState, CLIP_COLUMNS};
+
+const MAX_DESCRIBED: usize = 500;
+const OCR_SUMMARY_WORDS: usize = 12;
+const LISTED_FILES: usize = 3;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClipDescription {
+    id: String,
+    kind: String, // "text", "link", "code", "image" or "files"
+    description: String,
+}
+
+fn plural(count: usize, one: &str, many: &str) -> String {
+    format!("{} {}", count, if count == 1 { one } else { many })
+}
+
+fn ocr_summary(text: &str) -> Option<String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() {
+        return None;
+    }
+    let mut summary = words.iter().take(OCR_SUMMARY_WORDS).copied().collect::<Vec<_>>().join(" ");
+    if words.len() > OCR_SUMMARY_WORDS {
+        summary.push_str("...");
+    }
+    Some(summary)
+}
+
+fn file_names(content: &str) -> (usize, String) {
+    let paths = files::decode(content).unwrap_or_default();
+    let mut names: Vec<String> = paths
+        .iter()
+        .take(LISTED_FILES)
+        .map(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| p.display().to_string()))
+        .collect();
+    if paths.len() > LISTED_FILES {
+        names.push(format!("{} more", paths.len() - LISTED_FILES));
+    }
+    (paths.len(), names.join(", "))
+}
+
+/// Kind and one-line description of a clip, e.g. "Link to github.com,
+/// 48 characters, from Firefox. Pinned."
+pub fn describe(clip: &Clip) -> (&'static str, String) {
+    let mut parts = Vec::new();
+    let kind = match clip.clip_type.as_str() {
+        "image" => {
+            let mut part = "Image".to_string();
+            if let (Some(width), Some(height)) = (clip.image_width, clip.image_height) {
+                part.push_str(&format!(", {} by {} pixels", width, height));
+            }
+            parts.push(part);
+            "image"
+        }
+        "files" => {
+            let (count, names) = file_names(&clip.content);
+            parts.push(format!("{}: {}", plural(count, "file", "files"), names));
+            "files"
+        }
+        _ => {
+            let kind = summary_kind(&clip.clip_type, &clip.content);
+            let chars = clip.content.chars().count();
+            parts.push(match kind {
+                "link" => match domain_of(&clip.content) {
+                    Some(domain) => format!("Link to {}", domain),
+                    None => "Link".to_string(),
+                },
+                "code" => format!("Code, {}", plural(clip.content.lines().count(), "line", "lines")),
+                _ => match clip.detected_kind.as_deref() {
+                    Some("email") => "Email address".to_string(),
+                    Some("phone") => "Phone number".to_string(),
+                    Some("color") => "Color".to_string(),
+                    Some("path") => "File path".to_string(),
+                    Some("json") => format!("JSON, {}", plural(clip.content.lines().count(), "line", "lines")),
+                    _ => {
+                        let lines = clip.content.lines().count();
+                        if lines > 1 { format!("Text, {}", plural(lines, "line", "lines")) } else { "Text".to_string() }
+                    }
+                },
+            });
+            parts.push(plural(chars, "character", "characters"));
+            kind
+        }
+    };
+    if let Some(app) = clip.source_app.as_deref().filter(|a| !a.is_empty()) {
+        parts.push(format!("from {}", app));
+    }
+    let mut description = parts.join(", ");
+    description.push('.');
+
+    let status: Vec<&str> = [
+        (clip.pinned, "Pinned"),
+        (clip.is_favorite, "Favorite"),
+        (clip.locked, "Locked"),
+        (clip.is_sensitive, "Sensitive"),
+    ]
+    .iter()
+    .filter(|(on, _)| *on)
+    .map(|(_, label)| *label)
+    .collect();
+    if !status.is_empty() {
+        description.push_str(&format!(" {}.", status.join(", ")));
+    }
+
+    // For images the content is the recognized text, if any
+    if kind == "image" && !clip.is_sensitive {
+        if let Some(summary) = ocr_summary(&clip.content) {
+            description.push_str(&format!(" Text in image: {}", summary));
+        }
+    }
+    (kind, description)
+}
+
+/// Descriptions for the given clips, in the order asked; unknown ids are skipped.
+#[tauri::command]
+pub async fn describe_clips(state: tauri::State<'_, DbState>, ids: Vec<String>) -> Result<Vec<ClipDescription>, String> {
+    if ids.len() > MAX_DESCRIBED {
+        return Err(format!("At most {} clips can be described at once", MAX_DESCRIBED));
+    }
+    let query = format!("SELECT {} FROM clips WHERE id = ?", CLIP_COLUMNS);
+    let mut descriptions = Vec::with_capacity(ids.len());
+    for id in ids {
+        let clip = sqlx::query_as::<_, Clip>(&query)
+            .bind(&id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        if let Some(clip) = clip {
+            let (kind, description) = describe(&clip);
+            descriptions.push(ClipDescription { id, kind: kind.to_string(), description });
+        }
+    }
+    Ok(descriptions)
+}
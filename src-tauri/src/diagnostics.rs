@@ -0,0 +1,224 @@
+// Database health metrics: pool usage plus a ring buffer of slow queries.
+// Hot queries go through `timed`, which also opens a tracing span per query.
+// `benchmark_database` is an on-demand check of query plans at the current scale.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Instant;
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use sqlx::{Pool, Sqlite};
+use tracing::Instrument;
+
+use crate::{capture, search, DbState, CLIP_COLUMNS, DEFAULT_PAGE_SIZE, INDEXES};
+
+const SLOW_LOG_CAPACITY: usize = 100;
+pub const DEFAULT_SLOW_QUERY_MS: u64 = 200;
+
+static SLOW_QUERY_MS: AtomicU64 = AtomicU64::new(DEFAULT_SLOW_QUERY_MS);
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+static MAX_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+static TOTAL_QUERIES: AtomicU64 = AtomicU64::new(0);
+static SLOW_LOG: Mutex<VecDeque<SlowQuery>> = Mutex::new(VecDeque::new());
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SlowQuery {
+    label: String,
+    elapsed_ms: u64,
+    at: String, // ISO 8601
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DbDiagnostics {
+    pool_size: u32,
+    idle_connections: usize,
+    active_connections: u32,
+    max_connections: u32,
+    in_flight_queries: usize,
+    max_in_flight_queries: usize,
+    // Estimate: instrumented queries in flight beyond the connections currently checked out
+    queued_acquires: usize,
+    total_queries: u64,
+    slow_query_threshold_ms: u64,
+    slow_queries: Vec<SlowQuery>, // newest first
+}
+
+pub fn set_slow_query_threshold_ms(ms: u64) {
+    SLOW_QUERY_MS.store(ms, Ordering::Relaxed);
+}
+
+/// Runs a database future inside a `db_query` span, recording it if it exceeds the slow threshold.
+pub async fn timed<F: Future>(label: &'static str, fut: F) -> F::Output {
+    let in_flight = IN_FLIGHT.fetch_add(1, Ordering::Relaxed) + 1;
+    MAX_IN_FLIGHT.fetch_max(in_flight, Ordering::Relaxed);
+    TOTAL_QUERIES.fetch_add(1, Ordering::Relaxed);
+
+    let start = Instant::now();
+    let output = fut.instrument(tracing::info_span!("db_query", query = label)).await;
+    let elapsed_ms = start.elapsed().as_millis() as u64;
+    IN_FLIGHT.fetch_sub(1, Ordering::Relaxed);
+
+    if elapsed_ms >= SLOW_QUERY_MS.load(Ordering::Relaxed) {
+        tracing::warn!(query = label, elapsed_ms, "slow query");
+        let mut log = SLOW_LOG.lock().unwrap();
+        if log.len() == SLOW_LOG_CAPACITY {
+            log.pop_front();
+        }
+        log.push_back(SlowQuery { label: label.to_string(), elapsed_ms, at: Utc::now().to_rfc3339() });
+    }
+    output
+}
+
+#[tauri::command]
+pub fn get_db_diagnostics(state: tauri::State<'_, DbState>) -> DbDiagnostics {
+    let pool_size = state.pool.size();
+    let idle_connections = state.pool.num_idle();
+    let active_connections = pool_size.saturating_sub(idle_connections as u32);
+    let in_flight_queries = IN_FLIGHT.load(Ordering::Relaxed);
+
+    DbDiagnostics {
+        pool_size,
+        idle_connections,
+        active_connections,
+        max_connections: state.pool.options().get_max_connections(),
+        in_flight_queries,
+        max_in_flight_queries: MAX_IN_FLIGHT.load(Ordering::Relaxed),
+        queued_acquires: in_flight_queries.saturating_sub(active_connections as usize),
+        total_queries: TOTAL_QUERIES.load(Ordering::Relaxed),
+        slow_query_threshold_ms: SLOW_QUERY_MS.load(Ordering::Relaxed),
+        slow_queries: SLOW_LOG.lock().unwrap().iter().rev().cloned().collect(),
+    }
+}
+
+#[tauri::command]
+pub async fn set_slow_query_threshold(state: tauri::State<'_, DbState>, ms: u64) -> Result<(), String> {
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES ('slow_query_ms', ?)")
+        .bind(ms.to_string())
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    set_slow_query_threshold_ms(ms);
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueryBenchmark {
+    label: String,
+    best_ms: f64, // best of BENCHMARK_RUNS
+    rows: usize,
+    plan: Vec<String>, // EXPLAIN QUERY PLAN details
+    full_scan: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BenchmarkReport {
+    clip_count: i64,
+    queries: Vec<QueryBenchmark>,
+    missing_indexes: Vec<String>,
+    suggestions: Vec<String>,
+    applied: bool,
+}
+
+const BENCHMARK_RUNS: usize = 3;
+
+async fn bench_query(pool: &Pool<Sqlite>, label: &str, sql: &str, args: &[String]) -> Result<QueryBenchmark, String> {
+    let mut best_ms = f64::MAX;
+    let mut rows = 0;
+    for _ in 0..BENCHMARK_RUNS {
+        let mut query = sqlx::query(sql);
+        for arg in args {
+            query = query.bind(arg);
+        }
+        let start = Instant::now();
+        rows = query.fetch_all(pool).await.map_err(|e| e.to_string())?.len();
+        best_ms = best_ms.min(start.elapsed().as_secs_f64() * 1000.0);
+    }
+
+    let explain = format!("EXPLAIN QUERY PLAN {}", sql);
+    let mut plan_query = sqlx::query_as::<_, (i64, i64, i64, String)>(&explain);
+    for arg in args {
+        plan_query = plan_query.bind(arg);
+    }
+    let plan: Vec<String> = plan_query
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(_, _, _, detail)| detail)
+        .collect();
+    let full_scan = plan.iter().any(|d| d.starts_with("SCAN clips") && !d.contains("USING"));
+
+    Ok(QueryBenchmark { label: label.to_string(), best_ms, rows, plan, full_scan })
+}
+
+/// Times the app's common queries at the current database size and checks its indexes.
+/// With `apply`, missing indexes are recreated and the planner statistics refreshed.
+/// Not exposed in the UI; meant for diagnosing slow, very large histories.
+#[tauri::command]
+pub async fn benchmark_database(state: tauri::State<'_, DbState>, search_text: Option<String>, apply: Option<bool>) -> Result<BenchmarkReport, String> {
+    let pool = &state.pool;
+    let (clip_count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM clips")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let search = search::fts_query(search_text.as_deref().filter(|s| !s.is_empty()).unwrap_or("the")).unwrap_or_else(|| "\"the\"*".to_string());
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    let sample: Option<(String,)> = sqlx::query_as("SELECT content FROM clips ORDER BY created_at DESC LIMIT 1")
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let sample = sample.map(|(c,)| c).unwrap_or_default();
+
+    let recent = format!("SELECT {} FROM clips ORDER BY created_at DESC, id DESC LIMIT {}", CLIP_COLUMNS, DEFAULT_PAGE_SIZE);
+    let searched = format!("SELECT {} FROM clips WHERE clips.rowid IN (SELECT rowid FROM clips_fts WHERE clips_fts MATCH ?) ORDER BY created_at DESC, id DESC LIMIT {}", CLIP_COLUMNS, DEFAULT_PAGE_SIZE);
+    let dedup = format!("SELECT 1 FROM clips WHERE {} AND deleted_at IS NULL LIMIT 1", capture::TEXT_DUPLICATE);
+    let dated = format!("SELECT {} FROM clips WHERE strftime('%Y-%m-%d', created_at, 'localtime') = ? ORDER BY created_at DESC, id DESC LIMIT {}", CLIP_COLUMNS, DEFAULT_PAGE_SIZE);
+
+    let queries = vec![
+        bench_query(pool, "recent", &recent, &[]).await?,
+        bench_query(pool, "search", &searched, &[search]).await?,
+        bench_query(pool, "date_filter", &dated, &[today]).await?,
+        bench_query(pool, "dates_with_clips", "SELECT DISTINCT strftime('%Y-%m-%d', created_at, 'localtime') FROM clips ORDER BY created_at DESC", &[]).await?,
+        bench_query(pool, "dedup", &dedup, &[capture::text_hash(&sample), sample]).await?,
+    ];
+
+    let existing: Vec<(String,)> = sqlx::query_as("SELECT name FROM sqlite_master WHERE type = 'index'")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let missing: Vec<&(&str, &str)> = INDEXES
+        .iter()
+        .filter(|(name, _)| !existing.iter().any(|(e,)| e == name))
+        .collect();
+
+    let mut suggestions = Vec::new();
+    if !missing.is_empty() {
+        suggestions.push("Recreate the missing indexes (run again with apply)".to_string());
+    }
+    for query in queries.iter().filter(|q| q.full_scan) {
+        suggestions.push(match query.label.as_str() {
+            "search" | "date_filter" | "dedup" => format!("{} scans every clip ({:.1} ms); expected at this size, consider trimming history", query.label, query.best_ms),
+            _ => format!("{} scans every clip; run again with apply to refresh planner statistics", query.label),
+        });
+    }
+
+    let applied = apply.unwrap_or(false);
+    if applied {
+        for (_, ddl) in &missing {
+            sqlx::query(ddl).execute(pool).await.map_err(|e| e.to_string())?;
+        }
+        sqlx::query("ANALYZE").execute(pool).await.map_err(|e| e.to_string())?;
+        sqlx::query("PRAGMA optimize").execute(pool).await.map_err(|e| e.to_string())?;
+    }
+
+    Ok(BenchmarkReport {
+        clip_count,
+        queries,
+        missing_indexes: missing.iter().map(|(name, _)| name.to_string()).collect(),
+        suggestions,
+        applied,
+    })
+}
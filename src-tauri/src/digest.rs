@@ -0,0 +1,203 @@
+use tauri::{AppHandle, Manager};
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::classify::{domain_of, is_url};
+use crate::{app_data_dir, crypto, notify, trash, DbState, DB_FILENAME};
+
+const TOP_DOMAINS: usize = 5;
+const BIGGEST_CLIPS: usize = 5;
+const PREVIEW_CHARS: usize = 80;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DomainCount {
+    domain: String,
+    count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClipSize {
+    id: String,
+    clip_type: String,
+    preview: String,
+    bytes: u64,
+}
+
+#[derive(sqlx::FromRow)]
+struct DigestRow {
+    id: String,
+    content: String,
+    is_favorite: bool,
+    clip_type: String,
+    image_path: Option<String>,
+    source_url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WeeklyDigest {
+    period_start: String, // ISO 8601
+    period_end: String,
+    total_clips: i64,
+    text_clips: i64,
+    image_clips: i64,
+    favorites: i64,
+    top_domains: Vec<DomainCount>,
+    biggest_clips: Vec<ClipSize>,
+    bytes_added: u64, // text content plus image files captured this week
+    database_bytes: u64,
+}
+
+pub async fn build_weekly_digest(pool: &Pool<Sqlite>, app_dir: &std::path::Path) -> Result<WeeklyDigest, String> {
+    let end = Utc::now();
+    let start = end - chrono::Duration::days(7);
+
+    let rows = sqlx::query_as::<_, DigestRow>(&format!(
+        "SELECT id, content, is_favorite, clip_type, image_path, source_url FROM clips WHERE created_at >= ? AND {}",
+        trash::NOT_DELETED
+    ))
+    .bind(start.to_rfc3339())
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut domains: HashMap<String, i64> = HashMap::new();
+    let mut sizes: Vec<ClipSize> = Vec::new();
+    let (mut text_clips, mut image_clips, mut favorites) = (0, 0, 0);
+
+    for row in &rows {
+        let DigestRow { id, content, is_favorite, clip_type, image_path, source_url } = row;
+        if *is_favorite {
+            favorites += 1;
+        }
+
+        let bytes = if clip_type == "image" {
+            image_clips += 1;
+            image_path
+                .as_ref()
+                .and_then(|p| std::fs::metadata(p).ok())
+                .map(|m| m.len())
+                .unwrap_or(0)
+        } else {
+            text_clips += 1;
+            content.len() as u64
+        };
+
+        let link = source_url.as_deref().or(if is_url(content) { Some(content.as_str()) } else { None });
+        if let Some(domain) = link.and_then(domain_of) {
+            *domains.entry(domain).or_insert(0) += 1;
+        }
+
+        sizes.push(ClipSize {
+            id: id.clone(),
+            clip_type: clip_type.clone(),
+            preview: content.trim().chars().take(PREVIEW_CHARS).collect(),
+            bytes,
+        });
+    }
+
+    let bytes_added = sizes.iter().map(|s| s.bytes).sum();
+    sizes.sort_by_key(|s| std::cmp::Reverse(s.bytes));
+    sizes.truncate(BIGGEST_CLIPS);
+
+    let mut top_domains: Vec<DomainCount> = domains
+        .into_iter()
+        .map(|(domain, count)| DomainCount { domain, count })
+        .collect();
+    top_domains.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.domain.cmp(&b.domain)));
+    top_domains.truncate(TOP_DOMAINS);
+
+    let database_bytes = std::fs::metadata(app_dir.join(DB_FILENAME)).map(|m| m.len()).unwrap_or(0);
+
+    Ok(WeeklyDigest {
+        period_start: start.to_rfc3339(),
+        period_end: end.to_rfc3339(),
+        total_clips: rows.len() as i64,
+        text_clips,
+        image_clips,
+        favorites,
+        top_domains,
+        biggest_clips: sizes,
+        bytes_added,
+        database_bytes,
+    })
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{} B", bytes) } else { format!("{:.1} {}", value, UNITS[unit]) }
+}
+
+pub fn render_markdown(digest: &WeeklyDigest) -> String {
+    let day = |iso: &str| iso.get(..10).unwrap_or(iso).to_string();
+    let mut md = String::new();
+
+    let _ = writeln!(md, "# Klip weekly digest ({} – {})\n", day(&digest.period_start), day(&digest.period_end));
+    let _ = writeln!(md, "- Clips captured: {}", digest.total_clips);
+    let _ = writeln!(md, "- Text: {} · Images: {} · Favorited: {}", digest.text_clips, digest.image_clips, digest.favorites);
+    let _ = writeln!(md, "- Storage added: {}", format_bytes(digest.bytes_added));
+    let _ = writeln!(md, "- Database size: {}\n", format_bytes(digest.database_bytes));
+
+    if !digest.top_domains.is_empty() {
+        let _ = writeln!(md, "## Top domains\n");
+        for d in &digest.top_domains {
+            let _ = writeln!(md, "- {} ({})", d.domain, d.count);
+        }
+        md.push('\n');
+    }
+
+    if !digest.biggest_clips.is_empty() {
+        let _ = writeln!(md, "## Biggest clips\n");
+        for c in &digest.biggest_clips {
+            let label = if c.clip_type == "image" { "[image]".to_string() } else { c.preview.replace('\n', " ") };
+            let _ = writeln!(md, "- {} — {}", format_bytes(c.bytes), label);
+        }
+    }
+
+    md
+}
+
+/// Scheduled job: writes the digest into `reports/` and posts a notification.
+pub async fn run_weekly_digest(app: &AppHandle) -> Result<(), String> {
+    let state = app.try_state::<DbState>().ok_or_else(|| "Database is not available".to_string())?;
+    let app_dir = app_data_dir(app);
+    let digest = build_weekly_digest(&state.pool, &app_dir).await?;
+
+    let reports_dir = app_dir.join("reports");
+    std::fs::create_dir_all(&reports_dir).map_err(|e| e.to_string())?;
+    let file_name = format!("weekly-digest-{}.md", Local::now().format("%Y-%m-%d"));
+    std::fs::write(reports_dir.join(file_name), render_markdown(&digest)).map_err(|e| e.to_string())?;
+
+    notify::notify_text(
+        "Klip weekly digest",
+        &format!("{} clips this week, {} added", digest.total_clips, format_bytes(digest.bytes_added)),
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_weekly_digest(app_handle: AppHandle, state: tauri::State<'_, DbState>) -> Result<WeeklyDigest, String> {
+    let app_dir = app_data_dir(&app_handle);
+    build_weekly_digest(&state.pool, &app_dir).await
+}
+
+#[tauri::command]
+pub async fn export_weekly_digest(app_handle: AppHandle, state: tauri::State<'_, DbState>, path: String, passphrase: Option<String>) -> Result<(), String> {
+    let app_dir = app_data_dir(&app_handle);
+    let digest = build_weekly_digest(&state.pool, &app_dir).await?;
+
+    let output = if path.ends_with(".json") {
+        serde_json::to_string_pretty(&digest).map_err(|e| e.to_string())?
+    } else {
+        render_markdown(&digest)
+    };
+    crypto::write_export(&path, output.as_bytes(), passphrase.as_deref())
+}
@@ -0,0 +1,99 @@
+// "Double-tap copy": copying the same text again within a couple of seconds
+// is taken as a signal about that clip. What it does is chosen with the
+// `double_copy_action` setting; off by default. Needs a native clipboard
+// watcher, since polling can't see a copy that doesn't change the content.
+
+use tauri::{AppHandle, Emitter, Manager};
+use sqlx::{Pool, Sqlite};
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+
+use crate::{setting_value, store, tray, DbState};
+
+pub const WINDOW: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DoubleCopyAction {
+    Off,
+    Favorite,
+    QuickPicker,
+}
+
+impl DoubleCopyAction {
+    fn parse(value: &str) -> Option<DoubleCopyAction> {
+        match value {
+            "off" => Some(DoubleCopyAction::Off),
+            "favorite" => Some(DoubleCopyAction::Favorite),
+            "quick_picker" => Some(DoubleCopyAction::QuickPicker),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            DoubleCopyAction::Off => "off",
+            DoubleCopyAction::Favorite => "favorite",
+            DoubleCopyAction::QuickPicker => "quick_picker",
+        }
+    }
+}
+
+pub async fn double_copy_action(pool: &Pool<Sqlite>) -> DoubleCopyAction {
+    setting_value(pool, "double_copy_action")
+        .await
+        .and_then(|v| DoubleCopyAction::parse(&v))
+        .unwrap_or(DoubleCopyAction::Off)
+}
+
+/// Called by the monitor when `text` was copied a second time within `WINDOW`.
+/// The UI gets a `double-copy` event with the clip id for feedback.
+pub async fn on_double_copy(app: &AppHandle, text: &str) -> Result<(), String> {
+    let state = app.try_state::<DbState>().ok_or_else(|| "Database is not available".to_string())?;
+    let action = double_copy_action(&state.pool).await;
+    // Klip putting the current clip back on the clipboard isn't a gesture
+    if action == DoubleCopyAction::Off || store::klip_has_focus(app) {
+        return Ok(());
+    }
+
+    // The capture of the first copy, compared as it was stored
+    let (stored, _) = store::stored_text(&state.pool, text).await;
+    let clip: Option<(String,)> = sqlx::query_as("SELECT id FROM clips WHERE content = ? AND deleted_at IS NULL ORDER BY created_at DESC LIMIT 1")
+        .bind(&stored)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let Some((id,)) = clip else { return Ok(()) };
+
+    match action {
+        DoubleCopyAction::Favorite => {
+            sqlx::query("UPDATE clips SET is_favorite = 1 WHERE id = ?")
+                .bind(&id)
+                .execute(&state.pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            state.recent.update(&id, |clip| clip.is_favorite = true);
+            let _ = app.emit("clipboard-changed", ());
+        }
+        DoubleCopyAction::QuickPicker => tray::open_quick_picker(app),
+        DoubleCopyAction::Off => {}
+    }
+    let _ = app.emit("double-copy", id);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_double_copy_action(state: tauri::State<'_, DbState>) -> Result<String, String> {
+    Ok(double_copy_action(&state.pool).await.as_str().to_string())
+}
+
+#[tauri::command]
+pub async fn set_double_copy_action(state: tauri::State<'_, DbState>, action: String) -> Result<(), String> {
+    let action = DoubleCopyAction::parse(&action).ok_or_else(|| format!("Unknown double-copy action: {}", action))?;
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES ('double_copy_action', ?)")
+        .bind(action.as_str())
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,160 @@
+// Optional encryption at rest for `clips.db` with SQLCipher. The key is a
+// random 256-bit key kept in the OS keychain, so there is nothing to type.
+// Switching on or off rewrites the database with `sqlcipher_export` at the
+// next start, before any pool is opened. Image files are not encrypted.
+
+use tauri::AppHandle;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{ConnectOptions, Connection};
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+
+use crate::{app_data_dir, keychain, DB_FILENAME};
+
+pub const DB_ENCRYPTION_KEY: &str = "db_encryption_key"; // keychain entry
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EncryptionStatus {
+    enabled: bool,
+    pending: Option<String>, // "encrypt" or "decrypt", applied at the next start
+}
+
+/// Marks a requested decryption; the key must survive until it's done.
+fn decrypt_marker(db_path: &Path) -> PathBuf {
+    db_path.with_file_name(format!("{}.decrypt", DB_FILENAME))
+}
+
+/// A missing or empty file counts as plaintext: nothing to migrate.
+pub fn is_encrypted(db_path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    match std::fs::File::open(db_path).and_then(|mut f| std::io::Read::read_exact(&mut f, &mut header)) {
+        Ok(()) => header != SQLITE_HEADER,
+        Err(_) => false,
+    }
+}
+
+fn key_pragma(key: &str) -> String {
+    format!("\"x'{}'\"", key)
+}
+
+/// Connection options for the main database, keyed when encryption is on.
+pub fn connect_options(db_path: &Path, key: Option<&str>) -> SqliteConnectOptions {
+    let options = SqliteConnectOptions::new().filename(db_path).create_if_missing(true);
+    match key {
+        Some(key) => options.pragma("key", key_pragma(key)),
+        None => options,
+    }
+}
+
+/// Copies the database into a new file keyed with `to_key` (plaintext with
+/// `None`), then replaces the original with it.
+async fn rewrite(db_path: &Path, from_key: Option<&str>, to_key: Option<&str>) -> Result<(), String> {
+    let target = db_path.with_file_name(format!("{}.rewrite", DB_FILENAME));
+    let _ = std::fs::remove_file(&target);
+
+    let mut conn = connect_options(db_path, from_key).connect().await.map_err(|e| e.to_string())?;
+    let target_key = to_key.map(key_pragma).unwrap_or_else(|| "''".to_string());
+    let target_path = target.to_string_lossy().replace('\'', "''");
+    sqlx::query(&format!("ATTACH DATABASE '{}' AS rewritten KEY {}", target_path, target_key))
+        .execute(&mut conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query("SELECT sqlcipher_export('rewritten')")
+        .execute(&mut conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query("DETACH DATABASE rewritten")
+        .execute(&mut conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    conn.close().await.map_err(|e| e.to_string())?;
+
+    std::fs::rename(&target, db_path).map_err(|e| e.to_string())
+}
+
+/// Removes the key after decrypting, then the marker. The marker stays until
+/// the key is gone, so a failure here can't turn encryption back on.
+fn forget_key(marker: &Path) -> Result<(), String> {
+    keychain::remove_secret(DB_ENCRYPTION_KEY)
+        .map_err(|e| format!("The database was decrypted but its key couldn't be removed from the keychain: {}", e))?;
+    let _ = std::fs::remove_file(marker);
+    Ok(())
+}
+
+/// Brings the file in line with the requested mode and returns the key to
+/// open it with. Runs before the pool is created.
+pub async fn prepare(db_path: &Path) -> Result<Option<String>, String> {
+    let encrypted = is_encrypted(db_path);
+    let marker = decrypt_marker(db_path);
+    // Without a Secret Service the keychain errors; a plaintext database that
+    // isn't being encrypted doesn't need it, so that must not block startup
+    let key = if encrypted || marker.exists() {
+        keychain::get_secret(DB_ENCRYPTION_KEY)?
+    } else {
+        keychain::get_secret(DB_ENCRYPTION_KEY).unwrap_or_else(|e| {
+            eprintln!("Keychain unavailable, opening the database unencrypted: {}", e);
+            None
+        })
+    };
+
+    match key {
+        Some(key) if encrypted && marker.exists() => {
+            rewrite(db_path, Some(&key), None).await?;
+            forget_key(&marker)?;
+            Ok(None)
+        }
+        // Decrypted earlier but the key couldn't be removed then; don't encrypt again
+        Some(_) if marker.exists() => {
+            forget_key(&marker)?;
+            Ok(None)
+        }
+        Some(key) if !encrypted && std::fs::metadata(db_path).is_ok_and(|m| m.len() > 0) => {
+            rewrite(db_path, None, Some(&key)).await?;
+            Ok(Some(key))
+        }
+        Some(key) => {
+            let _ = std::fs::remove_file(&marker);
+            Ok(Some(key))
+        }
+        None if encrypted => Err("The database is encrypted but its key is missing from the keychain".to_string()),
+        None => {
+            let _ = std::fs::remove_file(&marker);
+            Ok(None)
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_database_encryption(app_handle: AppHandle) -> Result<EncryptionStatus, String> {
+    let db_path = app_data_dir(&app_handle).join(DB_FILENAME);
+    let enabled = is_encrypted(&db_path);
+    let wanted = matches!(keychain::get_secret(DB_ENCRYPTION_KEY), Ok(Some(_))) && !decrypt_marker(&db_path).exists();
+    let pending = match (enabled, wanted) {
+        (false, true) => Some("encrypt".to_string()),
+        (true, false) => Some("decrypt".to_string()),
+        _ => None,
+    };
+    Ok(EncryptionStatus { enabled, pending })
+}
+
+/// Turns encryption at rest on or off. The database is rewritten when the app
+/// restarts, which it does right away when `restart` is set.
+#[tauri::command]
+pub fn set_database_encryption(app_handle: AppHandle, enabled: bool, restart: Option<bool>) -> Result<EncryptionStatus, String> {
+    let db_path = app_data_dir(&app_handle).join(DB_FILENAME);
+    let marker = decrypt_marker(&db_path);
+    if enabled {
+        keychain::get_or_create_key(DB_ENCRYPTION_KEY)?;
+        let _ = std::fs::remove_file(&marker);
+    } else if is_encrypted(&db_path) {
+        std::fs::write(&marker, b"").map_err(|e| e.to_string())?;
+    } else {
+        keychain::remove_secret(DB_ENCRYPTION_KEY)?;
+    }
+
+    if restart.unwrap_or(false) {
+        app_handle.restart();
+    }
+    get_database_encryption(app_handle)
+}
@@ -0,0 +1,162 @@
+use sqlx::{Pool, Sqlite};
+use std::fmt::Write as _;
+use std::io::Cursor;
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use base64::Engine;
+
+use crate::{crypto, normalize_text, tags, Clip, DbState, CLIP_COLUMNS};
+
+const THUMBNAIL_SIZE: u32 = 320;
+
+/// Which clips an export covers. All fields are optional and combine with AND.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct ExportFilter {
+    pub ids: Option<Vec<String>>,
+    pub favorites_only: Option<bool>,
+    pub date_from: Option<String>, // YYYY-MM-DD, local time, inclusive
+    pub date_to: Option<String>,
+    pub search_text: Option<String>,
+    pub tags: Option<Vec<String>>, // every listed tag must be present
+}
+
+/// WHERE clause (and its bind values) selecting the clips a filter covers.
+pub fn filter_clause(filter: &ExportFilter) -> (String, Vec<String>) {
+    let mut query = "deleted_at IS NULL".to_string();
+    let mut args: Vec<String> = Vec::new();
+
+    if let Some(ids) = filter.ids.as_ref().filter(|ids| !ids.is_empty()) {
+        query.push_str(&format!(" AND id IN ({})", vec!["?"; ids.len()].join(", ")));
+        args.extend(ids.iter().cloned());
+    }
+    if filter.favorites_only.unwrap_or(false) {
+        query.push_str(" AND is_favorite = 1");
+    }
+    if let Some(from) = filter.date_from.as_ref().filter(|d| !d.is_empty()) {
+        query.push_str(" AND strftime('%Y-%m-%d', created_at, 'localtime') >= ?");
+        args.push(from.clone());
+    }
+    if let Some(to) = filter.date_to.as_ref().filter(|d| !d.is_empty()) {
+        query.push_str(" AND strftime('%Y-%m-%d', created_at, 'localtime') <= ?");
+        args.push(to.clone());
+    }
+    if let Some(search) = filter.search_text.as_ref().filter(|s| !s.is_empty()) {
+        query.push_str(" AND search_content LIKE ?");
+        args.push(format!("%{}%", normalize_text(search)));
+    }
+    for tag in filter.tags.iter().flatten().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        query.push_str(&format!(" AND {}", tags::HAS_TAG));
+        args.push(tag.to_string());
+    }
+    (query, args)
+}
+
+/// Clips matching the filter, oldest first.
+pub async fn fetch_clips(pool: &Pool<Sqlite>, filter: &ExportFilter) -> Result<Vec<Clip>, String> {
+    let (filter, args) = filter_clause(filter);
+    let query = format!("SELECT {} FROM clips WHERE {} ORDER BY created_at ASC, id ASC", CLIP_COLUMNS, filter);
+
+    let mut query_builder = sqlx::query_as::<_, Clip>(&query);
+    for arg in args {
+        query_builder = query_builder.bind(arg);
+    }
+
+    query_builder
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn is_web_link(url: &str) -> bool {
+    reqwest::Url::parse(url.trim()).is_ok_and(|u| matches!(u.scheme(), "http" | "https"))
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Small PNG of an image clip as a data URI, so the page has no external files.
+pub fn thumbnail_data_uri(path: &str, size: u32) -> Option<String> {
+    let img = image::open(path).ok()?.thumbnail(size, size);
+    let mut png = Vec::new();
+    img.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png).ok()?;
+    Some(format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(png)))
+}
+
+const PAGE_STYLE: &str = "body{font-family:-apple-system,Segoe UI,sans-serif;background:#1e1e1e;color:#ddd;max-width:860px;margin:2rem auto;padding:0 1rem}\
+h1{font-size:1.4rem}input{width:100%;padding:.5rem;margin-bottom:1rem;background:#3c3c3c;color:#fff;border:0;border-radius:4px}\
+.clip{background:#2d2d2d;border-radius:6px;padding:.75rem 1rem;margin-bottom:.75rem}\
+.meta{font-size:.75rem;color:#888;margin-bottom:.4rem}.fav{color:#f5c518}\
+pre{white-space:pre-wrap;word-break:break-word;margin:0;font-size:.85rem}img{max-width:100%;border-radius:4px}a{color:#6cb6ff}";
+
+// Client-side filter so the snapshot stays browsable without a server
+const PAGE_SCRIPT: &str = "document.getElementById('q').addEventListener('input',e=>{const q=e.target.value.toLowerCase();\
+document.querySelectorAll('.clip').forEach(c=>{c.style.display=c.textContent.toLowerCase().includes(q)?'':'none'})});";
+
+pub fn render_static_site(clips: &[Clip]) -> String {
+    let mut html = String::new();
+    let generated = Local::now().format("%Y-%m-%d %H:%M");
+
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Klip export</title><style>{}</style></head><body>\n\
+         <h1>Klip export</h1><p class=\"meta\">{} clips · generated {}</p>\n\
+         <input id=\"q\" placeholder=\"Filter…\">\n",
+        PAGE_STYLE,
+        clips.len(),
+        generated
+    );
+
+    for clip in clips {
+        let created = DateTime::parse_from_rfc3339(&clip.created_at)
+            .map(|d| d.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|_| clip.created_at.clone());
+
+        let _ = write!(html, "<div class=\"clip\"><div class=\"meta\">{}", escape_html(&created));
+        if clip.is_favorite {
+            html.push_str(" <span class=\"fav\">★</span>");
+        }
+        if let Some(url) = &clip.source_url {
+            // Only web links are clickable; anything else (javascript:, file:) stays text
+            if is_web_link(url) {
+                let _ = write!(html, " · <a href=\"{0}\">{0}</a>", escape_html(url));
+            } else {
+                let _ = write!(html, " · {}", escape_html(url));
+            }
+        }
+        html.push_str("</div>");
+
+        if clip.clip_type == "image" {
+            if let Some(uri) = clip.image_path.as_deref().and_then(|p| thumbnail_data_uri(p, THUMBNAIL_SIZE)) {
+                let _ = write!(html, "<img src=\"{}\" alt=\"\">", uri);
+            }
+        }
+        if !clip.content.is_empty() {
+            let _ = write!(html, "<pre>{}</pre>", escape_html(&clip.content));
+        }
+        html.push_str("</div>\n");
+    }
+
+    let _ = write!(html, "<script>{}</script>\n</body></html>\n", PAGE_SCRIPT);
+    html
+}
+
+/// Writes a single self-contained HTML page of the selected clips, encrypted when
+/// `passphrase` is given. Returns the clip count.
+#[tauri::command]
+pub async fn export_static_site(state: tauri::State<'_, DbState>, path: String, filter: Option<ExportFilter>, passphrase: Option<String>) -> Result<usize, String> {
+    let clips = fetch_clips(&state.pool, &filter.unwrap_or_default()).await?;
+    crypto::write_export(&path, render_static_site(&clips).as_bytes(), passphrase.as_deref())?;
+    Ok(clips.len())
+}
@@ -0,0 +1,83 @@
+// Counts for filter chips, computed over the same filters get_clips applies,
+// so the frontend gets every facet in one call.
+
+use sqlx::{Pool, Sqlite};
+use serde::{Serialize, Deserialize};
+
+use crate::{clip_filter, diagnostics, snapshots, DbState};
+
+const MAX_FACET_VALUES: i64 = 30;
+
+// Mirrors classify::summary_kind closely enough for counting; code detection
+// needs the Rust heuristics and is left out.
+const KIND_EXPR: &str = "CASE WHEN clip_type = 'image' THEN 'image' \
+     WHEN clip_type = 'files' THEN 'files' \
+     WHEN clip_type = 'url' THEN 'link' \
+     ELSE 'text' END";
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct FacetValue {
+    value: String,
+    count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClipFacets {
+    total: i64,
+    favorites: i64,
+    types: Vec<FacetValue>,
+    apps: Vec<FacetValue>, // by source app
+    days: Vec<FacetValue>, // newest first
+    tags: Vec<FacetValue>,
+}
+
+async fn facet(pool: &Pool<Sqlite>, expr: &str, filter: &str, args: &[String], order: &str) -> Result<Vec<FacetValue>, String> {
+    let query = format!(
+        "SELECT {expr} AS value, COUNT(*) AS count FROM clips WHERE {filter} AND {expr} IS NOT NULL \
+         GROUP BY value ORDER BY {order} LIMIT {MAX_FACET_VALUES}"
+    );
+    let mut query_builder = sqlx::query_as::<_, FacetValue>(&query);
+    for arg in args {
+        query_builder = query_builder.bind(arg);
+    }
+    query_builder.fetch_all(pool).await.map_err(|e| e.to_string())
+}
+
+async fn tag_facet(pool: &Pool<Sqlite>, filter: &str, args: &[String]) -> Result<Vec<FacetValue>, String> {
+    let query = format!(
+        "SELECT t.name AS value, COUNT(*) AS count FROM tags t JOIN clip_tags ct ON ct.tag_id = t.id \
+         WHERE ct.clip_id IN (SELECT id FROM clips WHERE {filter}) \
+         GROUP BY t.id ORDER BY count DESC LIMIT {MAX_FACET_VALUES}"
+    );
+    let mut query_builder = sqlx::query_as::<_, FacetValue>(&query);
+    for arg in args {
+        query_builder = query_builder.bind(arg);
+    }
+    query_builder.fetch_all(pool).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // same flat arguments as get_clips
+pub async fn get_clip_facets(state: tauri::State<'_, DbState>, search_text: Option<String>, date_filter: Option<String>, window_filter: Option<String>, project_id: Option<String>, tags: Option<Vec<String>>, collection_id: Option<String>, source_app: Option<String>, detected_kind: Option<String>) -> Result<ClipFacets, String> {
+    let (filter, args) = clip_filter(search_text, date_filter, window_filter, project_id, tags, collection_id, source_app, detected_kind);
+    let pool = &snapshots::read_pool(&state);
+
+    let totals_query = format!("SELECT COUNT(*), COALESCE(SUM(is_favorite), 0) FROM clips WHERE {}", filter);
+    let mut totals = sqlx::query_as::<_, (i64, i64)>(&totals_query);
+    for arg in &args {
+        totals = totals.bind(arg);
+    }
+
+    diagnostics::timed("get_clip_facets", async {
+        let (total, favorites) = totals.fetch_one(pool).await.map_err(|e| e.to_string())?;
+        Ok(ClipFacets {
+            total,
+            favorites,
+            types: facet(pool, KIND_EXPR, &filter, &args, "count DESC").await?,
+            apps: facet(pool, "source_app", &filter, &args, "count DESC").await?,
+            days: facet(pool, "strftime('%Y-%m-%d', created_at, 'localtime')", &filter, &args, "value DESC").await?,
+            tags: tag_facet(pool, &filter, &args).await?,
+        })
+    })
+    .await
+}
@@ -0,0 +1,39 @@
+// Files copied in Finder/Explorer/file managers, stored as "files" clips
+// whose content is a JSON array of their paths. Copying such a clip puts the
+// file references back, so they can be pasted into another folder; files
+// that were moved or deleted since are left out.
+
+use std::path::{Path, PathBuf};
+use arboard::Clipboard;
+
+/// Paths of the files on the clipboard, if it holds any.
+pub fn read(clipboard: &mut Clipboard) -> Option<Vec<PathBuf>> {
+    clipboard.get().file_list().ok().filter(|paths| !paths.is_empty())
+}
+
+pub fn encode(paths: &[PathBuf]) -> Result<String, String> {
+    let paths: Vec<String> = paths.iter().map(|p| p.to_string_lossy().to_string()).collect();
+    serde_json::to_string(&paths).map_err(|e| e.to_string())
+}
+
+pub fn decode(content: &str) -> Result<Vec<PathBuf>, String> {
+    serde_json::from_str::<Vec<String>>(content)
+        .map(|paths| paths.into_iter().map(PathBuf::from).collect())
+        .map_err(|e| format!("Not a file list: {}", e))
+}
+
+/// File names first, then full paths, so searches match on either.
+pub fn search_text(paths: &[PathBuf]) -> String {
+    let names = paths.iter().filter_map(|p| p.file_name()).map(|n| n.to_string_lossy());
+    let full = paths.iter().map(|p| p.to_string_lossy());
+    names.chain(full).collect::<Vec<_>>().join("\n")
+}
+
+/// Puts the files of a "files" clip back on the clipboard.
+pub fn write(clipboard: &mut Clipboard, content: &str) -> Result<(), String> {
+    let paths: Vec<PathBuf> = decode(content)?.into_iter().filter(|p| Path::new(p).exists()).collect();
+    if paths.is_empty() {
+        return Err("None of these files exist anymore".to_string());
+    }
+    clipboard.set().file_list(&paths).map_err(|e| e.to_string())
+}
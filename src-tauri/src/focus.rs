@@ -0,0 +1,210 @@
+// Focus-time rules: only capture inside configured hours and days, and not
+// during events of an ICS calendar. Evaluated by the scheduler every tick;
+// the monitor only reads the resulting flag.
+//
+// Calendar support is deliberately small: single events with UTC or local
+// DTSTART/DTEND (TZID is treated as local time). Recurring rules and all-day
+// events are ignored.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use sqlx::{Pool, Sqlite};
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::{setting_value, DbState};
+
+const CALENDAR_REFRESH: chrono::Duration = chrono::Duration::minutes(15);
+const CALENDAR_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(20);
+const DAY_NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+static OUTSIDE: AtomicBool = AtomicBool::new(false);
+static CALENDAR: Mutex<Option<Calendar>> = Mutex::new(None);
+
+struct Calendar {
+    url: String,
+    fetched_at: DateTime<Utc>,
+    events: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FocusRules {
+    pub hours: Option<String>,        // "09:00-18:00"; may wrap past midnight
+    pub days: Option<Vec<String>>,    // "mon".."sun"; None = every day
+    pub calendar_url: Option<String>, // ICS feed; capture pauses during its events
+}
+
+/// True while the rules say not to capture.
+pub fn outside_schedule() -> bool {
+    OUTSIDE.load(Ordering::Relaxed)
+}
+
+fn parse_hours(value: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let (start, end) = value.split_once('-')?;
+    let start = NaiveTime::parse_from_str(start.trim(), "%H:%M").ok()?;
+    let end = NaiveTime::parse_from_str(end.trim(), "%H:%M").ok()?;
+    Some((start, end))
+}
+
+fn within_hours((start, end): (NaiveTime, NaiveTime), now: NaiveTime) -> bool {
+    if start <= end {
+        now >= start && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+pub async fn load_rules(pool: &Pool<Sqlite>) -> FocusRules {
+    FocusRules {
+        hours: setting_value(pool, "capture_hours").await.filter(|v| !v.is_empty()),
+        days: setting_value(pool, "capture_days")
+            .await
+            .filter(|v| !v.is_empty())
+            .map(|v| v.split(',').map(|d| d.trim().to_lowercase()).collect()),
+        calendar_url: setting_value(pool, "calendar_ics_url").await.filter(|v| !v.is_empty()),
+    }
+}
+
+/// ICS date-time: "20240102T090000Z" (UTC) or "20240102T090000" (local).
+fn parse_ics_time(value: &str) -> Option<DateTime<Utc>> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok().map(|t| t.and_utc());
+    }
+    let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Local.from_local_datetime(&local).earliest().map(|t| t.with_timezone(&Utc))
+}
+
+fn parse_ics(body: &str) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    // Unfold continuation lines first (RFC 5545 3.1)
+    let mut lines: Vec<String> = Vec::new();
+    for line in body.lines() {
+        match (line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.trim_end().to_string()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let (mut start, mut end) = (None, None);
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else { continue };
+        let property = name.split(';').next().unwrap_or("");
+        match property {
+            "BEGIN" if value == "VEVENT" => (start, end) = (None, None),
+            // All-day events carry VALUE=DATE and don't parse as date-times, so they're skipped
+            "DTSTART" => start = parse_ics_time(value),
+            "DTEND" => end = parse_ics_time(value),
+            "END" if value == "VEVENT" => {
+                if let (Some(s), Some(e)) = (start, end) {
+                    events.push((s, e));
+                }
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+async fn fetch_calendar(url: &str) -> Result<Vec<(DateTime<Utc>, DateTime<Utc>)>, String> {
+    let client = reqwest::Client::builder().timeout(CALENDAR_TIMEOUT).build().map_err(|e| e.to_string())?;
+    let body = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .error_for_status()
+        .map_err(|e| e.to_string())?
+        .text()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(parse_ics(&body))
+}
+
+/// Whether an event is on right now, refreshing the feed every 15 minutes.
+/// A feed that can't be fetched keeps its last known events.
+async fn in_calendar_event(url: &str, now: DateTime<Utc>) -> bool {
+    let stale = match CALENDAR.lock().unwrap().as_ref() {
+        Some(calendar) => calendar.url != url || now - calendar.fetched_at >= CALENDAR_REFRESH,
+        None => true,
+    };
+    if stale {
+        match fetch_calendar(url).await {
+            Ok(events) => *CALENDAR.lock().unwrap() = Some(Calendar { url: url.to_string(), fetched_at: now, events }),
+            Err(e) => eprintln!("Failed to fetch calendar: {}", e),
+        }
+    }
+
+    CALENDAR
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|calendar| calendar.events.iter().any(|(start, end)| *start <= now && now < *end))
+}
+
+async fn should_capture_now(rules: &FocusRules) -> bool {
+    let now = Local::now();
+    if let Some(hours) = rules.hours.as_deref().and_then(parse_hours) {
+        if !within_hours(hours, now.time()) {
+            return false;
+        }
+    }
+    if let Some(days) = &rules.days {
+        let today = DAY_NAMES[now.weekday().num_days_from_monday() as usize];
+        if !days.iter().any(|d| d == today) {
+            return false;
+        }
+    }
+    if let Some(url) = &rules.calendar_url {
+        if in_calendar_event(url, now.with_timezone(&Utc)).await {
+            return false;
+        }
+    }
+    true
+}
+
+/// Re-evaluates the rules; called by the scheduler. Emits "focus-schedule"
+/// with the new state when capture switches on or off.
+pub async fn evaluate(app: &AppHandle, pool: &Pool<Sqlite>) {
+    let outside = !should_capture_now(&load_rules(pool).await).await;
+    if OUTSIDE.swap(outside, Ordering::Relaxed) != outside {
+        let _ = app.emit("focus-schedule", !outside);
+    }
+}
+
+#[tauri::command]
+pub async fn get_focus_rules(state: tauri::State<'_, DbState>) -> Result<FocusRules, String> {
+    Ok(load_rules(&state.pool).await)
+}
+
+/// Replaces the rules; empty or missing fields turn that rule off.
+#[tauri::command]
+pub async fn set_focus_rules(app_handle: AppHandle, state: tauri::State<'_, DbState>, rules: FocusRules) -> Result<(), String> {
+    let hours = rules.hours.map(|h| h.trim().to_string()).filter(|h| !h.is_empty());
+    if let Some(hours) = &hours {
+        parse_hours(hours).ok_or_else(|| format!("Invalid hours, expected HH:MM-HH:MM: {}", hours))?;
+    }
+    let days = rules.days.map(|days| days.iter().map(|d| d.trim().to_lowercase()).collect::<Vec<_>>());
+    if let Some(bad) = days.iter().flatten().find(|d| !DAY_NAMES.contains(&d.as_str())) {
+        return Err(format!("Invalid day: {}", bad));
+    }
+    let url = rules.calendar_url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    for (key, value) in [
+        ("capture_hours", hours),
+        ("capture_days", days.map(|d| d.join(","))),
+        ("calendar_ics_url", url),
+    ] {
+        sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
+            .bind(key)
+            .bind(value.unwrap_or_default())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    evaluate(&app_handle, &state.pool).await;
+    Ok(())
+}
@@ -0,0 +1,41 @@
+// The window that had focus when something was copied.
+// Best effort: on Wayland and in sandboxes this is usually unavailable.
+
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Default)]
+pub struct ForegroundWindow {
+    pub title: Option<String>,
+    pub app_name: Option<String>,
+    pub app_id: Option<String>, // bundle id on macOS, executable name elsewhere
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))] // read for app icons on Windows
+    pub process_path: Option<PathBuf>,
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() { None } else { Some(value.to_string()) }
+}
+
+pub fn foreground_window() -> Option<ForegroundWindow> {
+    let window = active_win_pos_rs::get_active_window().ok()?;
+    Some(ForegroundWindow {
+        title: non_empty(&window.title),
+        app_name: non_empty(&window.app_name),
+        app_id: app_id(&window.process_path),
+        process_path: Some(window.process_path).filter(|p| !p.as_os_str().is_empty()),
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn app_id(_process_path: &std::path::Path) -> Option<String> {
+    use objc2_app_kit::NSWorkspace;
+
+    let app = NSWorkspace::sharedWorkspace().frontmostApplication()?;
+    app.bundleIdentifier().and_then(|id| non_empty(&id.to_string()))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn app_id(process_path: &std::path::Path) -> Option<String> {
+    process_path.file_name().and_then(|name| non_empty(&name.to_string_lossy()))
+}
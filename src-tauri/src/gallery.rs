@@ -0,0 +1,133 @@
+// Paged image clips for the grid gallery: small thumbnails and sizes up front,
+// the full image only when the UI asks for it (via its file path). Also serves
+// the stored thumbnail files the history list shows instead of full images.
+
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+
+use crate::{export::thumbnail_data_uri, images, DbState};
+
+const GALLERY_PAGE_SIZE: i64 = 60;
+const GALLERY_THUMBNAIL_SIZE: u32 = 200;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GalleryImage {
+    id: String,
+    created_at: String,
+    is_favorite: bool,
+    width: Option<u32>,
+    height: Option<u32>,
+    dominant_color: Option<String>,
+    bytes: Option<u64>,
+    thumbnail: Option<String>, // PNG data URI
+    image_path: Option<String>, // omitted when only thumbnails were requested
+}
+
+#[derive(sqlx::FromRow)]
+struct GalleryRow {
+    id: String,
+    created_at: String,
+    is_favorite: bool,
+    image_path: Option<String>,
+    image_width: Option<i64>,
+    image_height: Option<i64>,
+    dominant_color: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GalleryPage {
+    page: i64,
+    total: i64,
+    has_more: bool,
+    images: Vec<GalleryImage>,
+}
+
+/// One page (0-based) of image clips, newest first.
+#[tauri::command]
+pub async fn get_image_clips(state: tauri::State<'_, DbState>, page: Option<i64>, page_size: Option<i64>, thumb_only: Option<bool>) -> Result<GalleryPage, String> {
+    let page = page.unwrap_or(0).max(0);
+    let page_size = page_size.unwrap_or(GALLERY_PAGE_SIZE).clamp(1, GALLERY_PAGE_SIZE * 4);
+    let thumb_only = thumb_only.unwrap_or(false);
+
+    let (total,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM clips WHERE clip_type = 'image' AND deleted_at IS NULL")
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let rows: Vec<GalleryRow> = sqlx::query_as(
+        "SELECT id, created_at, is_favorite, image_path, image_width, image_height, dominant_color FROM clips WHERE clip_type = 'image' AND deleted_at IS NULL
+         ORDER BY created_at DESC, id DESC LIMIT ? OFFSET ?"
+    )
+    .bind(page_size)
+    .bind(page * page_size)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Decoding for thumbnails is CPU-bound; keep it off the async runtime
+    let images = tauri::async_runtime::spawn_blocking(move || {
+        rows.into_iter()
+            .map(|row| {
+                let path = row.image_path.as_deref();
+                // Stored at capture time; older clips fall back to reading the file header
+                let (width, height) = match (row.image_width, row.image_height) {
+                    (Some(w), Some(h)) => (Some(w as u32), Some(h as u32)),
+                    _ => path
+                        .and_then(|p| image::image_dimensions(p).ok())
+                        .map_or((None, None), |(w, h)| (Some(w), Some(h))),
+                };
+                GalleryImage {
+                    id: row.id,
+                    created_at: row.created_at,
+                    is_favorite: row.is_favorite,
+                    width,
+                    height,
+                    dominant_color: row.dominant_color,
+                    bytes: path.and_then(|p| std::fs::metadata(p).ok()).map(|m| m.len()),
+                    thumbnail: path.and_then(|p| thumbnail_data_uri(p, GALLERY_THUMBNAIL_SIZE)),
+                    image_path: if thumb_only { None } else { row.image_path },
+                }
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(GalleryPage {
+        page,
+        total,
+        has_more: (page + 1) * page_size < total,
+        images,
+    })
+}
+
+/// Path of the clip's thumbnail file, creating it first for images stored
+/// before thumbnails existed. None for text clips or a missing image.
+#[tauri::command]
+pub async fn get_thumbnail(state: tauri::State<'_, DbState>, id: String) -> Result<Option<String>, String> {
+    let row: Option<(Option<String>, Option<String>)> = sqlx::query_as("SELECT image_path, thumbnail_path FROM clips WHERE id = ? AND clip_type = 'image'")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let Some((Some(image_path), thumbnail_path)) = row else { return Ok(None) };
+    if let Some(thumbnail_path) = thumbnail_path.filter(|p| Path::new(p).exists()) {
+        return Ok(Some(thumbnail_path));
+    }
+
+    let created = tauri::async_runtime::spawn_blocking(move || -> Result<String, String> {
+        let img = images::open_rgba(&image_path)?;
+        Ok(images::save_thumbnail(Path::new(&image_path), &img)?.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    let Ok(thumbnail_path) = created else { return Ok(None) };
+    sqlx::query("UPDATE clips SET thumbnail_path = ? WHERE id = ?")
+        .bind(&thumbnail_path)
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    state.recent.invalidate();
+    Ok(Some(thumbnail_path))
+}
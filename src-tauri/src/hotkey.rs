@@ -0,0 +1,190 @@
+// System-wide shortcuts. The main one shows Klip with the search box focused,
+// or hides it again when it's already in front; others, unset by default,
+// undo Klip's last clipboard write and paste the next queued clip. Each is
+// stored in its own setting; an empty value turns that shortcut off.
+
+use tauri::{AppHandle, Manager, Wry};
+use tauri::plugin::TauriPlugin;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::{paste_queue, restore, setting_value, tray, DbState};
+
+pub const DEFAULT_SHORTCUT: &str = "CmdOrCtrl+Shift+V";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HotkeyAction {
+    QuickPicker,
+    RestorePrevious,
+    PasteNext,
+}
+
+impl HotkeyAction {
+    const ALL: [HotkeyAction; 3] = [HotkeyAction::QuickPicker, HotkeyAction::RestorePrevious, HotkeyAction::PasteNext];
+
+    fn setting_key(self) -> &'static str {
+        match self {
+            HotkeyAction::QuickPicker => "global_shortcut",
+            HotkeyAction::RestorePrevious => "restore_previous_shortcut",
+            HotkeyAction::PasteNext => "paste_next_shortcut",
+        }
+    }
+
+    fn default_shortcut(self) -> Option<&'static str> {
+        match self {
+            HotkeyAction::QuickPicker => Some(DEFAULT_SHORTCUT),
+            HotkeyAction::RestorePrevious | HotkeyAction::PasteNext => None,
+        }
+    }
+}
+
+// Registered shortcuts by `Shortcut::id`, for dispatching presses
+static REGISTERED: Mutex<Option<HashMap<u32, HotkeyAction>>> = Mutex::new(None);
+
+pub fn plugin() -> TauriPlugin<Wry> {
+    tauri_plugin_global_shortcut::Builder::new()
+        .with_handler(|app, shortcut, event| {
+            if event.state != ShortcutState::Pressed {
+                return;
+            }
+            let action = REGISTERED.lock().unwrap().as_ref().and_then(|map| map.get(&shortcut.id()).copied());
+            match action {
+                Some(HotkeyAction::QuickPicker) => on_pressed(app),
+                Some(HotkeyAction::RestorePrevious) => {
+                    if let Err(e) = restore::restore_previous() {
+                        eprintln!("Failed to restore the previous clipboard: {}", e);
+                    }
+                }
+                Some(HotkeyAction::PasteNext) => {
+                    // The target app is already in front
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = paste_queue::paste_next_clip(&app, false).await {
+                            eprintln!("Failed to paste the next queued clip: {}", e);
+                        }
+                    });
+                }
+                None => {}
+            }
+        })
+        .build()
+}
+
+fn on_pressed(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else { return };
+    if window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false) {
+        let _ = window.hide();
+    } else {
+        tray::open_quick_picker(app);
+    }
+}
+
+/// The configured shortcut for `action`, `None` when turned off.
+async fn action_shortcut(pool: &Pool<Sqlite>, action: HotkeyAction) -> Option<String> {
+    match setting_value(pool, action.setting_key()).await {
+        Some(value) if value.is_empty() => None,
+        Some(value) => Some(value),
+        None => action.default_shortcut().map(str::to_string),
+    }
+}
+
+/// The configured quick picker shortcut, `None` when turned off.
+pub async fn shortcut(pool: &Pool<Sqlite>) -> Option<String> {
+    action_shortcut(pool, HotkeyAction::QuickPicker).await
+}
+
+fn register(app: &AppHandle, shortcut: &str, action: HotkeyAction) -> Result<(), String> {
+    let parsed = shortcut.parse::<Shortcut>().map_err(|e| format!("Invalid shortcut {}: {}", shortcut, e))?;
+    app.global_shortcut().register(parsed).map_err(|e| e.to_string())?;
+    REGISTERED.lock().unwrap().get_or_insert_with(HashMap::new).insert(parsed.id(), action);
+    Ok(())
+}
+
+fn unregister(app: &AppHandle, shortcut: &str) {
+    let Ok(parsed) = shortcut.parse::<Shortcut>() else { return };
+    let _ = app.global_shortcut().unregister(parsed);
+    if let Some(map) = REGISTERED.lock().unwrap().as_mut() {
+        map.remove(&parsed.id());
+    }
+}
+
+/// Registers the saved shortcuts at startup, and again after a config import
+/// (all previous ones are dropped first). Another app may already own a
+/// combination; that's logged rather than treated as fatal.
+pub async fn restore(app: &AppHandle) {
+    let Some(state) = app.try_state::<DbState>() else { return };
+    let _ = app.global_shortcut().unregister_all();
+    *REGISTERED.lock().unwrap() = None;
+    for action in HotkeyAction::ALL {
+        if let Some(shortcut) = action_shortcut(&state.pool, action).await {
+            if let Err(e) = register(app, &shortcut, action) {
+                eprintln!("Failed to register global shortcut {}: {}", shortcut, e);
+            }
+        }
+    }
+}
+
+/// Swaps the shortcut for `action`; `None` or empty turns it off. If the new
+/// one can't be registered the old one stays active.
+async fn set_shortcut(app: &AppHandle, pool: &Pool<Sqlite>, action: HotkeyAction, shortcut: Option<String>) -> Result<(), String> {
+    let new = shortcut.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+    if let Some(new) = &new {
+        new.parse::<Shortcut>().map_err(|e| format!("Invalid shortcut {}: {}", new, e))?;
+    }
+
+    let old = action_shortcut(pool, action).await;
+    if let Some(old) = &old {
+        unregister(app, old);
+    }
+    if let Some(new) = &new {
+        if let Err(e) = register(app, new, action) {
+            if let Some(old) = &old {
+                let _ = register(app, old, action);
+            }
+            return Err(e);
+        }
+    }
+
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
+        .bind(action.setting_key())
+        .bind(new.unwrap_or_default())
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_global_shortcut(state: tauri::State<'_, DbState>) -> Result<Option<String>, String> {
+    Ok(shortcut(&state.pool).await)
+}
+
+/// Swaps the quick picker shortcut, e.g. "Alt+Space"; `None` or empty turns it off.
+#[tauri::command]
+pub async fn set_global_shortcut(app_handle: AppHandle, state: tauri::State<'_, DbState>, shortcut: Option<String>) -> Result<(), String> {
+    set_shortcut(&app_handle, &state.pool, HotkeyAction::QuickPicker, shortcut).await
+}
+
+#[tauri::command]
+pub async fn get_restore_previous_shortcut(state: tauri::State<'_, DbState>) -> Result<Option<String>, String> {
+    Ok(action_shortcut(&state.pool, HotkeyAction::RestorePrevious).await)
+}
+
+/// Sets the shortcut that runs `restore_previous_clipboard`; off by default.
+#[tauri::command]
+pub async fn set_restore_previous_shortcut(app_handle: AppHandle, state: tauri::State<'_, DbState>, shortcut: Option<String>) -> Result<(), String> {
+    set_shortcut(&app_handle, &state.pool, HotkeyAction::RestorePrevious, shortcut).await
+}
+
+#[tauri::command]
+pub async fn get_paste_next_shortcut(state: tauri::State<'_, DbState>) -> Result<Option<String>, String> {
+    Ok(action_shortcut(&state.pool, HotkeyAction::PasteNext).await)
+}
+
+/// Sets the shortcut that pastes the next clip of the paste queue; off by default.
+#[tauri::command]
+pub async fn set_paste_next_shortcut(app_handle: AppHandle, state: tauri::State<'_, DbState>, shortcut: Option<String>) -> Result<(), String> {
+    set_shortcut(&app_handle, &state.pool, HotkeyAction::PasteNext, shortcut).await
+}
@@ -0,0 +1,120 @@
+// Helpers for stored image clips. Metadata and a small thumbnail are computed
+// once, when the image is stored, so list views never need to decode files.
+// Full-size buffers are the memory hot spot (an 8K screenshot is ~130 MB of
+// RGBA), so nothing here makes a second full copy.
+
+use std::fs::File;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use image::{imageops, ImageReader, RgbaImage};
+use sha2::{Digest, Sha256};
+
+// Bytes between samples for the cheap change check in the monitor
+const FINGERPRINT_STRIDE: usize = 4093;
+// Rows handed to the PNG encoder at a time when saving
+const SAVE_CHUNK_ROWS: usize = 64;
+// Longest side of list view thumbnails
+pub const THUMBNAIL_SIZE: u32 = 256;
+
+pub struct ImageMeta {
+    pub width: u32,
+    pub height: u32,
+    pub dominant_color: String, // "#rrggbb"
+}
+
+/// Average color of the opaque pixels, sampled from a tiny downscale.
+pub fn dominant_color(img: &RgbaImage) -> String {
+    let small = imageops::thumbnail(img, 16, 16);
+    let (mut r, mut g, mut b, mut n) = (0u64, 0u64, 0u64, 0u64);
+    for pixel in small.pixels().filter(|p| p[3] >= 128) {
+        r += pixel[0] as u64;
+        g += pixel[1] as u64;
+        b += pixel[2] as u64;
+        n += 1;
+    }
+    if n == 0 {
+        return "#000000".to_string();
+    }
+    format!("#{:02x}{:02x}{:02x}", r / n, g / n, b / n)
+}
+
+/// SHA-256 of the dimensions and raw RGBA pixels, hex encoded.
+pub fn content_hash(width: u32, height: u32, rgba: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(width.to_le_bytes());
+    hasher.update(height.to_le_bytes());
+    hasher.update(rgba);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Cheap sampled fingerprint, used to notice that the clipboard image changed
+/// without hashing every byte once a second.
+pub fn fingerprint(width: u32, height: u32, rgba: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (width, height, rgba.len()).hash(&mut hasher);
+    rgba.iter().step_by(FINGERPRINT_STRIDE).for_each(|b| b.hash(&mut hasher));
+    rgba.get(rgba.len().saturating_sub(64)..).hash(&mut hasher);
+    hasher.finish()
+}
+
+pub fn image_meta(img: &RgbaImage) -> ImageMeta {
+    ImageMeta {
+        width: img.width(),
+        height: img.height(),
+        dominant_color: dominant_color(img),
+    }
+}
+
+/// Writes an image as PNG, feeding the encoder a few rows at a time. The
+/// one-shot encoder filters the whole image into a second buffer first.
+pub fn save_png(path: &Path, img: &RgbaImage) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), img.width(), img.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    let mut stream = writer.stream_writer().map_err(|e| e.to_string())?;
+    let row_bytes = img.width() as usize * 4;
+    for chunk in img.as_raw().chunks(row_bytes * SAVE_CHUNK_ROWS) {
+        stream.write_all(chunk).map_err(|e| e.to_string())?;
+    }
+    stream.finish().map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())
+}
+
+/// Decodes a stored image as RGBA. Clip images are saved as RGBA already, so
+/// the conversion is free; `image::open(..).to_rgba8()` always copied.
+pub fn open_rgba(path: impl AsRef<Path>) -> Result<RgbaImage, String> {
+    let reader = ImageReader::open(path).map_err(|e| e.to_string())?;
+    let img = reader.with_guessed_format().map_err(|e| e.to_string())?.decode().map_err(|e| e.to_string())?;
+    Ok(img.into_rgba8())
+}
+
+/// Where the thumbnail of a stored image goes: `thumbnails/<name>.png` next to it.
+pub fn thumbnail_path(image_path: &Path) -> PathBuf {
+    let name = image_path.file_stem().map(|s| s.to_os_string()).unwrap_or_default();
+    image_path.with_file_name("thumbnails").join(name).with_extension("png")
+}
+
+/// Writes the thumbnail for an image stored at `image_path`, at most
+/// `THUMBNAIL_SIZE` on its longest side, and returns its path.
+pub fn save_thumbnail(image_path: &Path, img: &RgbaImage) -> Result<PathBuf, String> {
+    let path = thumbnail_path(image_path);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let (width, height) = img.dimensions();
+    let scale = (THUMBNAIL_SIZE as f64 / width.max(height).max(1) as f64).min(1.0);
+    let (thumb_width, thumb_height) = (((width as f64 * scale) as u32).max(1), ((height as f64 * scale) as u32).max(1));
+    save_png(&path, &imageops::thumbnail(img, thumb_width, thumb_height))?;
+    Ok(path)
+}
+
+/// Deletes a stored image and its thumbnail.
+pub fn remove_image(image_path: impl AsRef<Path>) -> std::io::Result<()> {
+    let image_path = image_path.as_ref();
+    let _ = std::fs::remove_file(thumbnail_path(image_path));
+    std::fs::remove_file(image_path)
+}
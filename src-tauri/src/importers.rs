@@ -0,0 +1,200 @@
+// Importers for other clipboard managers' history.
+// Each parser yields `ImportedItem`s; `ingest` dedups, inserts and reports progress.
+
+use tauri::{AppHandle, Emitter, Manager};
+use sqlx::{sqlite::SqliteConnectOptions, Pool, Sqlite, SqlitePool};
+use std::path::PathBuf;
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+use crate::{capture, store, DbState};
+
+const PROGRESS_EVERY: usize = 200;
+// Core Data stores dates as seconds since 2001-01-01T00:00:00Z
+const CORE_DATA_EPOCH_OFFSET: i64 = 978_307_200;
+
+pub struct ImportedItem {
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub is_favorite: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ImportReport {
+    pub total: usize,
+    pub imported: usize,
+    pub skipped: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct ImportProgress {
+    source: String,
+    processed: usize,
+    total: usize,
+}
+
+async fn open_readonly(path: &str) -> Result<SqlitePool, String> {
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+    SqlitePool::connect_with(options).await.map_err(|e| e.to_string())
+}
+
+fn home_dir() -> PathBuf {
+    std::env::var("HOME").map(PathBuf::from).unwrap_or_default()
+}
+
+fn default_path(source: &str) -> Option<PathBuf> {
+    match source {
+        "maccy" => {
+            let dir = home_dir().join("Library/Containers/org.p0deje.Maccy/Data/Library/Application Support/Maccy");
+            // Maccy 2 (SwiftData) renamed the store
+            ["default.store", "Storage.sqlite"].iter().map(|f| dir.join(f)).find(|p| p.exists())
+        }
+        "ditto" => std::env::var("APPDATA").ok().map(|d| PathBuf::from(d).join("Ditto").join("Ditto.db")),
+        _ => None,
+    }
+}
+
+async fn read_maccy(path: &str) -> Result<Vec<ImportedItem>, String> {
+    let db = open_readonly(path).await?;
+    let rows: Vec<(Vec<u8>, Option<f64>, Option<String>)> = sqlx::query_as(
+        "SELECT c.ZVALUE, i.ZLASTCOPIEDAT, i.ZPIN
+         FROM ZHISTORYITEMCONTENT c JOIN ZHISTORYITEM i ON c.ZITEM = i.Z_PK
+         WHERE c.ZTYPE = 'public.utf8-plain-text' AND c.ZVALUE IS NOT NULL"
+    )
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+    db.close().await;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(value, copied_at, pin)| {
+            let content = String::from_utf8(value).ok()?;
+            let seconds = copied_at.unwrap_or(0.0) as i64 + CORE_DATA_EPOCH_OFFSET;
+            Some(ImportedItem {
+                content,
+                created_at: Utc.timestamp_opt(seconds, 0).single().unwrap_or_else(Utc::now),
+                is_favorite: pin.is_some_and(|p| !p.is_empty()),
+            })
+        })
+        .collect())
+}
+
+async fn read_ditto(path: &str) -> Result<Vec<ImportedItem>, String> {
+    let db = open_readonly(path).await?;
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT mText, lDate FROM Main WHERE bIsGroup = 0 AND mText IS NOT NULL AND mText != ''"
+    )
+    .fetch_all(&db)
+    .await
+    .map_err(|e| e.to_string())?;
+    db.close().await;
+
+    Ok(rows
+        .into_iter()
+        .map(|(content, date)| ImportedItem {
+            content,
+            created_at: Utc.timestamp_opt(date, 0).single().unwrap_or_else(Utc::now),
+            is_favorite: false,
+        })
+        .collect())
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CopyQEntry {
+    Text(String),
+    Item { text: String, time: Option<i64> },
+}
+
+/// CopyQ keeps history in Qt-serialized tab files, so we read a JSON export instead:
+/// an array of strings (or `{ "text", "time" }` objects), e.g. from
+/// `copyq 'var r=[]; for (var i=0; i<size(); ++i) r.push(str(read(i))); print(JSON.stringify(r))'`.
+/// Entries are newest first, like CopyQ's own list.
+fn read_copyq(path: &str) -> Result<Vec<ImportedItem>, String> {
+    let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let entries: Vec<CopyQEntry> = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+    let now = Utc::now();
+
+    Ok(entries
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            // Without timestamps, keep the original order by spacing items a second apart
+            let (content, time) = match entry {
+                CopyQEntry::Text(text) => (text, None),
+                CopyQEntry::Item { text, time } => (text, time),
+            };
+            let created_at = time
+                .and_then(|t| Utc.timestamp_opt(t, 0).single())
+                .unwrap_or_else(|| now - chrono::Duration::seconds(i as i64));
+            ImportedItem { content, created_at, is_favorite: false }
+        })
+        .collect())
+}
+
+/// Inserts items that aren't already in history, in batched transactions.
+/// Dedup goes through the `content_hash` index, as captures do.
+pub async fn ingest(app: &AppHandle, pool: &Pool<Sqlite>, source: &str, items: Vec<ImportedItem>) -> Result<ImportReport, String> {
+    let mut report = ImportReport { total: items.len(), ..Default::default() };
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+    for (i, item) in items.into_iter().enumerate() {
+        if item.content.trim().is_empty() {
+            report.skipped += 1;
+            continue;
+        }
+
+        let exists: Option<(i32,)> = sqlx::query_as("SELECT 1 FROM clips WHERE content_hash = ? OR (content_hash IS NULL AND content = ?) LIMIT 1")
+            .bind(capture::text_hash(&item.content))
+            .bind(&item.content)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if exists.is_some() {
+            report.skipped += 1;
+        } else {
+            store::insert_text_clip(&mut *tx, &Uuid::new_v4().to_string(), &item.content, &item.created_at.to_rfc3339(), item.is_favorite).await?;
+            report.imported += 1;
+        }
+
+        let processed = i + 1;
+        if processed % PROGRESS_EVERY == 0 {
+            tx.commit().await.map_err(|e| e.to_string())?;
+            tx = pool.begin().await.map_err(|e| e.to_string())?;
+            let _ = app.emit("import-progress", ImportProgress { source: source.to_string(), processed, total: report.total });
+        }
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())?;
+    if let Some(state) = app.try_state::<DbState>() {
+        state.recent.invalidate();
+    }
+    let _ = app.emit("import-progress", ImportProgress { source: source.to_string(), processed: report.total, total: report.total });
+    let _ = app.emit("clipboard-changed", ());
+    Ok(report)
+}
+
+/// Imports history from another clipboard manager: "maccy", "ditto" or "copyq".
+/// `path` defaults to the app's standard database location where there is one.
+#[tauri::command]
+pub async fn import_history(app_handle: AppHandle, state: tauri::State<'_, DbState>, source: String, path: Option<String>) -> Result<ImportReport, String> {
+    let path = match path.filter(|p| !p.is_empty()) {
+        Some(p) => p,
+        None => default_path(&source)
+            .ok_or_else(|| format!("No default location for {}, please choose the file", source))?
+            .to_string_lossy()
+            .to_string(),
+    };
+
+    let items = match source.as_str() {
+        "maccy" => read_maccy(&path).await?,
+        "ditto" => read_ditto(&path).await?,
+        "copyq" => read_copyq(&path)?,
+        other => return Err(format!("Unsupported import source: {}", other)),
+    };
+
+    ingest(&app_handle, &state.pool, &source, items).await
+}
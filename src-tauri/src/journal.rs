@@ -0,0 +1,131 @@
+// Daily journal: clips favorited on a given day are appended to
+// `<journal_folder>/<YYYY-MM-DD>.md`, a running log of things worth keeping.
+// Opt-in with `daily_journal`; the scheduler writes new favorites hourly.
+// `journal_written_until` remembers the last favorite written, so every clip
+// lands in the journal once even if the app was closed for days.
+
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use sqlx::{Pool, Sqlite};
+use chrono::prelude::*;
+
+use crate::classify::{guess_language, is_text_type};
+use crate::code_block::fenced;
+use crate::{files, setting_enabled, setting_value, trash, DbState};
+
+const WATERMARK: &str = "journal_written_until";
+
+#[derive(sqlx::FromRow)]
+struct JournalRow {
+    content: String,
+    clip_type: String,
+    image_path: Option<String>,
+    is_sensitive: bool,
+    detected_kind: Option<String>,
+    link_title: Option<String>,
+    source_app: Option<String>,
+    favorited_at: String,
+}
+
+pub async fn enabled(pool: &Pool<Sqlite>) -> bool {
+    setting_enabled(pool, "daily_journal", false).await
+}
+
+pub async fn folder(pool: &Pool<Sqlite>) -> Option<PathBuf> {
+    setting_value(pool, "journal_folder").await.filter(|f| !f.trim().is_empty()).map(|f| PathBuf::from(f.trim()))
+}
+
+fn entry(row: &JournalRow, at: DateTime<Local>) -> String {
+    let mut entry = format!("## {}", at.format("%H:%M"));
+    if let Some(app) = &row.source_app {
+        let _ = write!(entry, " · {}", app);
+    }
+    entry.push_str("\n\n");
+
+    if row.is_sensitive {
+        entry.push_str("*Sensitive clip, not copied here.*");
+    } else if row.clip_type == "image" {
+        match &row.image_path {
+            Some(path) => entry.push_str(&format!("![Image](<{}>)", path)),
+            None => entry.push_str("*Image*"),
+        }
+    } else if row.clip_type == "files" {
+        let paths = files::decode(&row.content).unwrap_or_default();
+        let lines: Vec<String> = paths.iter().map(|p| format!("- `{}`", p.display())).collect();
+        entry.push_str(&lines.join("\n"));
+    } else if row.clip_type == "url" {
+        let url = row.content.trim();
+        let _ = write!(entry, "[{}](<{}>)", row.link_title.as_deref().unwrap_or(url).replace(['[', ']'], ""), url);
+    } else if is_text_type(&row.clip_type) {
+        match row.detected_kind.as_deref() {
+            Some("code") => entry.push_str(&fenced(&row.content, guess_language(&row.content).unwrap_or_default())),
+            Some("json") => entry.push_str(&fenced(&row.content, "json")),
+            _ => {
+                let quoted: Vec<String> = row.content.trim_end().lines().map(|l| format!("> {}", l).trim_end().to_string()).collect();
+                entry.push_str(&quoted.join("\n"));
+            }
+        }
+    }
+    entry.push_str("\n\n");
+    entry
+}
+
+fn append(path: &Path, day: NaiveDate, entries: &str) -> Result<(), String> {
+    let is_new = !path.exists();
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path).map_err(|e| e.to_string())?;
+    if is_new {
+        write!(file, "# {}\n\n", day.format("%A, %B %-d, %Y")).map_err(|e| e.to_string())?;
+    }
+    file.write_all(entries.as_bytes()).map_err(|e| e.to_string())
+}
+
+/// Appends clips favorited since the last run to their day's journal file.
+/// Returns how many were written.
+pub async fn write_new_favorites(pool: &Pool<Sqlite>) -> Result<usize, String> {
+    let folder = folder(pool).await.ok_or_else(|| "Choose a journal folder (journal_folder) first".to_string())?;
+    if !folder.is_absolute() {
+        return Err("The journal folder must be an absolute path".to_string());
+    }
+    let since = setting_value(pool, WATERMARK).await.unwrap_or_default();
+    let rows = sqlx::query_as::<_, JournalRow>(&format!(
+        "SELECT content, clip_type, image_path, is_sensitive, detected_kind, link_title, source_app, favorited_at
+         FROM clips WHERE is_favorite = 1 AND favorited_at > ? AND {} ORDER BY favorited_at, id",
+        trash::NOT_DELETED
+    ))
+    .bind(&since)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    let Some(last) = rows.last().map(|r| r.favorited_at.clone()) else { return Ok(0) };
+
+    std::fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+    let mut days: Vec<(NaiveDate, String)> = Vec::new();
+    for row in &rows {
+        let Ok(at) = DateTime::parse_from_rfc3339(&row.favorited_at) else { continue };
+        let at = at.with_timezone(&Local);
+        let text = entry(row, at);
+        match days.last_mut() {
+            Some((day, entries)) if *day == at.date_naive() => entries.push_str(&text),
+            _ => days.push((at.date_naive(), text)),
+        }
+    }
+    for (day, entries) in &days {
+        append(&folder.join(format!("{}.md", day.format("%Y-%m-%d"))), *day, entries)?;
+    }
+
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
+        .bind(WATERMARK)
+        .bind(&last)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(rows.len())
+}
+
+/// Writes pending favorites to the journal now instead of waiting for the
+/// scheduler. Works even while `daily_journal` is off.
+#[tauri::command]
+pub async fn write_journal_now(state: tauri::State<'_, DbState>) -> Result<usize, String> {
+    write_new_favorites(&state.pool).await
+}
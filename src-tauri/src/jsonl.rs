@@ -0,0 +1,118 @@
+// History as JSON Lines: one clip per line, oldest first, for piping into jq
+// or pandas. Clips are read and written in batches, so memory stays flat
+// however long the history is. Every line carries every field (null when
+// unset), and field names are part of the format: add fields, never rename.
+
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use serde::Serialize;
+
+use crate::export::{filter_clause, ExportFilter};
+use crate::{Clip, DbState, CLIP_COLUMNS};
+
+const BATCH_SIZE: usize = 500;
+
+#[derive(Serialize)]
+struct JsonlRecord<'a> {
+    id: &'a str,
+    created_at: &'a str, // ISO 8601
+    #[serde(rename = "type")]
+    clip_type: &'a str,
+    content: &'a str,
+    favorite: bool,
+    pinned: bool,
+    locked: bool,
+    tags: &'a [String],
+    source_app: Option<&'a str>,
+    source_url: Option<&'a str>,
+    window_title: Option<&'a str>,
+    image_path: Option<&'a str>,
+    image_width: Option<i64>,
+    image_height: Option<i64>,
+}
+
+async fn batch_tags(pool: &Pool<Sqlite>, clips: &[Clip]) -> Result<HashMap<String, Vec<String>>, String> {
+    let query = format!(
+        "SELECT ct.clip_id, t.name FROM clip_tags ct JOIN tags t ON t.id = ct.tag_id \
+         WHERE ct.clip_id IN ({}) ORDER BY t.name COLLATE NOCASE",
+        vec!["?"; clips.len()].join(", ")
+    );
+    let mut query_builder = sqlx::query_as::<_, (String, String)>(&query);
+    for clip in clips {
+        query_builder = query_builder.bind(&clip.id);
+    }
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for (clip_id, name) in query_builder.fetch_all(pool).await.map_err(|e| e.to_string())? {
+        tags.entry(clip_id).or_default().push(name);
+    }
+    Ok(tags)
+}
+
+/// Writes the clips matching `filter` to `out`, one JSON object per line.
+/// Returns the clip count.
+pub async fn write_jsonl<W: Write>(pool: &Pool<Sqlite>, filter: &ExportFilter, out: &mut W) -> Result<usize, String> {
+    let (clause, args) = filter_clause(filter);
+    let mut cursor: Option<(String, String)> = None; // (created_at, id) of the last clip written
+    let mut written = 0;
+
+    loop {
+        let mut query = format!("SELECT {} FROM clips WHERE {}", CLIP_COLUMNS, clause);
+        if cursor.is_some() {
+            query.push_str(" AND (created_at > ? OR (created_at = ? AND id > ?))");
+        }
+        query.push_str(&format!(" ORDER BY created_at ASC, id ASC LIMIT {}", BATCH_SIZE));
+
+        let mut query_builder = sqlx::query_as::<_, Clip>(&query);
+        for arg in &args {
+            query_builder = query_builder.bind(arg);
+        }
+        if let Some((created_at, id)) = &cursor {
+            query_builder = query_builder.bind(created_at).bind(created_at).bind(id);
+        }
+        let clips = query_builder.fetch_all(pool).await.map_err(|e| e.to_string())?;
+        if clips.is_empty() {
+            break;
+        }
+
+        let tags = batch_tags(pool, &clips).await?;
+        for clip in &clips {
+            let record = JsonlRecord {
+                id: &clip.id,
+                created_at: &clip.created_at,
+                clip_type: &clip.clip_type,
+                content: &clip.content,
+                favorite: clip.is_favorite,
+                pinned: clip.pinned,
+                locked: clip.locked,
+                tags: tags.get(&clip.id).map(Vec::as_slice).unwrap_or_default(),
+                source_app: clip.source_app.as_deref(),
+                source_url: clip.source_url.as_deref(),
+                window_title: clip.window_title.as_deref(),
+                image_path: clip.image_path.as_deref(),
+                image_width: clip.image_width,
+                image_height: clip.image_height,
+            };
+            serde_json::to_writer(&mut *out, &record).map_err(|e| e.to_string())?;
+            out.write_all(b"\n").map_err(|e| e.to_string())?;
+        }
+        written += clips.len();
+
+        if clips.len() < BATCH_SIZE {
+            break;
+        }
+        let last = &clips[clips.len() - 1];
+        cursor = Some((last.created_at.clone(), last.id.clone()));
+    }
+
+    out.flush().map_err(|e| e.to_string())?;
+    Ok(written)
+}
+
+/// Exports the selected clips as JSON Lines to `path`. Returns the clip count.
+#[tauri::command]
+pub async fn export_jsonl(state: tauri::State<'_, DbState>, path: String, filter: Option<ExportFilter>) -> Result<usize, String> {
+    let file = File::create(&path).map_err(|e| e.to_string())?;
+    write_jsonl(&state.pool, &filter.unwrap_or_default(), &mut BufWriter::new(file)).await
+}
@@ -0,0 +1,81 @@
+// Secrets live in the OS credential store (macOS Keychain, Windows Credential
+// Manager, Secret Service on Linux), never in the settings table.
+
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use keyring::Entry;
+
+use crate::{backup, share_link, sync};
+
+const SERVICE: &str = "com.miguelcuevas.klip";
+// Entries the frontend may touch; internal ones like the database key are off limits
+const USER_SECRETS: &[&str] = &[
+    backup::PASSPHRASE_SECRET,
+    backup::PASSWORD_SECRET,
+    share_link::TOKEN_SECRET,
+    sync::PASSPHRASE_SECRET,
+    sync::PASSWORD_SECRET,
+];
+
+fn entry(name: &str) -> Result<Entry, String> {
+    Entry::new(SERVICE, name).map_err(|e| e.to_string())
+}
+
+pub fn get_secret(name: &str) -> Result<Option<String>, String> {
+    match entry(name)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn store_secret(name: &str, value: &str) -> Result<(), String> {
+    entry(name)?.set_password(value).map_err(|e| e.to_string())
+}
+
+pub fn remove_secret(name: &str) -> Result<(), String> {
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Random 256-bit key (hex) stored under `name`, generated on first use.
+pub fn get_or_create_key(name: &str) -> Result<String, String> {
+    if let Some(key) = get_secret(name)? {
+        return Ok(key);
+    }
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let key: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    store_secret(name, &key)?;
+    Ok(key)
+}
+
+// The frontend can store and clear secrets (API tokens, pairing keys) but never read them back.
+
+fn user_secret(name: &str) -> Result<&str, String> {
+    if USER_SECRETS.contains(&name) {
+        Ok(name)
+    } else {
+        Err(format!("Unknown secret: {}", name))
+    }
+}
+
+#[tauri::command]
+pub fn set_secret(name: String, value: String) -> Result<(), String> {
+    let name = user_secret(&name)?;
+    if value.is_empty() {
+        return remove_secret(name);
+    }
+    store_secret(name, &value)
+}
+
+#[tauri::command]
+pub fn has_secret(name: String) -> Result<bool, String> {
+    Ok(get_secret(user_secret(&name)?)?.is_some())
+}
+
+#[tauri::command]
+pub fn delete_secret(name: String) -> Result<(), String> {
+    remove_secret(user_secret(&name)?)
+}
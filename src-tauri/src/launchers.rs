@@ -0,0 +1,86 @@
+// Exporting a collection or tag of text clips as snippets for launchers:
+// Alfred snippet bundles (.alfredsnippets, a zip of one JSON file per
+// snippet) and Raycast's snippet import JSON. Klip has no snippet keywords,
+// so those are left empty; snippets are named after their first line.
+
+use std::io::{Cursor, Write};
+use serde::Serialize;
+use serde_json::json;
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::{tags, DbState};
+
+const NAME_CHARS: usize = 40;
+
+#[derive(Serialize)]
+struct RaycastSnippet {
+    name: String,
+    text: String,
+    keyword: String,
+}
+
+/// First non-blank line, shortened.
+fn snippet_name(content: &str) -> String {
+    let line = content.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or("Snippet");
+    let mut name: String = line.chars().take(NAME_CHARS).collect();
+    if line.chars().count() > NAME_CHARS {
+        name.push('…');
+    }
+    name
+}
+
+fn alfred_bundle(snippets: &[(String, String)]) -> Result<Vec<u8>, String> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, text) in snippets {
+        let uid = Uuid::new_v4().to_string().to_uppercase();
+        let entry = json!({ "alfredsnippet": { "snippet": text, "uid": uid, "name": name, "keyword": "" } });
+        let file_name: String = name.chars().map(|c| if matches!(c, '/' | '\\' | ':') { '-' } else { c }).collect();
+        zip.start_file(format!("{} [{}].json", file_name, uid), options).map_err(|e| e.to_string())?;
+        zip.write_all(entry.to_string().as_bytes()).map_err(|e| e.to_string())?;
+    }
+    Ok(zip.finish().map_err(|e| e.to_string())?.into_inner())
+}
+
+fn raycast_json(snippets: &[(String, String)]) -> Result<Vec<u8>, String> {
+    let snippets: Vec<RaycastSnippet> = snippets
+        .iter()
+        .map(|(name, text)| RaycastSnippet { name: name.clone(), text: text.clone(), keyword: String::new() })
+        .collect();
+    serde_json::to_vec_pretty(&snippets).map_err(|e| e.to_string())
+}
+
+/// Writes the text clips of a collection or tag (by name) as a launcher
+/// snippet file. `format` is "alfred" or "raycast". Returns how many snippets
+/// were written.
+#[tauri::command]
+pub async fn export_launcher_snippets(state: tauri::State<'_, DbState>, path: String, format: String, collection_id: Option<String>, tag: Option<String>) -> Result<usize, String> {
+    let rows: Vec<(String,)> = match (collection_id, tag) {
+        (Some(collection_id), _) => sqlx::query_as("SELECT content FROM clips WHERE clip_type IN ('text', 'url') AND deleted_at IS NULL AND collection_id = ? ORDER BY created_at")
+            .bind(collection_id)
+            .fetch_all(&state.pool)
+            .await,
+        (None, Some(tag)) => sqlx::query_as(&format!("SELECT content FROM clips WHERE clip_type IN ('text', 'url') AND deleted_at IS NULL AND {} ORDER BY created_at", tags::HAS_TAG))
+            .bind(tag.trim().trim_start_matches('#'))
+            .fetch_all(&state.pool)
+            .await,
+        (None, None) => return Err("Pick a collection or a tag to export".to_string()),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let snippets: Vec<(String, String)> = rows
+        .into_iter()
+        .filter(|(content,)| !content.trim().is_empty())
+        .map(|(content,)| (snippet_name(&content), content))
+        .collect();
+    let data = match format.as_str() {
+        "alfred" => alfred_bundle(&snippets)?,
+        "raycast" => raycast_json(&snippets)?,
+        other => return Err(format!("Unknown snippet format: {}", other)),
+    };
+    let mut file = std::fs::File::create(&path).map_err(|e| e.to_string())?;
+    file.write_all(&data).map_err(|e| e.to_string())?;
+    Ok(snippets.len())
+}
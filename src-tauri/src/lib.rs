@@ -1,31 +1,229 @@
 use tauri::{AppHandle, Manager, Emitter, Listener, WindowEvent};
-use tauri::menu::{Menu, MenuItem};
-use tauri::tray::{TrayIconBuilder, MouseButton, TrayIconEvent};
+use tauri::menu::CheckMenuItem;
+use tauri::tray::{TrayIconBuilder, MouseButton, MouseButtonState, TrayIconEvent};
 use sqlx::{sqlite::SqlitePoolOptions, Pool, Sqlite, Row};
 use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::Instant;
 use arboard::Clipboard;
 use chrono::prelude::*;
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 use deunicode::deunicode_char;
 
+mod annotate;
+mod ansi;
+mod app_icons;
+mod archive;
+mod backup;
+mod cache;
+mod capture;
+mod capture_log;
+mod classify;
+mod cli;
+mod code_block;
+mod collections;
+mod config;
+mod context;
+mod crypto;
+mod describe;
+mod diagnostics;
+mod digest;
+mod double_copy;
+mod encryption;
+mod export;
+mod facets;
+mod files;
+mod focus;
+mod foreground;
+mod gallery;
+mod hotkey;
+mod images;
+mod importers;
+mod journal;
+mod jsonl;
+mod keychain;
+mod launchers;
+mod line_endings;
+mod link_preview;
+mod locking;
+mod maintenance;
+mod notify;
+mod ocr;
+mod paste;
+mod paste_queue;
+mod pdf;
+mod pinned;
+mod power;
+mod projects;
+mod purge;
+mod rate_limit;
+mod redact;
+mod relations;
+mod restore;
+mod retention;
+mod rich;
+mod scheduler;
+mod search;
+mod sessions;
+mod settings;
+mod share;
+mod share_link;
+mod snapshots;
+mod snippets;
+mod sounds;
+mod source;
+mod split;
+mod store;
+mod summary;
+mod sync;
+mod tables;
+mod tags;
+mod templates;
+mod transform;
+mod trash;
+mod tray;
+mod urls;
+mod usage;
+mod vault;
+mod watcher;
+mod window;
+
 #[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
 pub struct Clip {
     id: String,
     content: String,
     created_at: String, // ISO 8601
     is_favorite: bool,
-    clip_type: String, // "text" or "image"
+    clip_type: String, // "text", "url", "image" or "files"
     image_path: Option<String>,
+    #[sqlx(default)]
+    is_sensitive: bool,
+    #[sqlx(default)]
+    source_url: Option<String>,
+    // Image clips only, computed when the image is stored
+    #[sqlx(default)]
+    image_width: Option<i64>,
+    #[sqlx(default)]
+    image_height: Option<i64>,
+    #[sqlx(default)]
+    dominant_color: Option<String>,
+    #[sqlx(default)]
+    thumbnail_path: Option<String>,
+    #[sqlx(default)]
+    window_title: Option<String>,
+    // "html" or "rtf" when a formatted flavor was captured; the content itself isn't listed
+    #[sqlx(default)]
+    rich_format: Option<String>,
+    // Key shared by copies of the same link, see urls.rs; '' for other text
+    #[sqlx(default)]
+    canonical_url: Option<String>,
+    // Copies of this link in history, when it stands for more than one
+    #[sqlx(skip)]
+    url_variants: Option<i64>,
+    // Text clips only: "email", "phone", "json", ..., see classify::detect_kind
+    #[sqlx(default)]
+    detected_kind: Option<String>,
+    // URL clips only: page title and cached favicon file, fetched after capture
+    #[sqlx(default)]
+    link_title: Option<String>,
+    #[sqlx(default)]
+    link_favicon: Option<String>,
+    // Set while the clip is in the trash
+    #[sqlx(default)]
+    deleted_at: Option<String>,
+    // App that was frontmost at capture time, and its cached icon file
+    #[sqlx(default)]
+    source_app: Option<String>,
+    #[sqlx(default)]
+    source_app_icon: Option<String>,
+    #[sqlx(default)]
+    session_id: Option<String>,
+    #[sqlx(default)]
+    project_id: Option<String>,
+    #[sqlx(default)]
+    collection_id: Option<String>,
+    #[sqlx(default)]
+    pinned: bool,
+    #[sqlx(default)]
+    sort_order: Option<i64>, // position among pinned clips
+    #[sqlx(default)]
+    locked: bool,
+    // bm25 score, only set on relevance-ordered searches (lower is better)
+    #[sqlx(default)]
+    rank: Option<f64>,
+    // Fields the search matched ("content", "ocr", "title"), only set on searches
+    #[sqlx(skip)]
+    matched_in: Option<Vec<String>>,
+    // Matching lines with some context, only set on searches
+    #[sqlx(skip)]
+    excerpt: Option<String>,
+}
+
+impl Clip {
+    /// A freshly stored text clip, as the cache should see it.
+    fn new_text(id: String, content: String, created_at: String) -> Clip {
+        Clip {
+            id,
+            content,
+            created_at,
+            is_favorite: false,
+            clip_type: "text".to_string(),
+            image_path: None,
+            is_sensitive: false,
+            source_url: None,
+            image_width: None,
+            image_height: None,
+            dominant_color: None,
+            thumbnail_path: None,
+            window_title: None,
+            rich_format: None,
+            canonical_url: None,
+            url_variants: None,
+            detected_kind: None,
+            link_title: None,
+            link_favicon: None,
+            deleted_at: None,
+            source_app: None,
+            source_app_icon: None,
+            session_id: None,
+            project_id: None,
+            collection_id: None,
+            pinned: false,
+            sort_order: None,
+            locked: false,
+            rank: None,
+            matched_in: None,
+            excerpt: None,
+        }
+    }
 }
 
 struct DbState {
     pool: Pool<Sqlite>,
+    recent: cache::RecentCache,
+}
+
+/// Holds the database initialization error while running in degraded mode
+/// (no DbState managed, capture disabled).
+#[derive(Default)]
+struct InitState {
+    error: std::sync::Mutex<Option<String>>,
 }
 
 const DB_FILENAME: &str = "clips.db";
+// Columns selected wherever a full `Clip` is returned
+const CLIP_COLUMNS: &str = "id, content, created_at, is_favorite, clip_type, image_path, is_sensitive, source_url, image_width, image_height, dominant_color, thumbnail_path, window_title, rich_format, canonical_url, detected_kind, link_title, link_favicon, source_app, source_app_icon, session_id, project_id, collection_id, pinned, sort_order, locked, deleted_at";
+// Secondary indexes, also checked (and recreated if missing) by benchmark_database
+const INDEXES: &[(&str, &str)] = &[
+    ("idx_clips_created_at_id", "CREATE INDEX IF NOT EXISTS idx_clips_created_at_id ON clips (created_at DESC, id DESC)"),
+    ("idx_clip_usage_clip_id", "CREATE INDEX IF NOT EXISTS idx_clip_usage_clip_id ON clip_usage (clip_id)"),
+    ("idx_clip_relations_to_id", "CREATE INDEX IF NOT EXISTS idx_clip_relations_to_id ON clip_relations (to_id)"),
+    ("idx_sessions_ended_at", "CREATE INDEX IF NOT EXISTS idx_sessions_ended_at ON sessions (ended_at DESC)"),
+];
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
 
 fn normalize_text(text: &str) -> String {
     let mut normalized = String::with_capacity(text.len());
@@ -45,20 +243,20 @@ fn normalize_text(text: &str) -> String {
 
 // ... (DbState, DB_FILENAME)
 
+fn app_data_dir(app_handle: &AppHandle) -> std::path::PathBuf {
+    app_handle.path().app_data_dir().unwrap_or(std::path::PathBuf::from("."))
+}
+
 async fn init_db(app_handle: &AppHandle) -> Result<Pool<Sqlite>, String> {
-    let app_dir = app_handle.path().app_data_dir().unwrap_or(std::path::PathBuf::from("."));
+    let app_dir = app_data_dir(app_handle);
     std::fs::create_dir_all(&app_dir).map_err(|e| e.to_string())?;
     let db_path = app_dir.join(DB_FILENAME);
-    
-    if !db_path.exists() {
-        std::fs::File::create(&db_path).map_err(|e| e.to_string())?;
-    }
-    
-    let db_url = format!("sqlite://{}", db_path.to_string_lossy());
+    let key = encryption::prepare(&db_path).await?;
 
     let pool = SqlitePoolOptions::new()
         .max_connections(5)
-        .connect(&db_url)
+        .after_connect(|conn, _| Box::pin(cache::watch_changes(conn)))
+        .connect_with(encryption::connect_options(&db_path, key.as_deref()))
         .await
         .map_err(|e| e.to_string())?;
 
@@ -85,12 +283,244 @@ async fn init_db(app_handle: &AppHandle) -> Result<Pool<Sqlite>, String> {
     .await
     .map_err(|e| e.to_string())?;
 
+    // Usage log: one row every time a stored clip is copied back via Klip
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS clip_usage (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            clip_id TEXT NOT NULL,
+            action TEXT NOT NULL,
+            used_at DATETIME NOT NULL
+        )"
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Persistent state for {counter:...} template placeholders
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS template_counters (
+            name TEXT PRIMARY KEY,
+            value INTEGER NOT NULL
+        )"
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Provenance links between clips ("<from_id> is <kind> <to_id>")
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS clip_relations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            from_id TEXT NOT NULL,
+            to_id TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            created_at DATETIME NOT NULL,
+            UNIQUE (from_id, to_id, kind)
+        )"
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Concurrent edits found during sync; both versions are kept until the user picks one
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS sync_conflicts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            clip_id TEXT NOT NULL,
+            local_content TEXT NOT NULL,
+            remote_content TEXT NOT NULL,
+            remote_device TEXT,
+            detected_at DATETIME NOT NULL
+        )"
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Bursts of clips from the same app; see sessions.rs
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS sessions (
+            id TEXT PRIMARY KEY,
+            app TEXT,
+            started_at DATETIME NOT NULL,
+            ended_at DATETIME NOT NULL
+        )"
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Named groups of clips; see projects.rs
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS projects (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            apps TEXT NOT NULL DEFAULT '[]',
+            created_at DATETIME NOT NULL
+        )"
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Named collections of saved clips; see collections.rs
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS collections (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            created_at DATETIME NOT NULL
+        )"
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // User-defined text templates; see snippets.rs
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS snippets (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            body TEXT NOT NULL,
+            created_at DATETIME NOT NULL,
+            updated_at DATETIME NOT NULL
+        )"
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    for (_, ddl) in INDEXES {
+        let _ = sqlx::query(ddl).execute(&pool).await;
+    }
+
     // Migration: Add search_content column if not exists
     let _ = sqlx::query("ALTER TABLE clips ADD COLUMN search_content TEXT").execute(&pool).await;
 
     // Migration: Add clip_type and image_path columns
     let _ = sqlx::query("ALTER TABLE clips ADD COLUMN clip_type TEXT DEFAULT 'text'").execute(&pool).await;
     let _ = sqlx::query("ALTER TABLE clips ADD COLUMN image_path TEXT").execute(&pool).await;
+
+    // Migration: flag for clips that have been redacted (original kept, marked sensitive)
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN is_sensitive BOOLEAN DEFAULT 0").execute(&pool).await;
+
+    // Migration: page URL the content was copied from, when the browser exposes it
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN source_url TEXT").execute(&pool).await;
+
+    // Migration: image dimensions and placeholder color, so the list can reserve layout space
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN image_width INTEGER").execute(&pool).await;
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN image_height INTEGER").execute(&pool).await;
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN dominant_color TEXT").execute(&pool).await;
+
+    // Migration: focused window title at capture time (opt-in via capture_window_title)
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN window_title TEXT").execute(&pool).await;
+
+    // Migration: opt-in capture context as JSON (capture_context setting)
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN context TEXT").execute(&pool).await;
+
+    // Migration: app the clip was copied from; clips with capture context get it from there
+    if sqlx::query("ALTER TABLE clips ADD COLUMN source_app TEXT").execute(&pool).await.is_ok() {
+        let _ = sqlx::query("UPDATE clips SET source_app = json_extract(context, '$.app') WHERE context IS NOT NULL").execute(&pool).await;
+    }
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN source_app_icon TEXT").execute(&pool).await;
+    let _ = sqlx::query("CREATE INDEX IF NOT EXISTS idx_clips_source_app ON clips (source_app)").execute(&pool).await;
+
+    // Migration: HTML/RTF flavor of text clips, see rich.rs; dropped when the text is edited
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN rich_format TEXT").execute(&pool).await;
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN rich_content TEXT").execute(&pool).await;
+    sqlx::query(
+        "CREATE TRIGGER IF NOT EXISTS clips_rich_stale AFTER UPDATE OF content ON clips
+         WHEN new.rich_content IS NOT NULL AND new.content IS NOT old.content
+         BEGIN UPDATE clips SET rich_format = NULL, rich_content = NULL WHERE id = new.id; END"
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Migration: raw text of terminal copies stored without escape codes, see ansi.rs
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN ansi_original TEXT").execute(&pool).await;
+    sqlx::query(
+        "CREATE TRIGGER IF NOT EXISTS clips_ansi_stale AFTER UPDATE OF content ON clips
+         WHEN new.ansi_original IS NOT NULL AND new.content IS NOT old.content
+         BEGIN UPDATE clips SET ansi_original = NULL WHERE id = new.id; END"
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Migration: link variants folded into one entry, see urls.rs
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN canonical_url TEXT").execute(&pool).await;
+    let _ = sqlx::query("CREATE INDEX IF NOT EXISTS idx_clips_canonical_url ON clips (canonical_url)").execute(&pool).await;
+
+    // Migration: what a text clip holds (email, JSON, code, ...), see classify::detect_kind
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN detected_kind TEXT").execute(&pool).await;
+    let _ = sqlx::query("CREATE INDEX IF NOT EXISTS idx_clips_detected_kind ON clips (detected_kind)").execute(&pool).await;
+
+    // Migration: page title and favicon of URL clips, see link_preview.rs
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN link_title TEXT").execute(&pool).await;
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN link_favicon TEXT").execute(&pool).await;
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN link_checked BOOLEAN NOT NULL DEFAULT 0").execute(&pool).await;
+    // A preview belongs to the link it was fetched for
+    sqlx::query(
+        "CREATE TRIGGER IF NOT EXISTS clips_link_stale AFTER UPDATE OF content ON clips
+         WHEN new.content IS NOT old.content AND (old.link_checked = 1 OR old.link_title IS NOT NULL)
+         BEGIN UPDATE clips SET link_title = NULL, link_favicon = NULL, link_checked = 0 WHERE id = new.id; END"
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Migration: small PNG for list views, see images::save_thumbnail
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN thumbnail_path TEXT").execute(&pool).await;
+
+    // Migration: image clips OCR has been run on (found text or not)
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN ocr_checked BOOLEAN NOT NULL DEFAULT 0").execute(&pool).await;
+
+    // Migration: SHA-256 of image pixels, for dedup by content instead of size
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN content_hash TEXT").execute(&pool).await;
+    let _ = sqlx::query("CREATE INDEX IF NOT EXISTS idx_clips_content_hash ON clips (content_hash)").execute(&pool).await;
+    // Text clips are hashed too; editing one clears its hash until the next backfill
+    sqlx::query(
+        "CREATE TRIGGER IF NOT EXISTS clips_text_hash_stale AFTER UPDATE OF content ON clips
+         WHEN new.clip_type IN ('text', 'url') AND new.content IS NOT old.content
+         BEGIN UPDATE clips SET content_hash = NULL WHERE id = new.id; END"
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Migration: session the clip was captured in
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN session_id TEXT").execute(&pool).await;
+    let _ = sqlx::query("CREATE INDEX IF NOT EXISTS idx_clips_session_id ON clips (session_id)").execute(&pool).await;
+
+    // Migration: project the clip belongs to
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN project_id TEXT").execute(&pool).await;
+    let _ = sqlx::query("CREATE INDEX IF NOT EXISTS idx_clips_project_id ON clips (project_id)").execute(&pool).await;
+
+    // Migration: collection the clip is filed in
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN collection_id TEXT").execute(&pool).await;
+    let _ = sqlx::query("CREATE INDEX IF NOT EXISTS idx_clips_collection_id ON clips (collection_id)").execute(&pool).await;
+
+    // Migration: pinned clips and their manual order
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN pinned BOOLEAN NOT NULL DEFAULT 0").execute(&pool).await;
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN sort_order INTEGER").execute(&pool).await;
+
+    // Migration: read-only clips
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN locked BOOLEAN NOT NULL DEFAULT 0").execute(&pool).await;
+
+    // Migration: clips in the trash, see trash.rs
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN deleted_at DATETIME").execute(&pool).await;
+    let _ = sqlx::query("CREATE INDEX IF NOT EXISTS idx_clips_deleted_at ON clips (deleted_at)").execute(&pool).await;
+
+    // Migration: when a clip was last marked favorite, for the daily journal (journal.rs)
+    let _ = sqlx::query("ALTER TABLE clips ADD COLUMN favorited_at DATETIME").execute(&pool).await;
+    sqlx::query(
+        "CREATE TRIGGER IF NOT EXISTS clips_favorited AFTER UPDATE OF is_favorite ON clips
+         WHEN new.is_favorite = 1 AND old.is_favorite = 0
+         BEGIN UPDATE clips SET favorited_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now') WHERE id = new.id; END"
+    )
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
     
     // Backfill null search_content
     let rows_to_update: Vec<(String, String)> = sqlx::query_as("SELECT id, content FROM clips WHERE search_content IS NULL")
@@ -107,43 +537,39 @@ async fn init_db(app_handle: &AppHandle) -> Result<Pool<Sqlite>, String> {
             .await;
     }
 
+    search::init_fts(&pool).await?;
+    tags::init_tags(&pool).await?;
+    locking::init_locks(&pool).await?;
+    sync::init_sync(&pool).await?;
+    sessions::backfill_sessions(&pool).await?;
+    capture::backfill_text_hashes(&pool).await?;
+    urls::backfill_canonical_urls(&pool).await?;
+    classify::backfill_detected_kinds(&pool).await?;
+
     // Ensure images directory exists
     let images_dir = app_dir.join("images");
     if !images_dir.exists() {
         std::fs::create_dir_all(&images_dir).map_err(|e| e.to_string())?;
     }
 
-    // Retention policy (Dynamic)
-    // Get setting, default to 90
-    let retention_days: i64 = sqlx::query_as::<_, (String,)>("SELECT value FROM settings WHERE key = 'retention_days'")
-        .fetch_optional(&pool)
+    Ok(pool)
+}
+
+async fn setting_value(pool: &Pool<Sqlite>, key: &str) -> Option<String> {
+    sqlx::query_as::<_, (String,)>("SELECT value FROM settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
         .await
         .unwrap_or(None)
-        .map(|(v,)| v.parse().unwrap_or(90))
-        .unwrap_or(90);
+        .map(|(v,)| v)
+}
 
-    let retention_date = Utc::now() - chrono::Duration::days(retention_days);
-    
-    // Handle image deletion for expired clips
-    let expired_images: Vec<(Option<String>,)> = sqlx::query_as("SELECT image_path FROM clips WHERE is_favorite = 0 AND created_at < ? AND clip_type = 'image'")
-        .bind(retention_date.to_rfc3339())
-        .fetch_all(&pool)
-        .await
-        .unwrap_or_default();
-    
-    for (path,) in expired_images {
-        if let Some(p) = path {
-            let _ = std::fs::remove_file(p);
-        }
+async fn setting_enabled(pool: &Pool<Sqlite>, key: &str, default: bool) -> bool {
+    match setting_value(pool, key).await.as_deref() {
+        Some("true") => true,
+        Some("false") => false,
+        _ => default,
     }
-
-    sqlx::query("DELETE FROM clips WHERE is_favorite = 0 AND created_at < ?")
-        .bind(retention_date.to_rfc3339())
-        .execute(&pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(pool)
 }
 
 #[tauri::command]
@@ -160,57 +586,201 @@ async fn get_setting(state: tauri::State<'_, DbState>, key: String) -> Result<Op
 #[tauri::command]
 async fn set_setting(state: tauri::State<'_, DbState>, key: String, value: String) -> Result<(), String> {
     sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
-        .bind(key)
-        .bind(value)
+        .bind(&key)
+        .bind(&value)
         .execute(&state.pool)
         .await
         .map_err(|e| e.to_string())?;
+
+    if key == "recent_cache_size" {
+        state.recent.set_capacity(value.parse().unwrap_or(cache::DEFAULT_CAPACITY));
+    }
     Ok(())
 }
 
 // ... (rest of commands: get_clips, etc.)
 // Don't forget to add get_setting/set_setting to invoke_handler!
 
+/// WHERE clause (and its bind values) for the list filters shared by get_clips and facets.
+#[allow(clippy::too_many_arguments)]
+fn clip_filter(search_text: Option<String>, date_filter: Option<String>, window_filter: Option<String>, project_id: Option<String>, tags: Option<Vec<String>>, collection_id: Option<String>, source_app: Option<String>, detected_kind: Option<String>) -> (String, Vec<String>) {
+    let mut query = trash::NOT_DELETED.to_string();
+    let mut args = Vec::new();
+
+    if let Some(search) = search_text.filter(|s| !s.is_empty()) {
+        match search::fts_query(&search) {
+            Some(fts) => {
+                query.push_str(" AND clips.rowid IN (SELECT rowid FROM clips_fts WHERE clips_fts MATCH ?)");
+                args.push(fts);
+            }
+            // Punctuation-only searches have no FTS tokens; match them literally
+            None => {
+                query.push_str(" AND (search_content LIKE ? OR window_title LIKE ?)");
+                args.push(format!("%{}%", normalize_text(&search)));
+                args.push(format!("%{}%", search));
+            }
+        }
+    }
+
+    if let Some(date) = date_filter.filter(|d| !d.is_empty()) {
+        query.push_str(" AND strftime('%Y-%m-%d', created_at, 'localtime') = ?");
+        args.push(date);
+    }
+
+    if let Some(window) = window_filter.filter(|w| !w.is_empty()) {
+        query.push_str(" AND window_title LIKE ?");
+        args.push(format!("%{}%", window));
+    }
+
+    if let Some(project) = project_id.filter(|p| !p.is_empty()) {
+        query.push_str(" AND project_id = ?");
+        args.push(project);
+    }
+
+    if let Some(collection) = collection_id.filter(|c| !c.is_empty()) {
+        query.push_str(" AND collection_id = ?");
+        args.push(collection);
+    }
+
+    if let Some(app) = source_app.filter(|a| !a.is_empty()) {
+        query.push_str(" AND source_app = ?");
+        args.push(app);
+    }
+
+    if let Some(kind) = detected_kind.filter(|k| !k.is_empty()) {
+        query.push_str(" AND detected_kind = ?");
+        args.push(kind);
+    }
+
+    // Every listed tag must be present
+    for tag in tags.unwrap_or_default().into_iter().map(|t| t.trim().to_string()).filter(|t| !t.is_empty()) {
+        query.push_str(&format!(" AND {}", tags::HAS_TAG));
+        args.push(tag);
+    }
+
+    query.push_str(&format!(" AND {}", urls::FOLD_VARIANTS));
+
+    (query, args)
+}
+
 #[tauri::command]
-async fn get_clips(state: tauri::State<'_, DbState>, search_text: Option<String>, date_filter: Option<String>) -> Result<Vec<Clip>, String> {
+#[allow(clippy::too_many_arguments)] // flat arguments, as the frontend passes them
+async fn get_clips(state: tauri::State<'_, DbState>, search_text: Option<String>, date_filter: Option<String>, cursor_created_at: Option<String>, cursor_id: Option<String>, page_size: Option<i64>, window_filter: Option<String>, order_by: Option<String>, cursor_rank: Option<f64>, project_id: Option<String>, tags: Option<Vec<String>>, collection_id: Option<String>, source_app: Option<String>, detected_kind: Option<String>) -> Result<Vec<Clip>, String> {
 // ...
-    let mut query = "SELECT id, content, created_at, is_favorite, clip_type, image_path FROM clips WHERE 1=1".to_string();
-    let mut args = Vec::new();
+    let limit = match page_size {
+        Some(size) => size.clamp(1, MAX_PAGE_SIZE),
+        None => settings::page_size(&state.pool).await,
+    };
+    // A loaded snapshot stands in for the live history, see snapshots.rs
+    let pool = snapshots::read_pool(&state);
+    let fts = search_text.as_deref().and_then(search::fts_query);
+    let search = search_text.clone().filter(|s| !s.is_empty());
 
-    if let Some(search) = search_text {
-        if !search.is_empty() {
-             let normalized_search = normalize_text(&search);
-             query.push_str(" AND search_content LIKE ?");
-             args.push(format!("%{}%", normalized_search));
+    // order_by "relevance" ranks full-text matches by bm25; pages continue from (rank, id)
+    if let (Some("relevance"), Some(fts)) = (order_by.as_deref(), fts) {
+        let (filter, filter_args) = clip_filter(search_text, date_filter, window_filter, project_id, tags, collection_id, source_app, detected_kind);
+        let mut query = format!(
+            "SELECT * FROM (SELECT {}, bm25(clips_fts) AS rank FROM clips JOIN clips_fts ON clips_fts.rowid = clips.rowid WHERE clips_fts MATCH ? AND {}) WHERE 1=1",
+            CLIP_COLUMNS, filter
+        );
+        if cursor_rank.is_some() {
+            query.push_str(" AND (rank > ? OR (rank = ? AND id > ?))");
         }
+        query.push_str(&format!(" ORDER BY rank, id LIMIT {}", limit));
+
+        let mut query_builder = sqlx::query_as::<_, Clip>(&query).bind(fts);
+        for arg in filter_args {
+            query_builder = query_builder.bind(arg);
+        }
+        if let Some(rank) = cursor_rank {
+            query_builder = query_builder.bind(rank).bind(rank).bind(cursor_id.unwrap_or_default());
+        }
+
+        let mut rows = diagnostics::timed("get_clips_ranked", query_builder.fetch_all(&pool))
+            .await
+            .map_err(|e| e.to_string())?;
+        urls::count_variants(&pool, &mut rows).await?;
+        return Ok(with_matches(rows, search.as_deref()));
     }
-    
-    if let Some(date) = date_filter {
-        if !date.is_empty() {
-            query.push_str(" AND strftime('%Y-%m-%d', created_at, 'localtime') = ?");
-            args.push(date); 
+
+    let (filter, args) = clip_filter(search_text, date_filter, window_filter, project_id, tags, collection_id, source_app, detected_kind);
+
+    // Pinned clips head the first page in their own order and are left out of the pages by date
+    let first_page = cursor_created_at.as_deref().is_none_or(str::is_empty);
+    let mut rows = Vec::new();
+    if first_page {
+        let pinned_sql = format!("SELECT {} FROM clips WHERE {} AND pinned = 1 ORDER BY sort_order, id", CLIP_COLUMNS, filter);
+        let mut pinned_query = sqlx::query_as::<_, Clip>(&pinned_sql);
+        for arg in &args {
+            pinned_query = pinned_query.bind(arg);
         }
+        rows = pinned_query.fetch_all(&pool).await.map_err(|e| e.to_string())?;
     }
 
-    query.push_str(" ORDER BY created_at DESC LIMIT 50");
+    let cursor = cursor_created_at.filter(|c| !c.is_empty()).map(|c| (c, cursor_id.unwrap_or_default()));
+    rows.extend(diagnostics::timed("get_clips", date_page(&pool, &filter, args, cursor, limit)).await?);
+    urls::count_variants(&pool, &mut rows).await?;
+
+    Ok(with_matches(rows, search.as_deref()))
+}
+
+/// Unpinned clips by date, newest first. Keyset pagination: the client passes
+/// the (created_at, id) of the last clip it has. Unlike OFFSET, new clips
+/// arriving at the top don't shift the next page.
+async fn date_page(pool: &Pool<Sqlite>, filter: &str, mut args: Vec<String>, cursor: Option<(String, String)>, limit: i64) -> Result<Vec<Clip>, String> {
+    let mut query = format!("SELECT {} FROM clips WHERE {} AND pinned = 0", CLIP_COLUMNS, filter);
+    if let Some((created_at, id)) = cursor {
+        query.push_str(" AND (created_at < ? OR (created_at = ? AND id < ?))");
+        args.push(created_at.clone());
+        args.push(created_at);
+        args.push(id);
+    }
+    query.push_str(&format!(" ORDER BY created_at DESC, id DESC LIMIT {}", limit));
 
     let mut query_builder = sqlx::query_as::<_, Clip>(&query);
     for arg in args {
         query_builder = query_builder.bind(arg);
     }
+    query_builder.fetch_all(pool).await.map_err(|e| e.to_string())
+}
 
-    let rows = query_builder
-        .fetch_all(&state.pool)
+/// Tags search results with the fields that matched, so the UI can say why an
+/// image showed up for a text query, and an excerpt around the first match.
+fn with_matches(mut clips: Vec<Clip>, search: Option<&str>) -> Vec<Clip> {
+    if let Some(search) = search {
+        for clip in &mut clips {
+            clip.matched_in = Some(search::matched_in(clip, search));
+            clip.excerpt = search::excerpt(&clip.content, search);
+        }
+    }
+    clips
+}
+
+/// Number of clips matching the same filters as `get_clips`, for the
+/// infinite-scroll UI to show how far back history goes.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // same flat arguments as get_clips
+async fn get_clip_count(state: tauri::State<'_, DbState>, search_text: Option<String>, date_filter: Option<String>, window_filter: Option<String>, project_id: Option<String>, tags: Option<Vec<String>>, collection_id: Option<String>, source_app: Option<String>, detected_kind: Option<String>) -> Result<i64, String> {
+    let (filter, args) = clip_filter(search_text, date_filter, window_filter, project_id, tags, collection_id, source_app, detected_kind);
+    let pool = snapshots::read_pool(&state);
+    let query = format!("SELECT COUNT(*) FROM clips WHERE {}", filter);
+
+    let mut query_builder = sqlx::query_as::<_, (i64,)>(&query);
+    for arg in args {
+        query_builder = query_builder.bind(arg);
+    }
+
+    let (count,) = diagnostics::timed("get_clip_count", query_builder.fetch_one(&pool))
         .await
         .map_err(|e| e.to_string())?;
-    
-    Ok(rows)
+    Ok(count)
 }
 
 #[tauri::command]
 async fn get_dates_with_clips(state: tauri::State<'_, DbState>) -> Result<Vec<String>, String> {
-    let rows: Vec<(String,)> = sqlx::query_as("SELECT DISTINCT strftime('%Y-%m-%d', created_at, 'localtime') FROM clips ORDER BY created_at DESC")
-        .fetch_all(&state.pool)
+    let pool = snapshots::read_pool(&state);
+    let query = sqlx::query_as::<_, (String,)>("SELECT DISTINCT strftime('%Y-%m-%d', created_at, 'localtime') FROM clips WHERE deleted_at IS NULL ORDER BY created_at DESC");
+    let rows = diagnostics::timed("get_dates_with_clips", query.fetch_all(&pool))
         .await
         .map_err(|e| e.to_string())?;
 
@@ -219,61 +789,118 @@ async fn get_dates_with_clips(state: tauri::State<'_, DbState>) -> Result<Vec<St
 }
 
 #[tauri::command]
-async fn add_clip(state: tauri::State<'_, DbState>, content: String) -> Result<String, String> {
-    // Check if content already exists TODAY
-    let exists: Option<(i32,)> = sqlx::query_as(
-        "SELECT 1 FROM clips WHERE content = ? AND strftime('%Y-%m-%d', created_at, 'localtime') = strftime('%Y-%m-%d', 'now', 'localtime') LIMIT 1"
-    )
-    .bind(&content)
-    .fetch_optional(&state.pool)
-    .await
-    .map_err(|e| e.to_string())?;
+async fn add_clip(app_handle: AppHandle, state: tauri::State<'_, DbState>, content: String) -> Result<String, String> {
+    // Stored like a capture of the same text, so the two dedup against each other
+    let (content, ansi_original) = store::stored_text(&state.pool, &content).await;
 
-    if exists.is_some() {
+    // Check if content already exists within the dedup window
+    if capture::is_duplicate(&state, &content).await? {
         return Ok("Duplicate".to_string());
     }
 
     let id = Uuid::new_v4().to_string();
     let created_at = Utc::now().to_rfc3339();
-    let search_content = normalize_text(&content);
-    
-    sqlx::query("INSERT INTO clips (id, content, created_at, is_favorite, search_content, clip_type, image_path) VALUES (?, ?, ?, ?, ?, 'text', NULL)")
-        .bind(&id)
-        .bind(&content)
-        .bind(&created_at)
-        .bind(false)
-        .bind(search_content)
-        .execute(&state.pool)
-        .await
-        .map_err(|e| e.to_string())?;
+    let clip_type = store::insert_text_clip(&state.pool, &id, &content, &created_at, false).await?;
+    if let Some(original) = ansi_original {
+        sqlx::query("UPDATE clips SET ansi_original = ? WHERE id = ?")
+            .bind(original)
+            .bind(&id)
+            .execute(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
 
+    if clip_type == "url" {
+        link_preview::queue(&app_handle, id.clone(), content.clone());
+    }
+    let mut clip = Clip::new_text(id.clone(), content, created_at);
+    clip.clip_type = clip_type.to_string();
+    clip.detected_kind = Some(classify::detect_kind(&clip.content).to_string());
+    state.recent.insert(clip);
     Ok(id)
 }
 
 #[tauri::command]
-fn copy_to_clipboard(content: String) -> Result<(), String> {
+async fn copy_to_clipboard(state: tauri::State<'_, DbState>, content: String, id: Option<String>) -> Result<(), String> {
+    if let Some(clip_id) = &id {
+        let clip_type: Option<(String,)> = sqlx::query_as("SELECT clip_type FROM clips WHERE id = ?")
+            .bind(clip_id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        if matches!(clip_type, Some((t,)) if t == "files") {
+            let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
+            restore::remember_current(&mut clipboard);
+            files::write(&mut clipboard, &content)?;
+            sounds::play(&state.pool, sounds::Sound::Copy).await;
+            return usage::record_usage(&state.pool, clip_id, "copy_files").await;
+        }
+    }
+
+    let rich = match &id {
+        Some(clip_id) => rich::rich_content(&state.pool, clip_id, &content).await?,
+        None => None,
+    };
     let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
-    clipboard.set_text(content).map_err(|e| e.to_string())?;
+    restore::remember_current(&mut clipboard);
+    match rich {
+        Some(rich) => rich::write(&mut clipboard, &content, &rich)?,
+        None => clipboard.set_text(content).map_err(|e| e.to_string())?,
+    }
+
+    if let Some(clip_id) = id {
+        sounds::play(&state.pool, sounds::Sound::Copy).await;
+        usage::record_usage(&state.pool, &clip_id, "copy_text").await?;
+    }
     Ok(())
 }
 
 #[tauri::command]
-async fn update_clip_content(state: tauri::State<'_, DbState>, id: String, content: String) -> Result<(), String> {
+async fn update_clip_content(app_handle: AppHandle, state: tauri::State<'_, DbState>, id: String, content: String) -> Result<(), String> {
+    set_clip_content(&app_handle, &state, &id, content).await
+}
+
+/// Replaces a clip's text, keeping its derived columns (type, link key, kind) in step.
+async fn set_clip_content(app_handle: &AppHandle, state: &DbState, id: &str, content: String) -> Result<(), String> {
+    locking::ensure_unlocked(&state.pool, id).await?;
     let search_content = normalize_text(&content);
-    sqlx::query("UPDATE clips SET content = ?, search_content = ? WHERE id = ?")
-        .bind(content)
-        .bind(search_content)
-        .bind(id)
-        .execute(&state.pool)
-        .await
-        .map_err(|e| e.to_string())?;
+    // Text edited into a link (or out of one) changes type; other types keep theirs
+    let updated: Option<(String,)> = sqlx::query_as(
+        "UPDATE clips SET content = ?, search_content = ?,
+         clip_type = CASE WHEN clip_type IN ('text', 'url') THEN ? ELSE clip_type END,
+         canonical_url = CASE WHEN clip_type IN ('text', 'url') THEN ? ELSE canonical_url END,
+         detected_kind = CASE WHEN clip_type IN ('text', 'url') THEN ? ELSE detected_kind END
+         WHERE id = ? RETURNING clip_type"
+    )
+    .bind(&content)
+    .bind(search_content)
+    .bind(classify::text_clip_type(&content))
+    .bind(urls::canonical_url(&content).unwrap_or_default())
+    .bind(classify::detect_kind(&content))
+    .bind(id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let clip_type = updated.map(|(t,)| t);
+    if clip_type.as_deref() == Some("url") {
+        link_preview::queue(app_handle, id.to_string(), content.clone());
+    }
+    state.recent.update(id, |clip| {
+        if let Some(clip_type) = clip_type {
+            if classify::is_text_type(&clip_type) {
+                clip.detected_kind = Some(classify::detect_kind(&content).to_string());
+            }
+            clip.clip_type = clip_type;
+        }
+        clip.content = content;
+    });
     Ok(())
 }
 
 #[tauri::command]
-async fn copy_image_to_clipboard(path: String) -> Result<(), String> {
-    let img = image::open(&path).map_err(|e| e.to_string())?;
-    let rgba = img.to_rgba8();
+async fn copy_image_to_clipboard(state: tauri::State<'_, DbState>, path: String, id: Option<String>) -> Result<(), String> {
+    let rgba = images::open_rgba(&path)?;
     let (width, height) = rgba.dimensions();
     let bytes = rgba.into_raw();
 
@@ -284,34 +911,126 @@ async fn copy_image_to_clipboard(path: String) -> Result<(), String> {
     };
 
     let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
+    restore::remember_current(&mut clipboard);
     clipboard.set_image(image_data).map_err(|e| e.to_string())?;
+
+    if let Some(clip_id) = id {
+        sounds::play(&state.pool, sounds::Sound::Copy).await;
+        usage::record_usage(&state.pool, &clip_id, "copy_image").await?;
+    }
     Ok(())
 }
 
+/// Moves a clip to the trash; see trash.rs.
 #[tauri::command]
 async fn delete_clip(state: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
+    trash::trash_clip(&state, &id).await
+}
+
+async fn remove_clip(state: &DbState, id: &str) -> Result<(), String> {
+    let pool = &state.pool;
+    locking::ensure_unlocked(pool, id).await?;
+
     // Get image path first
     let row: Option<(Option<String>,)> = sqlx::query_as("SELECT image_path FROM clips WHERE id = ?")
-        .bind(&id)
-        .fetch_optional(&state.pool)
+        .bind(id)
+        .fetch_optional(pool)
         .await
         .map_err(|e| e.to_string())?;
 
     if let Some((image_path,)) = row {
         if let Some(path) = image_path {
-             let _ = std::fs::remove_file(path);
+             let _ = images::remove_image(path);
         }
     }
 
+    sqlx::query("DELETE FROM clip_usage WHERE clip_id = ?")
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    relations::remove_relations(pool, id).await?;
+
     sqlx::query("DELETE FROM clips WHERE id = ?")
         .bind(id)
-        .execute(&state.pool)
+        .execute(pool)
         .await
         .map_err(|e| e.to_string())?;
+
+    state.recent.remove(id);
         
     Ok(())
 }
 
+/// Opens the database, registers DbState and starts capture. On failure the
+/// error is kept in InitState so the frontend can show it and offer retry/reset.
+async fn start_database(app_handle: &AppHandle) -> Result<(), String> {
+    let init_state = app_handle.state::<InitState>();
+
+    match init_db(app_handle).await {
+        Ok(pool) => {
+            if let Some(ms) = setting_value(&pool, "slow_query_ms").await.and_then(|v| v.parse().ok()) {
+                diagnostics::set_slow_query_threshold_ms(ms);
+            }
+            capture_log::set_enabled(setting_enabled(&pool, "capture_debug", false).await);
+            capture::restore_paused(setting_enabled(&pool, "capture_paused", false).await);
+            settings::restore(&pool).await;
+            let cache_size = setting_value(&pool, "recent_cache_size")
+                .await
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(cache::DEFAULT_CAPACITY);
+            app_handle.manage(DbState { pool, recent: cache::RecentCache::new(cache_size) });
+            *init_state.error.lock().unwrap() = None;
+            start_clipboard_monitor(app_handle.clone());
+            Ok(())
+        }
+        Err(e) => {
+            *init_state.error.lock().unwrap() = Some(e.clone());
+            Err(e)
+        }
+    }
+}
+
+/// Returns the initialization error when running in degraded mode, None when healthy.
+#[tauri::command]
+fn get_init_status(init_state: tauri::State<'_, InitState>) -> Option<String> {
+    init_state.error.lock().unwrap().clone()
+}
+
+#[tauri::command]
+async fn retry_init(app_handle: AppHandle) -> Result<(), String> {
+    if app_handle.try_state::<DbState>().is_some() {
+        return Ok(());
+    }
+    start_database(&app_handle).await?;
+    let _ = app_handle.emit("clipboard-changed", ());
+    Ok(())
+}
+
+/// Moves the (presumably corrupt) database aside and starts with a fresh one.
+/// The old file and its WAL are kept next to it so nothing is lost for good.
+#[tauri::command]
+async fn reset_database(app_handle: AppHandle) -> Result<(), String> {
+    if app_handle.try_state::<DbState>().is_some() {
+        return Err("Database is healthy; refusing to reset".to_string());
+    }
+
+    let db_path = app_data_dir(&app_handle).join(DB_FILENAME);
+    if db_path.exists() {
+        let backup = format!("{}.broken-{}", DB_FILENAME, Utc::now().format("%Y%m%d%H%M%S"));
+        std::fs::rename(&db_path, db_path.with_file_name(&backup)).map_err(|e| e.to_string())?;
+        // A stale WAL would otherwise be replayed into the fresh database
+        for suffix in ["-wal", "-shm"] {
+            let side_file = db_path.with_file_name(format!("{}{}", DB_FILENAME, suffix));
+            if side_file.exists() {
+                std::fs::rename(&side_file, db_path.with_file_name(format!("{}{}", backup, suffix))).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    retry_init(app_handle).await
+}
+
 fn start_clipboard_monitor(app_handle: AppHandle) {
     let handle = app_handle.clone();
     
@@ -324,154 +1043,178 @@ fn start_clipboard_monitor(app_handle: AppHandle) {
             }
         };
 
+        let mut watcher = watcher::create();
         let mut last_content = String::new();
-        // Track last image hash/size to avoid dups. Simple length check for now, can improve.
-        let mut last_image_len: usize = 0; 
+        // When the current text was last copied, for double-copy detection
+        let mut last_copied_at: Option<Instant> = None;
+        // Sampled fingerprint of the last image seen; the full hash is only computed on change
+        let mut last_image: Option<u64> = None;
+
+        if let Ok(image) = clipboard.get_image() {
+            last_image = Some(images::fingerprint(image.width as u32, image.height as u32, &image.bytes));
+        }
+
+        // Files copied in a file manager, stored as one "files" clip
+        let mut last_files: Option<Vec<std::path::PathBuf>> = files::read(&mut clipboard);
 
         if let Ok(text) = clipboard.get_text() {
-             last_content = text;
+             last_content = text.clone();
+
+             // Catch up on whatever was copied before Klip started
+             if !text.is_empty() && last_files.is_none() {
+                 let handle_clone = handle.clone();
+                 let concealed = source::is_concealed();
+                 tauri::async_runtime::block_on(async move {
+                     if let Err(e) = store::catch_up(&handle_clone, &text, concealed).await {
+                         eprintln!("Startup catch-up failed: {}", e);
+                     }
+                 });
+             }
         }
 
         loop {
+            // Check for Files; the names and icon offered with them aren't stored separately
+            if let Some(paths) = files::read(&mut clipboard) {
+                if last_files.as_ref() != Some(&paths) {
+                    last_files = Some(paths.clone());
+                    last_content = clipboard.get_text().unwrap_or_default();
+                    last_image = clipboard.get_image().ok().map(|image| images::fingerprint(image.width as u32, image.height as u32, &image.bytes));
+
+                    let handle_clone = handle.clone();
+                    let concealed = source::is_concealed();
+                    let seen_at = Instant::now();
+
+                    tauri::async_runtime::block_on(async move {
+                        if let Err(e) = store::store_monitored_files(&handle_clone, &paths, concealed, seen_at).await {
+                            eprintln!("Failed to store files clip: {}", e);
+                        }
+                    });
+                }
+                watcher.wait();
+                continue;
+            }
+            last_files = None;
+
             // Check for Text
             if let Ok(text) = clipboard.get_text() {
-                if text != last_content && !text.trim().is_empty() {
+                if text != last_content && !text.is_empty() {
                     last_content = text.clone();
+                    last_copied_at = Some(Instant::now());
                     
                     let handle_clone = handle.clone();
-                    let text_clone = text.clone();
-                    
+                    let source_url = source::source_url();
+                    let rich = rich::read();
+                    let concealed = source::is_concealed();
+                    let seen_at = Instant::now();
+
                     // Run async DB insert
                     tauri::async_runtime::block_on(async move {
-                         let state = handle_clone.state::<DbState>();
-                         
-                         // Check duplicates for today before inserting
-                          let exists: Option<(i32,)> = sqlx::query_as(
-                              "SELECT 1 FROM clips WHERE content = ? AND strftime('%Y-%m-%d', created_at, 'localtime') = strftime('%Y-%m-%d', 'now', 'localtime') LIMIT 1"
-                          )
-                          .bind(&text_clone)
-                          .fetch_optional(&state.pool)
-                          .await
-                          .unwrap_or(None);
-
-                          if exists.is_none() {
-                                let id = Uuid::new_v4().to_string();
-                                let created_at = Utc::now().to_rfc3339();
-                                let search_content = normalize_text(&text_clone);
-
-                                let _ = sqlx::query("INSERT INTO clips (id, content, created_at, is_favorite, search_content, clip_type, image_path) VALUES (?, ?, ?, ?, ?, 'text', NULL)")
-                                .bind(id)
-                                .bind(text_clone)
-                                .bind(created_at)
-                                .bind(false)
-                                .bind(search_content)
-                                .execute(&state.pool)
-                                .await;
-                                
-                                let _ = handle_clone.emit("clipboard-changed", ());
-                          }
+                        if let Err(e) = store::store_monitored_text(&handle_clone, &text, source_url, rich, concealed, seen_at).await {
+                            eprintln!("Failed to store clip: {}", e);
+                        }
                     });
+                } else if !text.is_empty() && watcher.reports_every_copy() {
+                    // Same text copied again; a second copy soon after the first is a gesture
+                    if last_copied_at.is_some_and(|at| at.elapsed() <= double_copy::WINDOW) {
+                        last_copied_at = None;
+                        let handle_clone = handle.clone();
+                        tauri::async_runtime::block_on(async move {
+                            if let Err(e) = double_copy::on_double_copy(&handle_clone, &text).await {
+                                eprintln!("Double-copy action failed: {}", e);
+                            }
+                        });
+                    } else {
+                        last_copied_at = Some(Instant::now());
+                    }
                 }
             }
 
             // Check for Image
-            // if let Ok(image) = clipboard.get_image() {
-            //     if image.bytes.len() != last_image_len && image.bytes.len() > 0 {
-            //         last_image_len = image.bytes.len(); // Update last seen
-            //         
-            //         // Logic to process image...
-            //         let width = image.width;
-            //         let height = image.height;
-            //         let bytes = image.bytes.into_owned(); // Clone bytes
-            //         
-            //         let handle_clone = handle.clone();
-            //         let app_dir = handle_clone.path().app_data_dir().unwrap_or(std::path::PathBuf::from("."));
-            //         
-            //         tauri::async_runtime::block_on(async move {
-            //              let state = handle_clone.state::<DbState>();
-            //              let id = Uuid::new_v4().to_string();
-            //              let created_at = Utc::now().to_rfc3339();
-            //              let file_name = format!("{}.png", id);
-            //              let file_path = app_dir.join("images").join(&file_name);
-            //              
-            //              // Save Image using `image` crate
-            //              if let Some(img_buffer) = image::ImageBuffer::<image::Rgba<u8>, _>::from_raw(width as u32, height as u32, bytes) {
-            //                  if let Ok(_) = img_buffer.save(&file_path) {
-            //                       let image_path_str = file_path.to_string_lossy().to_string();
-            //                       
-            //                       // Insert into DB (content is empty for now, search_content null)
-            //                       let _ = sqlx::query("INSERT INTO clips (id, content, created_at, is_favorite, search_content, clip_type, image_path) VALUES (?, '', ?, ?, NULL, 'image', ?)")
-            //                         .bind(id)
-            //                         .bind(created_at)
-            //                         .bind(false)
-            //                         .bind(image_path_str)
-            //                         .execute(&state.pool)
-            //                         .await;
-            //
-            //                       let _ = handle_clone.emit("clipboard-changed", ());
-            //                  }
-            //              }
-            //         });
-            //     }
-            // }
-
-            thread::sleep(Duration::from_millis(1000));
+            if let Ok(image) = clipboard.get_image() {
+                let (width, height) = (image.width as u32, image.height as u32);
+                let fingerprint = images::fingerprint(width, height, &image.bytes);
+                if !image.bytes.is_empty() && last_image != Some(fingerprint) {
+                    last_image = Some(fingerprint);
+
+                    let handle_clone = handle.clone();
+                    let bytes = image.bytes.into_owned();
+                    let concealed = source::is_concealed();
+                    let seen_at = Instant::now();
+
+                    tauri::async_runtime::block_on(async move {
+                        if let Err(e) = store::store_monitored_image(&handle_clone, width, height, bytes, concealed, seen_at).await {
+                            eprintln!("Failed to store image clip: {}", e);
+                        }
+                    });
+                }
+            }
+
+            watcher.wait();
         }
     });
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Headless subcommands (`klip export-jsonl ...`) exit before any window opens
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-            let _ = app.get_webview_window("main").expect("no main window").show();
-            let _ = app.get_webview_window("main").expect("no main window").set_focus();
+            tray::show_window(app);
         }))
+        .plugin(hotkey::plugin())
         .setup(|app| {
-            let handle = app.handle().clone();
-            tauri::async_runtime::block_on(async move {
-                let pool = init_db(&handle).await.expect("failed to init db");
-                handle.manage(DbState { pool });
-            });
-            
-            start_clipboard_monitor(app.handle().clone());
+            app.manage(InitState::default());
+            if let Err(e) = tauri::async_runtime::block_on(start_database(app.handle())) {
+                // Keep the UI up so the user can retry or reset instead of crashing before any window exists
+                eprintln!("Failed to init db, running in degraded mode: {}", e);
+                let _ = app.handle().emit("init-failed", e);
+            }
+            scheduler::start_scheduler(app.handle().clone());
 
-            let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>).unwrap();
-            let show_i = MenuItem::with_id(app, "show", "Open Klip", true, None::<&str>).unwrap();
-            let menu = Menu::with_items(app, &[&show_i, &quit_i]).unwrap();
+            let on_top_i = CheckMenuItem::with_id(app, "always_on_top", "Always on Top", true, false, None::<&str>).unwrap();
+            app.manage(window::TrayItems { always_on_top: on_top_i });
+            let menu = tray::build_menu(app.handle(), &[])?;
 
-            let _tray = TrayIconBuilder::new()
+            let _tray = TrayIconBuilder::with_id(tray::TRAY_ID)
                 .menu(&menu)
                 .on_menu_event(move |app, event| {
                     match event.id.as_ref() {
+                        id if tray::on_menu_clip(app, id) => {}
                         "quit" => app.exit(0),
-                        "show" => {
-                            if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            }
-                        }
+                        "show" => tray::show_window(app),
+                        "always_on_top" => window::on_tray_toggle(app),
                         _ => {}
                     }
                 })
                 .on_tray_icon_event(|tray, event| {
-                     if let TrayIconEvent::Click { button: MouseButton::Left, .. } = event {
-                        let app = tray.app_handle();
-                        if let Some(window) = app.get_webview_window("main") {
-                            if window.is_visible().unwrap_or(false) {
-                                let _ = window.hide();
-                            } else {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            }
-                        }
+                     if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                        tray::on_left_click(tray.app_handle());
                      }
                 })
                 .icon(app.default_window_icon().unwrap().clone())
                 .build(app)?;
 
+            let handle = app.handle().clone();
+            app.listen("clipboard-changed", move |_| {
+                let handle = handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = tray::rebuild_menu(&handle).await {
+                        eprintln!("Failed to rebuild tray menu: {}", e);
+                    }
+                });
+            });
+            let _ = tauri::async_runtime::block_on(tray::rebuild_menu(app.handle()));
+
+            tauri::async_runtime::block_on(window::restore_always_on_top(app.handle()));
+            tauri::async_runtime::block_on(hotkey::restore(app.handle()));
+
             Ok(())
         })
         .on_window_event(|window, event| {
@@ -480,7 +1223,233 @@ pub fn run() {
                 api.prevent_close();
             }
         })
-        .invoke_handler(tauri::generate_handler![get_clips, get_dates_with_clips, add_clip, copy_to_clipboard, update_clip_content, delete_clip, copy_image_to_clipboard, get_setting, set_setting])
+        .invoke_handler(tauri::generate_handler![
+            get_clips,
+            get_clip_count,
+            get_dates_with_clips,
+            add_clip,
+            copy_to_clipboard,
+            update_clip_content,
+            delete_clip,
+            copy_image_to_clipboard,
+            get_setting,
+            set_setting,
+            usage::get_clip_usage,
+            summary::get_today_summary,
+            annotate::annotate_image_clip,
+            redact::redact_clip,
+            notify::handle_notification_action,
+            digest::get_weekly_digest,
+            digest::export_weekly_digest,
+            templates::expand_template,
+            templates::get_template_counters,
+            templates::set_template_counter,
+            export::export_static_site,
+            vault::export_to_vault,
+            importers::import_history,
+            get_init_status,
+            retry_init,
+            reset_database,
+            diagnostics::get_db_diagnostics,
+            diagnostics::set_slow_query_threshold,
+            cache::get_recent_clips,
+            relations::link_clips,
+            relations::unlink_clips,
+            relations::get_related_clips,
+            crypto::decrypt_export,
+            purge::purge_where,
+            keychain::set_secret,
+            keychain::has_secret,
+            keychain::delete_secret,
+            sync::get_sync_scope,
+            sync::set_sync_scope,
+            sync::list_sync_conflicts,
+            sync::resolve_conflict,
+            capture_log::set_capture_debug,
+            capture_log::get_capture_events,
+            capture_log::export_capture_events,
+            diagnostics::benchmark_database,
+            gallery::get_image_clips,
+            maintenance::start_image_maintenance,
+            maintenance::cancel_image_maintenance,
+            maintenance::is_image_maintenance_running,
+            context::get_clip_context,
+            context::clear_clip_context,
+            facets::get_clip_facets,
+            retention::get_pending_retention,
+            retention::preview_retention,
+            retention::confirm_retention,
+            store::capture_now,
+            window::set_always_on_top,
+            capture::set_capture_paused,
+            capture::is_capture_paused,
+            tray::get_tray_action,
+            tray::set_tray_action,
+            usage::record_drag,
+            share::share_clip,
+            pdf::export_pdf,
+            sessions::get_sessions,
+            sessions::get_session_clips,
+            sessions::copy_session,
+            sessions::export_session,
+            settings::get_settings,
+            settings::update_settings,
+            projects::list_projects,
+            projects::create_project,
+            projects::update_project,
+            projects::delete_project,
+            projects::set_active_project,
+            projects::get_active_project,
+            projects::set_clip_project,
+            focus::get_focus_rules,
+            focus::set_focus_rules,
+            hotkey::get_global_shortcut,
+            hotkey::set_global_shortcut,
+            power::get_power_status,
+            tags::add_tag,
+            tags::remove_tag,
+            tags::get_tags,
+            collections::list_collections,
+            collections::create_collection,
+            collections::rename_collection,
+            collections::delete_collection,
+            collections::set_clip_collection,
+            pinned::pin_clip,
+            pinned::unpin_clip,
+            pinned::reorder_pinned,
+            locking::lock_clip,
+            locking::unlock_clip,
+            archive::export_clips,
+            tags::add_tag_to_clips,
+            tags::remove_tag_from_clips,
+            archive::import_clips,
+            backup::get_backup_config,
+            backup::set_backup_config,
+            backup::run_backup_now,
+            backup::list_backups,
+            backup::restore_backup,
+            encryption::get_database_encryption,
+            encryption::set_database_encryption,
+            backup::browse_backup,
+            backup::restore_clips_from_backup,
+            config::export_config,
+            config::import_config,
+            capture::get_ignored_apps,
+            capture::set_ignored_apps,
+            jsonl::export_jsonl,
+            restore::restore_clipboard_at,
+            double_copy::get_double_copy_action,
+            double_copy::set_double_copy_action,
+            paste::paste_clip,
+            restore::restore_previous_clipboard,
+            hotkey::get_restore_previous_shortcut,
+            hotkey::set_restore_previous_shortcut,
+            paste_queue::enqueue_clips,
+            paste_queue::get_paste_queue,
+            paste_queue::clear_queue,
+            paste_queue::paste_next,
+            hotkey::get_paste_next_shortcut,
+            hotkey::set_paste_next_shortcut,
+            launchers::export_launcher_snippets,
+            gallery::get_thumbnail,
+            split::split_clip,
+            urls::get_url_variants,
+            describe::describe_clips,
+            sounds::preview_sound,
+            snapshots::create_snapshot,
+            snapshots::load_snapshot,
+            snapshots::unload_snapshot,
+            snapshots::get_active_snapshot,
+            snapshots::list_snapshots,
+            snapshots::delete_snapshot,
+            transform::transform_clip,
+            share_link::share_clip_link,
+            snippets::list_snippets,
+            snippets::create_snippet,
+            snippets::update_snippet,
+            snippets::delete_snippet,
+            snippets::expand_snippet,
+            journal::write_journal_now,
+            tables::get_table_shape,
+            tables::copy_table_part,
+            sync::get_sync_config,
+            sync::set_sync_config,
+            sync::sync_now,
+            code_block::copy_as_code_block,
+            ansi::get_ansi_original,
+            trash::get_deleted_clips,
+            trash::restore_clip,
+            trash::delete_clip_forever,
+            trash::empty_trash,
+            purge::delete_clips,
+            purge::clear_history,
+            line_endings::convert_line_endings,
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_pool() -> Pool<Sqlite> {
+        // One connection, so every query sees the same in-memory database
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        sqlx::query(
+            "CREATE TABLE clips (
+                id TEXT PRIMARY KEY, content TEXT NOT NULL, created_at DATETIME, is_favorite BOOLEAN DEFAULT 0,
+                clip_type TEXT DEFAULT 'text', image_path TEXT, is_sensitive BOOLEAN DEFAULT 0, source_url TEXT,
+                image_width INTEGER, image_height INTEGER, dominant_color TEXT, thumbnail_path TEXT, window_title TEXT,
+                rich_format TEXT, canonical_url TEXT, detected_kind TEXT, link_title TEXT, link_favicon TEXT,
+                source_app TEXT, source_app_icon TEXT, session_id TEXT, project_id TEXT, collection_id TEXT,
+                pinned BOOLEAN NOT NULL DEFAULT 0, sort_order INTEGER, locked BOOLEAN NOT NULL DEFAULT 0, deleted_at TEXT
+            )"
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool
+    }
+
+    async fn insert(pool: &Pool<Sqlite>, id: &str, created_at: &str) {
+        sqlx::query("INSERT INTO clips (id, content, created_at) VALUES (?, ?, ?)")
+            .bind(id)
+            .bind(id)
+            .bind(created_at)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn page(pool: &Pool<Sqlite>, cursor: Option<&Clip>) -> Vec<String> {
+        let (filter, args) = clip_filter(None, None, None, None, None, None, None, None);
+        let cursor = cursor.map(|c| (c.created_at.clone(), c.id.clone()));
+        date_page(pool, &filter, args, cursor, 3).await.unwrap().into_iter().map(|c| c.id).collect()
+    }
+
+    #[test]
+    fn keyset_pages_survive_concurrent_inserts() {
+        tauri::async_runtime::block_on(async {
+            let pool = test_pool().await;
+            // Two clips share a timestamp, so the id breaks the tie
+            for (id, created_at) in [("a", "2024-01-01"), ("b", "2024-01-02"), ("c", "2024-01-03"), ("d", "2024-01-03"), ("e", "2024-01-04")] {
+                insert(&pool, id, created_at).await;
+            }
+
+            let first = page(&pool, None).await;
+            assert_eq!(first, ["e", "d", "c"]);
+
+            // Copies made while the user scrolls land at the top, including one
+            // with the same timestamp as the cursor
+            insert(&pool, "f", "2024-01-05").await;
+            insert(&pool, "z", "2024-01-03").await;
+
+            let last: Clip = sqlx::query_as(&format!("SELECT {} FROM clips WHERE id = 'c'", CLIP_COLUMNS))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(page(&pool, Some(&last)).await, ["b", "a"]);
+        });
+    }
+}
@@ -0,0 +1,88 @@
+// Line endings: CRLF from Windows apps breaks shell scripts and diffs, bare
+// LF confuses some Windows tools. `normalize_line_endings` ("off", "lf" or
+// "crlf") converts captured text; `convert_line_endings` fixes a stored clip.
+
+use tauri::AppHandle;
+use sqlx::{Pool, Sqlite};
+
+use crate::classify::is_text_type;
+use crate::{set_clip_content, setting_value, DbState};
+
+pub const MODES: &[&str] = &["off", "lf", "crlf"];
+
+/// `text` with every line break (CRLF, LF or a lone CR) written as `style`.
+pub fn convert(text: &str, style: &str) -> Result<String, String> {
+    let newline = match style {
+        "lf" => "\n",
+        "crlf" => "\r\n",
+        other => return Err(format!("Unknown line ending style: {} (expected \"lf\" or \"crlf\")", other)),
+    };
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => {
+                chars.next_if_eq(&'\n');
+                result.push_str(newline);
+            }
+            '\n' => result.push_str(newline),
+            _ => result.push(c),
+        }
+    }
+    Ok(result)
+}
+
+pub async fn mode(pool: &Pool<Sqlite>) -> String {
+    setting_value(pool, "normalize_line_endings")
+        .await
+        .filter(|m| MODES.contains(&m.as_str()))
+        .unwrap_or_else(|| "off".to_string())
+}
+
+/// Captured text with its line endings normalized, unless turned off.
+pub async fn normalize_capture(pool: &Pool<Sqlite>, text: String) -> String {
+    let mode = mode(pool).await;
+    if mode == "off" || !text.contains(['\r', '\n']) {
+        return text;
+    }
+    convert(&text, &mode).unwrap_or(text)
+}
+
+/// Rewrites the line endings of a text clip as `style` ("lf" or "crlf").
+/// Returns the converted text.
+#[tauri::command]
+pub async fn convert_line_endings(app_handle: AppHandle, state: tauri::State<'_, DbState>, id: String, style: String) -> Result<String, String> {
+    let clip: Option<(String, String)> = sqlx::query_as("SELECT content, clip_type FROM clips WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (content, clip_type) = clip.ok_or_else(|| "Clip not found".to_string())?;
+    if !is_text_type(&clip_type) {
+        return Err("Only text clips have line endings to convert".to_string());
+    }
+
+    let converted = convert(&content, style.trim())?;
+    if converted != content {
+        set_clip_content(&app_handle, &state, &id, converted.clone()).await?;
+    }
+    Ok(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_every_line_break() {
+        assert_eq!(convert("a\r\nb\nc\rd", "lf").unwrap(), "a\nb\nc\nd");
+        assert_eq!(convert("a\r\nb\nc\rd", "crlf").unwrap(), "a\r\nb\r\nc\r\nd");
+        assert_eq!(convert("a\r\r\nb\n", "lf").unwrap(), "a\n\nb\n");
+    }
+
+    #[test]
+    fn rejects_unknown_styles() {
+        assert!(convert("a\nb", "cr").is_err());
+        assert!(convert("a\nb", "off").is_err());
+    }
+}
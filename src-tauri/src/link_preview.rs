@@ -0,0 +1,222 @@
+// Page title and favicon for URL clips, fetched in the background after
+// capture so the list can show "GitHub - sqlx README" instead of a bare link.
+// Favicons are cached once per host as a small PNG under `favicons/`.
+// Off unless `fetch_link_previews` is turned on, since fetching tells the site
+// what was copied. Links that look like they carry credentials are never
+// fetched. Skipped in low power mode; the scheduler catches up later.
+
+use tauri::{AppHandle, Emitter, Manager};
+use sqlx::{Pool, Sqlite};
+use image::imageops;
+use regex::Regex;
+use std::time::Duration;
+use chrono::prelude::*;
+
+use crate::classify::domain_of;
+use crate::{app_data_dir, images, power, setting_enabled, trash, DbState};
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_PAGE_BYTES: usize = 512 * 1024; // the <head> is all we need
+const MAX_FAVICON_BYTES: usize = 256 * 1024;
+const MAX_TITLE_CHARS: usize = 200;
+const FAVICON_SIZE: u32 = 32;
+// Query parameters that carry secrets (reset links, signed URLs, OAuth codes)
+const PRIVATE_PARAMS: &[&str] = &["token", "key", "code", "auth", "sig", "signature", "secret", "password", "session", "credential"];
+const MIN_TOKEN_CHARS: usize = 24;
+// Clips skipped in low power mode are caught up for this long after capture
+const CATCH_UP_DAYS: i64 = 7;
+const CATCH_UP_BATCH: i64 = 20;
+
+pub async fn enabled(pool: &Pool<Sqlite>) -> bool {
+    setting_enabled(pool, "fetch_link_previews", false).await
+}
+
+/// True for links that shouldn't be sent anywhere: ones with a login in them
+/// or a query parameter that looks like a token.
+fn looks_private(url: &reqwest::Url) -> bool {
+    if !url.username().is_empty() || url.password().is_some() {
+        return true;
+    }
+    url.query_pairs().any(|(name, value)| {
+        let name = name.to_lowercase();
+        let random = value.len() >= MIN_TOKEN_CHARS && value.chars().all(|c| c.is_ascii_alphanumeric() || "-_.~+/=%".contains(c));
+        PRIVATE_PARAMS.iter().any(|p| name.contains(p)) || random
+    })
+}
+
+fn client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("Klip/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Reads at most `limit` bytes of the response body.
+async fn read_limited(mut response: reqwest::Response, limit: usize) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        body.extend_from_slice(&chunk);
+        if body.len() >= limit {
+            body.truncate(limit);
+            break;
+        }
+    }
+    Ok(body)
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+fn page_title(html: &str) -> Option<String> {
+    // Open Graph titles skip the " | Site name" suffix most <title>s carry
+    let og_title = Regex::new(r#"(?is)<meta[^>]+property\s*=\s*["']og:title["'][^>]*content\s*=\s*["']([^"']*)["']"#).unwrap();
+    let title = Regex::new(r"(?is)<title[^>]*>(.*?)</title>").unwrap();
+    let raw = og_title.captures(html).or_else(|| title.captures(html))?.get(1)?.as_str();
+    let title = decode_entities(&raw.split_whitespace().collect::<Vec<_>>().join(" "));
+    if title.is_empty() {
+        return None;
+    }
+    Some(title.chars().take(MAX_TITLE_CHARS).collect())
+}
+
+/// The icon the page declares with `<link rel="icon">`, if any.
+fn favicon_url(page: &reqwest::Url, html: &str) -> Option<reqwest::Url> {
+    let link = Regex::new(r"(?is)<link\b[^>]*>").unwrap();
+    let attributes = Regex::new(r#"(?is)(rel|href)\s*=\s*["']([^"']*)["']"#).unwrap();
+    let href = link.find_iter(html).find_map(|tag| {
+        let mut rel = None;
+        let mut href = None;
+        for attribute in attributes.captures_iter(tag.as_str()) {
+            match attribute[1].to_lowercase().as_str() {
+                "rel" => rel = Some(attribute[2].to_lowercase()),
+                _ => href = Some(attribute[2].to_string()),
+            }
+        }
+        rel.filter(|r| r.split_whitespace().any(|r| r == "icon")).and(href)
+    })?;
+    page.join(&decode_entities(&href)).ok()
+}
+
+/// Downloads and caches the favicon for `host`, returning the cached file.
+async fn cached_favicon(app: &AppHandle, client: &reqwest::Client, host: &str, url: reqwest::Url) -> Option<String> {
+    let key: String = host.chars().map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' }).collect();
+    let path = app_data_dir(app).join("favicons").join(format!("{}.png", key));
+    if path.exists() {
+        return Some(path.to_string_lossy().to_string());
+    }
+
+    let response = client.get(url).send().await.ok()?.error_for_status().ok()?;
+    let bytes = read_limited(response, MAX_FAVICON_BYTES).await.ok()?;
+    let icon = image::load_from_memory(&bytes).ok()?.into_rgba8();
+    let icon = imageops::resize(&icon, FAVICON_SIZE, FAVICON_SIZE, imageops::FilterType::Triangle);
+    std::fs::create_dir_all(path.parent()?).ok()?;
+    images::save_png(&path, &icon).ok()?;
+    Some(path.to_string_lossy().to_string())
+}
+
+/// Fetches the title and favicon of a URL clip and stores them. Returns
+/// whether anything was found.
+pub async fn process(app: &AppHandle, pool: &Pool<Sqlite>, id: &str, url: &str) -> Result<bool, String> {
+    let client = client()?;
+    let page = reqwest::Url::parse(url.trim()).map_err(|e| e.to_string())?;
+    let host = domain_of(url).unwrap_or_default();
+
+    let mut title = None;
+    let mut favicon = None;
+    let private = looks_private(&page);
+    let response = if private { None } else { client.get(page.clone()).send().await.and_then(|r| r.error_for_status()).ok() };
+    if let Some(response) = response {
+        // Redirects are followed, so relative icon links resolve against the final URL
+        let final_url = response.url().clone();
+        let is_html = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_none_or(|v| v.contains("html"));
+        if is_html {
+            let html = String::from_utf8_lossy(&read_limited(response, MAX_PAGE_BYTES).await?).to_string();
+            title = page_title(&html);
+            if let Some(icon) = favicon_url(&final_url, &html) {
+                favicon = cached_favicon(app, &client, &host, icon).await;
+            }
+        }
+    }
+    // Most sites that don't declare one still serve the conventional path
+    if favicon.is_none() && !private {
+        if let Ok(icon) = page.join("/favicon.ico") {
+            favicon = cached_favicon(app, &client, &host, icon).await;
+        }
+    }
+
+    sqlx::query("UPDATE clips SET link_title = ?, link_favicon = ?, link_checked = 1 WHERE id = ?")
+        .bind(&title)
+        .bind(&favicon)
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(title.is_some() || favicon.is_some())
+}
+
+/// Queues a preview fetch for a freshly captured URL clip.
+pub fn queue(app: &AppHandle, id: String, url: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = app.try_state::<DbState>() else { return };
+        if !enabled(&state.pool).await || power::is_low_power() {
+            return;
+        }
+        match process(&app, &state.pool, &id, &url).await {
+            Ok(true) => {
+                state.recent.invalidate();
+                let _ = app.emit("clipboard-changed", ());
+            }
+            Ok(false) => {}
+            Err(e) => eprintln!("Link preview failed for clip {}: {}", id, e),
+        }
+    });
+}
+
+/// Fetches previews for recent URL clips that don't have one yet, such as
+/// those captured in low power mode. Run from the scheduler; returns how
+/// many clips got a preview.
+pub async fn catch_up(app: &AppHandle, pool: &Pool<Sqlite>) -> Result<usize, String> {
+    if !enabled(pool).await || power::is_low_power() {
+        return Ok(0);
+    }
+    let since = Utc::now() - chrono::Duration::days(CATCH_UP_DAYS);
+    let pending: Vec<(String, String)> = sqlx::query_as(&format!(
+        "SELECT id, content FROM clips WHERE clip_type = 'url' AND link_checked = 0 AND created_at >= ? AND {} ORDER BY created_at DESC LIMIT ?",
+        trash::NOT_DELETED
+    ))
+    .bind(since.to_rfc3339())
+    .bind(CATCH_UP_BATCH)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut found = 0;
+    for (id, url) in pending {
+        match process(app, pool, &id, &url).await {
+            Ok(true) => found += 1,
+            Ok(false) => {}
+            Err(e) => {
+                // Not retried, so one bad link can't hold up the rest
+                eprintln!("Link preview failed for clip {}: {}", id, e);
+                let _ = sqlx::query("UPDATE clips SET link_checked = 1 WHERE id = ?").bind(&id).execute(pool).await;
+            }
+        }
+    }
+    if found > 0 {
+        let _ = app.emit("clipboard-changed", ());
+    }
+    Ok(found)
+}
@@ -0,0 +1,62 @@
+// Locked clips (license keys, canonical templates) can't be edited or deleted
+// until unlocked. Single-clip commands check up front for a clear error; bulk
+// deletes skip locked clips; triggers back both up so no path can slip past.
+
+use sqlx::{Pool, Sqlite};
+
+use crate::DbState;
+
+const SCHEMA: &[&str] = &[
+    "CREATE TRIGGER IF NOT EXISTS clips_locked_delete BEFORE DELETE ON clips WHEN old.locked = 1 BEGIN
+        SELECT RAISE(ABORT, 'Clip is locked');
+    END",
+    "CREATE TRIGGER IF NOT EXISTS clips_locked_update BEFORE UPDATE OF content ON clips
+     WHEN old.locked = 1 AND new.content IS NOT old.content BEGIN
+        SELECT RAISE(ABORT, 'Clip is locked');
+    END",
+];
+
+/// Must run after the `locked` migration.
+pub async fn init_locks(pool: &Pool<Sqlite>) -> Result<(), String> {
+    for statement in SCHEMA {
+        sqlx::query(statement).execute(pool).await.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub async fn ensure_unlocked(pool: &Pool<Sqlite>, id: &str) -> Result<(), String> {
+    let locked: Option<(bool,)> = sqlx::query_as("SELECT locked FROM clips WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    match locked {
+        Some((true,)) => Err("Clip is locked".to_string()),
+        _ => Ok(()),
+    }
+}
+
+async fn set_locked(state: &DbState, id: &str, locked: bool) -> Result<(), String> {
+    let result = sqlx::query("UPDATE clips SET locked = ? WHERE id = ?")
+        .bind(locked)
+        .bind(id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if result.rows_affected() == 0 {
+        return Err("Clip not found".to_string());
+    }
+
+    state.recent.update(id, |clip| clip.locked = locked);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn lock_clip(state: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
+    set_locked(&state, &id, true).await
+}
+
+#[tauri::command]
+pub async fn unlock_clip(state: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
+    set_locked(&state, &id, false).await
+}
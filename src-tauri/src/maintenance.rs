@@ -0,0 +1,121 @@
+// Background backfill of derived data for existing image clips (dimensions,
+// dominant color, thumbnail, OCR text), for histories captured before those
+// were computed or while they were put off in low power mode.
+// Runs in batches on its own thread, reports progress and can be cancelled.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use tauri::{AppHandle, Emitter, Manager};
+use serde::{Serialize, Deserialize};
+
+use crate::{images, ocr, DbState};
+
+const BATCH_SIZE: usize = 25;
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static CANCEL: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MaintenanceProgress {
+    processed: usize,
+    total: usize,
+    updated: usize,
+    failed: usize, // missing or unreadable files, or OCR errors
+    done: bool,
+    cancelled: bool,
+}
+
+async fn run(app: &AppHandle, force: bool) -> Result<(), String> {
+    let state = app.try_state::<DbState>().ok_or_else(|| "Database is not available".to_string())?;
+    let ocr = ocr::enabled(&state.pool).await && ocr::available();
+    let mut query = "SELECT id, image_path, ocr_checked FROM clips WHERE clip_type = 'image'".to_string();
+    if !force {
+        query.push_str(" AND (image_width IS NULL OR dominant_color IS NULL OR thumbnail_path IS NULL");
+        if ocr {
+            query.push_str(" OR (ocr_checked = 0 AND content = '')");
+        }
+        query.push(')');
+    }
+    let rows: Vec<(String, Option<String>, bool)> = sqlx::query_as(&query)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut progress = MaintenanceProgress { total: rows.len(), ..Default::default() };
+    for batch in rows.chunks(BATCH_SIZE) {
+        if CANCEL.load(Ordering::Relaxed) {
+            progress.cancelled = true;
+            break;
+        }
+
+        for (id, image_path, ocr_checked) in batch {
+            progress.processed += 1;
+            let (path, img) = match image_path.as_deref().and_then(|p| Some((p, images::open_rgba(p).ok()?))) {
+                Some(found) => found,
+                None => {
+                    progress.failed += 1;
+                    continue;
+                }
+            };
+
+            let meta = images::image_meta(&img);
+            let thumbnail_path = images::save_thumbnail(Path::new(path), &img).ok();
+            sqlx::query("UPDATE clips SET image_width = ?, image_height = ?, dominant_color = ?, thumbnail_path = ? WHERE id = ?")
+                .bind(meta.width)
+                .bind(meta.height)
+                .bind(meta.dominant_color)
+                .bind(thumbnail_path.map(|p| p.to_string_lossy().to_string()))
+                .bind(id)
+                .execute(&state.pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            if ocr && (force || !ocr_checked) && ocr::process(&state.pool, id, PathBuf::from(path)).await.is_err() {
+                progress.failed += 1;
+                continue;
+            }
+            progress.updated += 1;
+        }
+
+        let _ = app.emit("maintenance-progress", progress.clone());
+    }
+
+    progress.done = true;
+    state.recent.invalidate();
+    let _ = app.emit("maintenance-progress", progress);
+    let _ = app.emit("clipboard-changed", ());
+    Ok(())
+}
+
+/// Starts the image backfill. With `force`, every image clip is reprocessed,
+/// not just those missing data. Progress arrives as `maintenance-progress` events.
+#[tauri::command]
+pub fn start_image_maintenance(app_handle: AppHandle, force: Option<bool>) -> Result<(), String> {
+    if app_handle.try_state::<DbState>().is_none() {
+        return Err("Database is not available".to_string());
+    }
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err("Maintenance is already running".to_string());
+    }
+    CANCEL.store(false, Ordering::SeqCst);
+
+    thread::spawn(move || {
+        if let Err(e) = tauri::async_runtime::block_on(run(&app_handle, force.unwrap_or(false))) {
+            eprintln!("Image maintenance failed: {}", e);
+            let _ = app_handle.emit("maintenance-failed", e);
+        }
+        RUNNING.store(false, Ordering::SeqCst);
+    });
+    Ok(())
+}
+
+/// Stops the running backfill after the current batch.
+#[tauri::command]
+pub fn cancel_image_maintenance() {
+    CANCEL.store(true, Ordering::SeqCst);
+}
+
+#[tauri::command]
+pub fn is_image_maintenance_running() -> bool {
+    RUNNING.load(Ordering::SeqCst)
+}
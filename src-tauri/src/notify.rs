@@ -0,0 +1,81 @@
+use tauri::{AppHandle, Manager, Emitter};
+use notify_rust::Notification;
+
+use crate::{remove_clip, trash, DbState};
+
+const PREVIEW_CHARS: usize = 120;
+
+fn preview(content: &str) -> String {
+    let line = content.trim().lines().next().unwrap_or_default();
+    let mut preview: String = line.chars().take(PREVIEW_CHARS).collect();
+    if line.chars().count() > PREVIEW_CHARS || content.trim().lines().count() > 1 {
+        preview.push('…');
+    }
+    preview
+}
+
+fn build(content: &str) -> Notification {
+    let mut notification = Notification::new();
+    notification
+        .appname("Klip")
+        .summary("Clip saved")
+        .body(&preview(content));
+    notification
+}
+
+/// Shows a "clip saved" notification with Favorite, Delete and Don't save
+/// buttons, handled right here in the backend.
+pub fn notify_capture(app: &AppHandle, clip_id: String, content: &str) {
+    let mut notification = build(content);
+    notification
+        .action("favorite", "Favorite")
+        .action("delete", "Delete")
+        .action("dont_save", "Don't save");
+
+    if let Ok(handle) = notification.show() {
+        let app = app.clone();
+        // wait_for_action blocks until the user acts or the notification closes.
+        // On macOS it is also what sends the notification.
+        std::thread::spawn(move || {
+            handle.wait_for_action(|action| {
+                let action = action.to_string();
+                tauri::async_runtime::block_on(async move {
+                    let _ = apply_capture_action(&app, &clip_id, &action).await;
+                });
+            });
+        });
+    }
+}
+
+async fn apply_capture_action(app: &AppHandle, clip_id: &str, action: &str) -> Result<(), String> {
+    let state = app.try_state::<DbState>().ok_or_else(|| "Database is not available".to_string())?;
+
+    match action {
+        "favorite" => {
+            sqlx::query("UPDATE clips SET is_favorite = 1 WHERE id = ?")
+                .bind(clip_id)
+                .execute(&state.pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            state.recent.update(clip_id, |clip| clip.is_favorite = true);
+        }
+        "delete" => trash::trash_clip(&state, clip_id).await?,
+        // A copy that was never meant to be kept skips the trash
+        "dont_save" => remove_clip(&state, clip_id).await?,
+        // Notification dismissed or unknown action
+        _ => return Ok(()),
+    }
+
+    let _ = app.emit("clipboard-changed", ());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn handle_notification_action(app_handle: AppHandle, clip_id: String, action: String) -> Result<(), String> {
+    apply_capture_action(&app_handle, &clip_id, &action).await
+}
+
+/// Plain informational notification, used by scheduled jobs.
+pub fn notify_text(summary: &str, body: &str) {
+    let _ = Notification::new().appname("Klip").summary(summary).body(body).show();
+}
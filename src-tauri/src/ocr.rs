@@ -0,0 +1,128 @@
+// Background OCR for image clips, so screenshots are found by the text in
+// them. Uses Windows.Media.Ocr on Windows and the `tesseract` command (when
+// installed) elsewhere. The text becomes the image clip's content, like the
+// manual "extract text" action, and is indexed through `search_content`.
+// Disabled with the `ocr_images` setting; skipped in low power mode, where
+// image maintenance catches up later, and for the rest of the session once
+// tesseract turns out to be missing. Locked clips keep their content.
+
+use tauri::{AppHandle, Emitter, Manager};
+use sqlx::{Pool, Sqlite};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{normalize_text, power, setting_enabled, setting_value, DbState};
+
+// Set when the OCR engine can't be run at all
+static UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+pub async fn enabled(pool: &Pool<Sqlite>) -> bool {
+    setting_enabled(pool, "ocr_images", true).await
+}
+
+/// False once the OCR engine failed to start this session.
+pub fn available() -> bool {
+    !UNAVAILABLE.load(Ordering::Relaxed)
+}
+
+#[cfg(target_os = "windows")]
+fn recognize(path: &Path, _languages: Option<&str>) -> Result<String, String> {
+    use windows::core::HSTRING;
+    use windows::Graphics::Imaging::BitmapDecoder;
+    use windows::Media::Ocr::OcrEngine;
+    use windows::Storage::{FileAccessMode, StorageFile};
+
+    (|| -> windows::core::Result<String> {
+        let file = StorageFile::GetFileFromPathAsync(&HSTRING::from(path.as_os_str()))?.get()?;
+        let stream = file.OpenAsync(FileAccessMode::Read)?.get()?;
+        let bitmap = BitmapDecoder::CreateAsync(&stream)?.get()?.GetSoftwareBitmapAsync()?.get()?;
+        let engine = OcrEngine::TryCreateFromUserProfileLanguages()?;
+        Ok(engine.RecognizeAsync(&bitmap)?.get()?.Text()?.to_string())
+    })()
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn recognize(path: &Path, languages: Option<&str>) -> Result<String, String> {
+    let mut command = std::process::Command::new("tesseract");
+    command.arg(path).arg("stdout");
+    if let Some(languages) = languages {
+        command.args(["-l", languages]);
+    }
+    let output = command.output().map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => {
+            UNAVAILABLE.store(true, Ordering::Relaxed);
+            "tesseract is not installed".to_string()
+        }
+        _ => e.to_string(),
+    })?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Runs OCR on an image clip and stores the text, unless the clip already has
+/// text (extracted or typed by hand) or is locked. Returns whether text was stored.
+pub async fn process(pool: &Pool<Sqlite>, id: &str, path: PathBuf) -> Result<bool, String> {
+    if !available() {
+        return Ok(false);
+    }
+    let locked: Option<(bool,)> = sqlx::query_as("SELECT locked FROM clips WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if locked.is_some_and(|(locked,)| locked) {
+        mark_checked(pool, id).await?;
+        return Ok(false);
+    }
+
+    // Tesseract language codes, e.g. "eng+spa"; its default otherwise
+    let languages = setting_value(pool, "ocr_languages").await.filter(|l| !l.trim().is_empty());
+    let text = tauri::async_runtime::spawn_blocking(move || recognize(&path, languages.as_deref()))
+        .await
+        .map_err(|e| e.to_string())??;
+    let text = text.trim();
+
+    mark_checked(pool, id).await?;
+    if text.is_empty() {
+        return Ok(false);
+    }
+    let result = sqlx::query("UPDATE clips SET content = ?, search_content = ? WHERE id = ? AND content = '' AND locked = 0")
+        .bind(text)
+        .bind(normalize_text(text))
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(result.rows_affected() > 0)
+}
+
+async fn mark_checked(pool: &Pool<Sqlite>, id: &str) -> Result<(), String> {
+    sqlx::query("UPDATE clips SET ocr_checked = 1 WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Queues OCR for a freshly captured image clip.
+pub fn queue(app: &AppHandle, id: String, path: PathBuf) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = app.try_state::<DbState>() else { return };
+        if !enabled(&state.pool).await || power::is_low_power() {
+            return;
+        }
+        match process(&state.pool, &id, path).await {
+            Ok(true) => {
+                state.recent.invalidate();
+                let _ = app.emit("clipboard-changed", ());
+            }
+            Ok(false) => {}
+            Err(e) => eprintln!("OCR failed for clip {}: {}", id, e),
+        }
+    });
+}
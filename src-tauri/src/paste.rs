@@ -0,0 +1,213 @@
+// Pasting a clip straight into the app that was in front before Klip: copy
+// it, hide Klip, give focus back to that app and send the paste keystroke.
+// The app is remembered whenever Klip's window is shown. Keystrokes need the
+// Accessibility permission on macOS and an X11 session (XTEST) on Linux.
+// With the `auto_paste` setting off, selecting a clip stops after the copy.
+
+use tauri::{AppHandle, Manager};
+use sqlx::{Pool, Sqlite};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::{restore, setting_enabled, DbState};
+
+// Time for the window manager to hand focus back before the keystroke
+const FOCUS_DELAY: Duration = Duration::from_millis(120);
+
+// HWND on Windows, pid on macOS, X11 window id on Linux
+static PREVIOUS_APP: Mutex<Option<i64>> = Mutex::new(None);
+
+/// Remembers the frontmost app, unless it's Klip itself. Call before showing Klip.
+pub fn remember_previous_app() {
+    if let Some(app) = frontmost_app() {
+        *PREVIOUS_APP.lock().unwrap() = Some(app);
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn frontmost_app() -> Option<i64> {
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        (!hwnd.is_invalid() && pid != std::process::id()).then_some(hwnd.0 as i64)
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn activate(app: i64) -> bool {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::SetForegroundWindow;
+
+    unsafe { SetForegroundWindow(HWND(app as *mut _)) }.as_bool()
+}
+
+#[cfg(target_os = "windows")]
+fn send_paste() -> Result<(), String> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP, VIRTUAL_KEY, VK_CONTROL, VK_V};
+
+    let key = |vk: VIRTUAL_KEY, flags: KEYBD_EVENT_FLAGS| INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 { ki: KEYBDINPUT { wVk: vk, dwFlags: flags, ..Default::default() } },
+    };
+    let inputs = [
+        key(VK_CONTROL, KEYBD_EVENT_FLAGS(0)),
+        key(VK_V, KEYBD_EVENT_FLAGS(0)),
+        key(VK_V, KEYEVENTF_KEYUP),
+        key(VK_CONTROL, KEYEVENTF_KEYUP),
+    ];
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        return Err(windows::core::Error::from_win32().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn frontmost_app() -> Option<i64> {
+    use objc2_app_kit::NSWorkspace;
+
+    let pid = NSWorkspace::sharedWorkspace().frontmostApplication()?.processIdentifier();
+    (pid > 0 && pid as u32 != std::process::id()).then_some(pid as i64)
+}
+
+#[cfg(target_os = "macos")]
+fn activate(app: i64) -> bool {
+    use objc2_app_kit::{NSApplicationActivationOptions, NSRunningApplication};
+
+    NSRunningApplication::runningApplicationWithProcessIdentifier(app as i32)
+        .is_some_and(|running| running.activateWithOptions(NSApplicationActivationOptions::ActivateAllWindows))
+}
+
+#[cfg(target_os = "macos")]
+fn send_paste() -> Result<(), String> {
+    use objc2_core_graphics::{CGEvent, CGEventFlags, CGEventTapLocation};
+
+    const KEY_V: u16 = 9; // kVK_ANSI_V
+    for key_down in [true, false] {
+        let event = CGEvent::new_keyboard_event(None, KEY_V, key_down)
+            .ok_or_else(|| "Couldn't create the paste keystroke (is Accessibility access granted?)".to_string())?;
+        CGEvent::set_flags(Some(&*event), CGEventFlags::MaskCommand);
+        CGEvent::post(CGEventTapLocation::HIDEventTap, Some(&*event));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn x11_connect() -> Result<(x11rb::rust_connection::RustConnection, u32), String> {
+    use x11rb::connection::Connection;
+
+    let (conn, screen) = x11rb::connect(None).map_err(|e| e.to_string())?;
+    let root = conn.setup().roots.get(screen).ok_or_else(|| "No X11 screen".to_string())?.root;
+    Ok((conn, root))
+}
+
+#[cfg(target_os = "linux")]
+fn frontmost_app() -> Option<i64> {
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _};
+
+    let (conn, root) = x11_connect().ok()?;
+    let active = conn.intern_atom(false, b"_NET_ACTIVE_WINDOW").ok()?.reply().ok()?.atom;
+    let pid_atom = conn.intern_atom(false, b"_NET_WM_PID").ok()?.reply().ok()?.atom;
+    let window = conn.get_property(false, root, active, AtomEnum::WINDOW, 0, 1).ok()?.reply().ok()?.value32()?.next()?;
+    let pid = conn.get_property(false, window, pid_atom, AtomEnum::CARDINAL, 0, 1).ok()?.reply().ok()?.value32()?.next();
+    (window != x11rb::NONE && pid != Some(std::process::id())).then_some(window as i64)
+}
+
+#[cfg(target_os = "linux")]
+fn activate(app: i64) -> bool {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{ClientMessageEvent, ConnectionExt as _, EventMask};
+
+    let Ok((conn, root)) = x11_connect() else { return false };
+    let Some(active) = conn.intern_atom(false, b"_NET_ACTIVE_WINDOW").ok().and_then(|c| c.reply().ok()) else { return false };
+    // Source 2: a pager-like request, which window managers honor without focus-stealing checks
+    let event = ClientMessageEvent::new(32, app as u32, active.atom, [2, x11rb::CURRENT_TIME, 0, 0, 0]);
+    conn.send_event(false, root, EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY, event).is_ok() && conn.flush().is_ok()
+}
+
+#[cfg(target_os = "linux")]
+fn send_paste() -> Result<(), String> {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{ConnectionExt as _, KEY_PRESS_EVENT, KEY_RELEASE_EVENT};
+    use x11rb::protocol::xtest::ConnectionExt as _;
+    use x11rb::wrapper::ConnectionExt as _;
+
+    const XK_CONTROL_L: u32 = 0xffe3;
+    const XK_V: u32 = 0x0076;
+
+    let (conn, root) = x11_connect()?;
+    let setup = conn.setup();
+    let (min, max) = (setup.min_keycode, setup.max_keycode);
+    let mapping = conn.get_keyboard_mapping(min, max - min + 1).map_err(|e| e.to_string())?.reply().map_err(|e| e.to_string())?;
+    let per_keycode = mapping.keysyms_per_keycode.max(1) as usize;
+    let keycode = |keysym: u32| {
+        mapping
+            .keysyms
+            .chunks(per_keycode)
+            .position(|syms| syms.contains(&keysym))
+            .map(|index| min + index as u8)
+            .ok_or_else(|| format!("No key for keysym {:#x}", keysym))
+    };
+    let (control, v) = (keycode(XK_CONTROL_L)?, keycode(XK_V)?);
+
+    for (event, key) in [(KEY_PRESS_EVENT, control), (KEY_PRESS_EVENT, v), (KEY_RELEASE_EVENT, v), (KEY_RELEASE_EVENT, control)] {
+        conn.xtest_fake_input(event, key, x11rb::CURRENT_TIME, root, 0, 0, 0).map_err(|e| e.to_string())?;
+    }
+    conn.sync().map_err(|e| e.to_string())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn frontmost_app() -> Option<i64> {
+    None
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn activate(_app: i64) -> bool {
+    false
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn send_paste() -> Result<(), String> {
+    Err("Auto-paste is not supported on this platform".to_string())
+}
+
+/// Copies a clip and pastes it into the frontmost app. With `refocus`, Klip is
+/// hidden and focus returned to the app it was opened from first. Returns
+/// whether the keystroke was sent: false with `auto_paste` off, or when that
+/// app is unknown or couldn't be brought back (the keystroke would land in
+/// whatever has focus instead).
+pub async fn paste(app: &AppHandle, pool: &Pool<Sqlite>, id: &str, refocus: bool) -> Result<bool, String> {
+    restore::copy_clip(pool, id).await?;
+
+    if refocus {
+        let previous = *PREVIOUS_APP.lock().unwrap();
+        let refocused = previous.is_some_and(activate);
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.hide();
+        }
+        if !refocused {
+            return Ok(false);
+        }
+    }
+    if !setting_enabled(pool, "auto_paste", true).await {
+        return Ok(false);
+    }
+
+    tauri::async_runtime::spawn_blocking(|| {
+        std::thread::sleep(FOCUS_DELAY);
+        send_paste()
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    Ok(true)
+}
+
+/// Copies a clip, hides Klip, returns focus to the previous app and pastes
+/// there. Returns whether the keystroke was sent (false with `auto_paste` off).
+#[tauri::command]
+pub async fn paste_clip(app_handle: AppHandle, state: tauri::State<'_, DbState>, id: String) -> Result<bool, String> {
+    paste(&app_handle, &state.pool, &id, true).await
+}
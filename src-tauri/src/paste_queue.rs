@@ -0,0 +1,55 @@
+// Sequential pasting: select several clips, then paste them one by one into
+// another app. The queue lives in memory; each `paste_next` (or the
+// `paste_next_shortcut` hotkey) pastes the front clip and drops it. The UI
+// gets a `paste-queue-changed` event with the remaining ids.
+
+use tauri::{AppHandle, Emitter, Manager};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::{paste, DbState};
+
+static QUEUE: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+fn snapshot() -> Vec<String> {
+    QUEUE.lock().unwrap().iter().cloned().collect()
+}
+
+fn notify(app: &AppHandle) {
+    let _ = app.emit("paste-queue-changed", snapshot());
+}
+
+/// Pastes the next queued clip into the frontmost app. Returns the pasted id,
+/// or None when the queue is empty. With `refocus` Klip hides and hands focus
+/// back first, as when pasting from its window rather than a hotkey.
+pub async fn paste_next_clip(app: &AppHandle, refocus: bool) -> Result<Option<String>, String> {
+    let Some(id) = QUEUE.lock().unwrap().pop_front() else { return Ok(None) };
+    notify(app);
+    let state = app.try_state::<DbState>().ok_or_else(|| "Database is not available".to_string())?;
+    paste::paste(app, &state.pool, &id, refocus).await?;
+    Ok(Some(id))
+}
+
+/// Appends clips to the queue, in the given order. Returns the queue.
+#[tauri::command]
+pub fn enqueue_clips(app_handle: AppHandle, ids: Vec<String>) -> Vec<String> {
+    QUEUE.lock().unwrap().extend(ids);
+    notify(&app_handle);
+    snapshot()
+}
+
+#[tauri::command]
+pub fn get_paste_queue() -> Vec<String> {
+    snapshot()
+}
+
+#[tauri::command]
+pub fn clear_queue(app_handle: AppHandle) {
+    QUEUE.lock().unwrap().clear();
+    notify(&app_handle);
+}
+
+#[tauri::command]
+pub async fn paste_next(app_handle: AppHandle) -> Result<Option<String>, String> {
+    paste_next_clip(&app_handle, true).await
+}
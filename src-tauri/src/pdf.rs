@@ -0,0 +1,243 @@
+// Print-friendly PDF of selected text clips: a title and timestamp per clip,
+// prose in Helvetica, code in Courier with light keyword/string/comment
+// coloring. Built-in PDF fonts only cover Windows-1252, so other characters
+// are transliterated rather than silently dropped.
+
+use chrono::prelude::*;
+use deunicode::deunicode_char;
+use printpdf::{BuiltinFont, Color, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Rgb};
+
+use crate::classify::{is_text_type, looks_like_code};
+use crate::export::{fetch_clips, ExportFilter};
+use crate::{Clip, DbState};
+
+const PAGE_WIDTH: f32 = 210.0; // A4, mm
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 18.0;
+const PT_TO_MM: f32 = 0.3528;
+
+const TITLE_SIZE: f32 = 11.0;
+const META_SIZE: f32 = 8.0;
+const TEXT_SIZE: f32 = 10.0;
+const CODE_SIZE: f32 = 8.5;
+const TITLE_CHARS: usize = 80;
+
+const KEYWORDS: &[&str] = &[
+    "fn", "let", "mut", "const", "function", "def", "class", "import", "from", "return", "if", "else",
+    "for", "while", "match", "struct", "enum", "impl", "pub", "use", "var", "async", "await", "new",
+    "true", "false", "null", "None", "self", "this", "SELECT", "FROM", "WHERE", "INSERT", "UPDATE", "DELETE",
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Token {
+    Plain,
+    Keyword,
+    Literal, // strings and numbers
+    Comment,
+}
+
+impl Token {
+    fn color(self) -> Color {
+        let (r, g, b) = match self {
+            Token::Plain => (0.1, 0.1, 0.1),
+            Token::Keyword => (0.0, 0.2, 0.6),
+            Token::Literal => (0.1, 0.45, 0.15),
+            Token::Comment => (0.5, 0.5, 0.5),
+        };
+        Color::Rgb(Rgb::new(r, g, b, None))
+    }
+}
+
+/// Maps text onto what the built-in fonts can draw.
+fn printable(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\t' => out.push_str("    "),
+            c if (c as u32) < 0x100 && !c.is_control() => out.push(c),
+            c if c.is_control() => {}
+            c => out.push_str(deunicode_char(c).unwrap_or("?")),
+        }
+    }
+    out
+}
+
+/// Word-wraps to at most `width` characters, hard-breaking words that don't fit.
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in line.split(' ') {
+        let fits = current.chars().count() + word.chars().count() + usize::from(!current.is_empty()) <= width;
+        if !fits && !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+        while current.chars().count() > width {
+            let head: String = current.chars().take(width).collect();
+            current = current.chars().skip(width).collect();
+            lines.push(head);
+        }
+    }
+    lines.push(current);
+    lines
+}
+
+/// Splits a line of code into colored runs. Heuristic, language-agnostic.
+fn highlight(line: &str) -> Vec<(Token, String)> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut runs: Vec<(Token, String)> = Vec::new();
+    let mut push = |token: Token, text: String| match runs.last_mut() {
+        Some((last, run)) if *last == token => run.push_str(&text),
+        _ => runs.push((token, text)),
+    };
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let rest: String = chars[i..].iter().collect();
+        if rest.starts_with("//") || rest.starts_with("# ") || rest.starts_with("--") {
+            push(Token::Comment, rest);
+            break;
+        }
+        if c == '"' || c == '\'' || c == '`' {
+            let end = chars[i + 1..].iter().position(|&q| q == c).map(|p| i + p + 2).unwrap_or(chars.len());
+            push(Token::Literal, chars[i..end].iter().collect());
+            i = end;
+        } else if c.is_alphanumeric() || c == '_' {
+            let end = chars[i..].iter().position(|&w| !(w.is_alphanumeric() || w == '_')).map(|p| i + p).unwrap_or(chars.len());
+            let word: String = chars[i..end].iter().collect();
+            let token = if KEYWORDS.contains(&word.as_str()) {
+                Token::Keyword
+            } else if c.is_ascii_digit() {
+                Token::Literal
+            } else {
+                Token::Plain
+            };
+            push(token, word);
+            i = end;
+        } else {
+            push(Token::Plain, c.to_string());
+            i += 1;
+        }
+    }
+    runs
+}
+
+struct Writer {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    y: f32, // baseline of the next line, mm from the bottom
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    mono: IndirectFontRef,
+}
+
+impl Writer {
+    fn new(title: &str) -> Result<Writer, String> {
+        let (doc, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Clips");
+        let font = |f| doc.add_builtin_font(f).map_err(|e| e.to_string());
+        let (regular, bold, mono) = (font(BuiltinFont::Helvetica)?, font(BuiltinFont::HelveticaBold)?, font(BuiltinFont::Courier)?);
+        let layer = doc.get_page(page).get_layer(layer);
+        Ok(Writer { doc, layer, y: PAGE_HEIGHT - MARGIN, regular, bold, mono })
+    }
+
+    /// Starts a new page unless `height` mm still fit on this one.
+    fn reserve(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Clips");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    fn line(&mut self, runs: &[(Token, String)], font: &IndirectFontRef, size: f32) {
+        let height = size * PT_TO_MM * 1.35;
+        self.reserve(height);
+        self.y -= height;
+        self.layer.begin_text_section();
+        self.layer.set_font(font, size);
+        self.layer.set_text_cursor(Mm(MARGIN), Mm(self.y));
+        for (token, text) in runs {
+            self.layer.set_fill_color(token.color());
+            self.layer.write_text(text.as_str(), font);
+        }
+        self.layer.end_text_section();
+    }
+
+    fn clip(&mut self, clip: &Clip) {
+        let content = printable(&clip.content.replace("\r\n", "\n"));
+        let first_line = content.lines().find(|l| !l.trim().is_empty()).unwrap_or("").trim();
+        let mut title: String = first_line.chars().take(TITLE_CHARS).collect();
+        if first_line.chars().count() > TITLE_CHARS {
+            title.push_str("...");
+        }
+
+        let created = DateTime::parse_from_rfc3339(&clip.created_at)
+            .map(|d| d.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|_| clip.created_at.clone());
+        let mut meta = created;
+        if let Some(window) = &clip.window_title {
+            meta.push_str(&format!(" - {}", printable(window)));
+        }
+        if let Some(url) = &clip.source_url {
+            meta.push_str(&format!(" - {}", url));
+        }
+
+        // Keep the heading with at least the first few lines of the body
+        self.reserve((TITLE_SIZE + META_SIZE + 3.0 * TEXT_SIZE) * PT_TO_MM * 1.35);
+        let bold = self.bold.clone();
+        let regular = self.regular.clone();
+        self.line(&[(Token::Plain, title)], &bold, TITLE_SIZE);
+        self.line(&[(Token::Comment, meta)], &regular, META_SIZE);
+        self.y -= 1.5;
+
+        let text_width = PAGE_WIDTH - 2.0 * MARGIN;
+        if looks_like_code(&clip.content) {
+            let mono = self.mono.clone();
+            let width = (text_width / (0.6 * CODE_SIZE * PT_TO_MM)) as usize;
+            for line in content.lines() {
+                for piece in wrap(line, width) {
+                    self.line(&highlight(&piece), &mono, CODE_SIZE);
+                }
+            }
+        } else {
+            // Helvetica averages about half an em per character
+            let width = (text_width / (0.52 * TEXT_SIZE * PT_TO_MM)) as usize;
+            for line in content.lines() {
+                for piece in wrap(line, width) {
+                    self.line(&[(Token::Plain, piece)], &regular, TEXT_SIZE);
+                }
+            }
+        }
+        self.y -= 6.0;
+    }
+}
+
+/// Writes the given text clips to a PDF at `path`, oldest first. Image clips
+/// are skipped. Returns the number of clips written.
+#[tauri::command]
+pub async fn export_pdf(state: tauri::State<'_, DbState>, ids: Vec<String>, path: String) -> Result<usize, String> {
+    if ids.is_empty() {
+        return Err("No clips selected".to_string());
+    }
+    let filter = ExportFilter { ids: Some(ids), ..Default::default() };
+    let clips: Vec<Clip> = fetch_clips(&state.pool, &filter)
+        .await?
+        .into_iter()
+        .filter(|c| is_text_type(&c.clip_type))
+        .collect();
+    if clips.is_empty() {
+        return Err("None of the selected clips are text".to_string());
+    }
+
+    let mut writer = Writer::new("Klip export")?;
+    for clip in &clips {
+        writer.clip(clip);
+    }
+    let bytes = writer.doc.save_to_bytes().map_err(|e| e.to_string())?;
+    std::fs::write(&path, bytes).map_err(|e| e.to_string())?;
+    Ok(clips.len())
+}
@@ -0,0 +1,68 @@
+// Pinned clips stay at the top of the history list in a hand-picked order
+// (`sort_order`, ascending) instead of by recency. Like favorites, they are
+// never removed by retention.
+
+use crate::DbState;
+
+/// Pins a clip at the end of the pinned list. Pinning twice keeps its place.
+#[tauri::command]
+pub async fn pin_clip(state: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
+    let result = sqlx::query(
+        "UPDATE clips SET pinned = 1, sort_order = (SELECT COALESCE(MAX(sort_order), -1) + 1 FROM clips WHERE pinned = 1)
+         WHERE id = ? AND pinned = 0"
+    )
+    .bind(&id)
+    .execute(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if result.rows_affected() > 0 {
+        state.recent.invalidate();
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unpin_clip(state: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
+    sqlx::query("UPDATE clips SET pinned = 0, sort_order = NULL WHERE id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    state.recent.update(&id, |clip| {
+        clip.pinned = false;
+        clip.sort_order = None;
+    });
+    Ok(())
+}
+
+/// Sets the pinned order to `ids`, top first. Pinned clips left out keep
+/// their relative order after the listed ones.
+#[tauri::command]
+pub async fn reorder_pinned(state: tauri::State<'_, DbState>, ids: Vec<String>) -> Result<(), String> {
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    let current: Vec<(String,)> = sqlx::query_as("SELECT id FROM clips WHERE pinned = 1 ORDER BY sort_order, id")
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let pinned: Vec<String> = current.into_iter().map(|(id,)| id).collect();
+    if let Some(unknown) = ids.iter().find(|id| !pinned.contains(id)) {
+        return Err(format!("Clip is not pinned: {}", unknown));
+    }
+    let rest = pinned.iter().filter(|id| !ids.contains(id));
+
+    for (position, id) in ids.iter().chain(rest).enumerate() {
+        sqlx::query("UPDATE clips SET sort_order = ? WHERE id = ?")
+            .bind(position as i64)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    state.recent.invalidate();
+    Ok(())
+}
@@ -0,0 +1,108 @@
+// Low-power mode for laptops: polls the clipboard less often, skips derived
+// image data at capture time (filled in by maintenance once back on power)
+// and runs retention less often. `low_power_mode` is "auto" (follow the
+// battery, the default), "on" or "off". Re-evaluated on every scheduler tick.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter};
+use sqlx::{Pool, Sqlite};
+use serde::{Serialize, Deserialize};
+
+use crate::{maintenance, setting_value, DbState};
+
+pub const POLL_FACTOR: u32 = 3;
+pub const MIN_RETENTION_INTERVAL_HOURS: i64 = 6;
+pub const MODES: [&str; 3] = ["auto", "on", "off"];
+
+static LOW_POWER: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PowerStatus {
+    mode: String,
+    on_battery: bool,
+    low_power: bool,
+}
+
+pub fn is_low_power() -> bool {
+    LOW_POWER.load(Ordering::Relaxed)
+}
+
+/// Whether the machine is running on battery. Unknown counts as plugged in.
+#[cfg(target_os = "windows")]
+pub fn on_battery() -> bool {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    // ACLineStatus: 0 = offline, 1 = online, 255 = unknown
+    unsafe { GetSystemPowerStatus(&mut status) }.is_ok() && status.ACLineStatus == 0
+}
+
+#[cfg(target_os = "macos")]
+pub fn on_battery() -> bool {
+    // First line reads "Now drawing from 'Battery Power'" or "'AC Power'"
+    std::process::Command::new("pmset")
+        .args(["-g", "ps"])
+        .output()
+        .is_ok_and(|out| String::from_utf8_lossy(&out.stdout).contains("'Battery Power'"))
+}
+
+#[cfg(target_os = "linux")]
+pub fn on_battery() -> bool {
+    let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else { return false };
+    let read = |path: std::path::PathBuf| std::fs::read_to_string(path).unwrap_or_default().trim().to_string();
+
+    let (mut has_battery, mut mains_online) = (false, false);
+    for supply in supplies.flatten() {
+        let path = supply.path();
+        match read(path.join("type")).as_str() {
+            "Battery" => has_battery = true,
+            "Mains" | "USB" => mains_online |= read(path.join("online")) == "1",
+            _ => {}
+        }
+    }
+    has_battery && !mains_online
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn on_battery() -> bool {
+    false
+}
+
+pub async fn mode(pool: &Pool<Sqlite>) -> String {
+    setting_value(pool, "low_power_mode")
+        .await
+        .filter(|m| MODES.contains(&m.as_str()))
+        .unwrap_or_else(|| "auto".to_string())
+}
+
+/// Recomputes low-power mode. Emits "low-power-changed" when it flips, and on
+/// leaving it catches up on image data skipped in the meantime.
+pub async fn refresh(app: &AppHandle, pool: &Pool<Sqlite>) -> PowerStatus {
+    let mode = mode(pool).await;
+    let on_battery = on_battery();
+    let low_power = match mode.as_str() {
+        "on" => true,
+        "off" => false,
+        _ => on_battery,
+    };
+
+    if LOW_POWER.swap(low_power, Ordering::Relaxed) != low_power {
+        let _ = app.emit("low-power-changed", low_power);
+        if !low_power {
+            let deferred: Option<(i32,)> = sqlx::query_as("SELECT 1 FROM clips WHERE clip_type = 'image' AND (dominant_color IS NULL OR thumbnail_path IS NULL) LIMIT 1")
+                .fetch_optional(pool)
+                .await
+                .unwrap_or(None);
+            if deferred.is_some() {
+                // Already running is fine; it picks these up too
+                let _ = maintenance::start_image_maintenance(app.clone(), None);
+            }
+        }
+    }
+    PowerStatus { mode, on_battery, low_power }
+}
+
+#[tauri::command]
+pub async fn get_power_status(app_handle: AppHandle, state: tauri::State<'_, DbState>) -> Result<PowerStatus, String> {
+    Ok(refresh(&app_handle, &state.pool).await)
+}
@@ -0,0 +1,183 @@
+// Projects keep client work apart without manual tagging. While a project is
+// active (`active_project` setting) every new capture is assigned to it;
+// otherwise a capture goes to the first project listing the foreground app.
+
+use sqlx::{Pool, Sqlite};
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+use crate::{setting_value, DbState};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Project {
+    id: String,
+    name: String,
+    apps: Vec<String>, // foreground app names, matched case-insensitively
+    created_at: String, // ISO 8601
+    clip_count: i64,
+    active: bool,
+}
+
+fn parse_apps(json: &str) -> Vec<String> {
+    serde_json::from_str(json).unwrap_or_default()
+}
+
+fn clean_apps(apps: Vec<String>) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for app in apps.iter().map(|a| a.trim()).filter(|a| !a.is_empty()) {
+        if !cleaned.iter().any(|c| c.eq_ignore_ascii_case(app)) {
+            cleaned.push(app.to_string());
+        }
+    }
+    cleaned
+}
+
+pub async fn active_project(pool: &Pool<Sqlite>) -> Option<String> {
+    setting_value(pool, "active_project").await.filter(|id| !id.is_empty())
+}
+
+/// Project a capture from `app` is assigned to, if any.
+pub async fn project_for_capture(pool: &Pool<Sqlite>, app: Option<&str>) -> Option<String> {
+    if let Some(active) = active_project(pool).await {
+        return Some(active);
+    }
+
+    let app = app?;
+    let projects: Vec<(String, String)> = sqlx::query_as("SELECT id, apps FROM projects ORDER BY created_at ASC")
+        .fetch_all(pool)
+        .await
+        .ok()?;
+    projects
+        .into_iter()
+        .find(|(_, apps)| parse_apps(apps).iter().any(|a| a.eq_ignore_ascii_case(app)))
+        .map(|(id, _)| id)
+}
+
+#[tauri::command]
+pub async fn list_projects(state: tauri::State<'_, DbState>) -> Result<Vec<Project>, String> {
+    let active = active_project(&state.pool).await;
+    let rows: Vec<(String, String, String, String, i64)> = sqlx::query_as(
+        "SELECT p.id, p.name, p.apps, p.created_at, (SELECT COUNT(*) FROM clips c WHERE c.project_id = p.id AND c.deleted_at IS NULL)
+         FROM projects p ORDER BY p.name COLLATE NOCASE"
+    )
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, name, apps, created_at, clip_count)| Project {
+            active: active.as_deref() == Some(id.as_str()),
+            apps: parse_apps(&apps),
+            id,
+            name,
+            created_at,
+            clip_count,
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn create_project(state: tauri::State<'_, DbState>, name: String, apps: Option<Vec<String>>) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Project name can't be empty".to_string());
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let apps = serde_json::to_string(&clean_apps(apps.unwrap_or_default())).map_err(|e| e.to_string())?;
+    sqlx::query("INSERT INTO projects (id, name, apps, created_at) VALUES (?, ?, ?, ?)")
+        .bind(&id)
+        .bind(name)
+        .bind(apps)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+#[tauri::command]
+pub async fn update_project(state: tauri::State<'_, DbState>, id: String, name: Option<String>, apps: Option<Vec<String>>) -> Result<(), String> {
+    if let Some(name) = name.as_deref().map(str::trim) {
+        if name.is_empty() {
+            return Err("Project name can't be empty".to_string());
+        }
+        sqlx::query("UPDATE projects SET name = ? WHERE id = ?")
+            .bind(name)
+            .bind(&id)
+            .execute(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    if let Some(apps) = apps {
+        let apps = serde_json::to_string(&clean_apps(apps)).map_err(|e| e.to_string())?;
+        sqlx::query("UPDATE projects SET apps = ? WHERE id = ?")
+            .bind(apps)
+            .bind(&id)
+            .execute(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Deletes the project; its clips stay in history, unassigned.
+#[tauri::command]
+pub async fn delete_project(state: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    for statement in [
+        "UPDATE clips SET project_id = NULL WHERE project_id = ?",
+        "DELETE FROM projects WHERE id = ?",
+        "DELETE FROM settings WHERE key = 'active_project' AND value = ?",
+    ] {
+        sqlx::query(statement)
+            .bind(&id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+/// Switches the active project; `None` goes back to app-based assignment.
+#[tauri::command]
+pub async fn set_active_project(state: tauri::State<'_, DbState>, id: Option<String>) -> Result<(), String> {
+    if let Some(id) = &id {
+        let exists: Option<(i32,)> = sqlx::query_as("SELECT 1 FROM projects WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        if exists.is_none() {
+            return Err("Project not found".to_string());
+        }
+    }
+
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES ('active_project', ?)")
+        .bind(id.unwrap_or_default())
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_active_project(state: tauri::State<'_, DbState>) -> Result<Option<String>, String> {
+    Ok(active_project(&state.pool).await)
+}
+
+/// Moves a clip into a project by hand, or out of any with `None`.
+#[tauri::command]
+pub async fn set_clip_project(state: tauri::State<'_, DbState>, id: String, project_id: Option<String>) -> Result<(), String> {
+    sqlx::query("UPDATE clips SET project_id = ? WHERE id = ?")
+        .bind(&project_id)
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    state.recent.update(&id, |clip| clip.project_id = project_id);
+    Ok(())
+}
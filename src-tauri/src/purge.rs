@@ -0,0 +1,212 @@
+// Targeted "forget": delete every clip matching a set of criteria, along with
+// its usage events, relations and image files. Locked clips are never matched.
+// Also the bulk deletes: a multi-selection, or a whole slice of history.
+// These skip the trash.
+
+use tauri::{AppHandle, Emitter};
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::classify::{domain_of, is_url};
+use crate::retention::PurgeSummary;
+use crate::{images, tags, trash, DbState};
+
+const MAX_BULK_IDS: usize = 10_000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PurgeCriteria {
+    pub domain: Option<String>,    // matches subdomains too
+    pub date: Option<String>,      // YYYY-MM-DD, local time
+    pub date_from: Option<String>, // YYYY-MM-DD, local time, inclusive
+    pub date_to: Option<String>,
+    pub tag: Option<String>,
+    pub source_app: Option<String>,
+    pub include_favorites: Option<bool>, // default false: favorites survive a purge
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PurgeReport {
+    images: usize, // image files removed (or that would be, on a dry run)
+    dry_run: bool,
+    #[serde(flatten)]
+    summary: PurgeSummary,
+}
+
+#[derive(sqlx::FromRow)]
+struct Candidate {
+    id: String,
+    content: String,
+    image_path: Option<String>,
+    source_url: Option<String>,
+    created_at: String,
+}
+
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+fn matches_domain(candidate: &Candidate, domain: &str) -> bool {
+    let from_source = candidate.source_url.as_deref().and_then(domain_of);
+    let from_content = if is_url(&candidate.content) { domain_of(&candidate.content) } else { None };
+    [from_source, from_content]
+        .iter()
+        .flatten()
+        .any(|host| domain_matches(host, domain))
+}
+
+/// Deletes clips matching all given criteria in a single transaction.
+/// At least one criterion is required, so an empty call never wipes history.
+/// With `dry_run`, only reports what would be deleted.
+#[tauri::command]
+pub async fn purge_where(app_handle: AppHandle, state: tauri::State<'_, DbState>, criteria: PurgeCriteria, dry_run: Option<bool>) -> Result<PurgeReport, String> {
+    let domain = criteria
+        .domain
+        .as_deref()
+        .map(|d| d.trim().trim_start_matches("www.").to_lowercase())
+        .filter(|d| !d.is_empty());
+    let date = criteria.date.filter(|d| !d.is_empty());
+    let date_from = criteria.date_from.filter(|d| !d.is_empty());
+    let date_to = criteria.date_to.filter(|d| !d.is_empty());
+    let tag = criteria.tag.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    let source_app = criteria.source_app.map(|a| a.trim().to_string()).filter(|a| !a.is_empty());
+
+    if domain.is_none() && date.is_none() && date_from.is_none() && date_to.is_none() && tag.is_none() && source_app.is_none() {
+        return Err("Nothing to purge: pass a domain, a date, a tag or an app".to_string());
+    }
+
+    let mut query = "SELECT id, content, image_path, source_url, created_at FROM clips WHERE locked = 0".to_string();
+    let mut args = Vec::new();
+    if !criteria.include_favorites.unwrap_or(false) {
+        query.push_str(" AND is_favorite = 0");
+    }
+    if let Some(date) = date {
+        query.push_str(" AND strftime('%Y-%m-%d', created_at, 'localtime') = ?");
+        args.push(date);
+    }
+    if let Some(from) = date_from {
+        query.push_str(" AND strftime('%Y-%m-%d', created_at, 'localtime') >= ?");
+        args.push(from);
+    }
+    if let Some(to) = date_to {
+        query.push_str(" AND strftime('%Y-%m-%d', created_at, 'localtime') <= ?");
+        args.push(to);
+    }
+    if let Some(tag) = tag {
+        query.push_str(&format!(" AND {}", tags::HAS_TAG));
+        args.push(tag);
+    }
+    if let Some(app) = source_app {
+        query.push_str(" AND source_app = ?");
+        args.push(app);
+    }
+
+    let mut sql_query = sqlx::query_as::<_, Candidate>(&query);
+    for arg in args {
+        sql_query = sql_query.bind(arg);
+    }
+    let candidates = sql_query.fetch_all(&state.pool).await.map_err(|e| e.to_string())?;
+
+    let doomed: Vec<Candidate> = match &domain {
+        Some(domain) => candidates.into_iter().filter(|c| matches_domain(c, domain)).collect(),
+        None => candidates,
+    };
+
+    let bytes = doomed.iter().map(|c| c.content.len() as u64).sum::<u64>()
+        + doomed
+            .iter()
+            .filter_map(|c| c.image_path.as_ref())
+            .filter_map(|p| std::fs::metadata(p).ok())
+            .map(|m| m.len())
+            .sum::<u64>();
+    let oldest = doomed.iter().map(|c| c.created_at.clone()).min();
+    let mut report = PurgeReport {
+        images: doomed.iter().filter(|c| c.image_path.is_some()).count(),
+        dry_run: dry_run.unwrap_or(false),
+        summary: PurgeSummary::new(doomed.len() as i64, bytes, oldest),
+    };
+    if report.dry_run {
+        return Ok(report);
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    for clip in &doomed {
+        for statement in [
+            "DELETE FROM clip_usage WHERE clip_id = ?",
+            "DELETE FROM clip_relations WHERE from_id = ?1 OR to_id = ?1",
+            "DELETE FROM clips WHERE id = ?",
+        ] {
+            sqlx::query(statement)
+                .bind(&clip.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    // Files go only after the rows are gone, so a failed transaction leaves nothing dangling
+    report.images = 0;
+    for path in doomed.iter().filter_map(|c| c.image_path.as_ref()) {
+        if images::remove_image(path).is_ok() {
+            report.images += 1;
+        }
+    }
+
+    state.recent.invalidate();
+    let _ = app_handle.emit("clipboard-changed", ());
+    Ok(report)
+}
+
+/// Deletes the given clips for good in one transaction. Locked clips are
+/// skipped. Returns how many were deleted.
+#[tauri::command]
+pub async fn delete_clips(app_handle: AppHandle, state: tauri::State<'_, DbState>, ids: Vec<String>) -> Result<usize, String> {
+    if ids.len() > MAX_BULK_IDS {
+        return Err(format!("At most {} clips can be deleted at once", MAX_BULK_IDS));
+    }
+    let deleted = trash::delete_forever(&state.pool, &ids).await?;
+    state.recent.invalidate();
+    let _ = app_handle.emit("clipboard-changed", ());
+    Ok(deleted)
+}
+
+/// Clears part of history for good: `scope` "all", "non_favorites" (keeps
+/// favorites and pinned clips), "today" or "older_than" (with `days`).
+/// Locked clips always stay. Returns how many clips were deleted.
+#[tauri::command]
+pub async fn clear_history(app_handle: AppHandle, state: tauri::State<'_, DbState>, scope: String, days: Option<i64>) -> Result<usize, String> {
+    let mut cutoff = None;
+    let condition = match scope.trim() {
+        "all" => "1=1",
+        "non_favorites" => "is_favorite = 0 AND pinned = 0",
+        "today" => "strftime('%Y-%m-%d', created_at, 'localtime') = strftime('%Y-%m-%d', 'now', 'localtime')",
+        "older_than" => {
+            let days = days.filter(|d| *d >= 0).ok_or_else(|| "Clearing older clips needs a number of days".to_string())?;
+            let since = chrono::Duration::try_days(days)
+                .and_then(|days| Utc::now().checked_sub_signed(days))
+                .ok_or_else(|| format!("{} days is out of range", days))?;
+            cutoff = Some(since.to_rfc3339());
+            "created_at < ?"
+        }
+        other => return Err(format!("Unknown scope: {} (expected \"all\", \"non_favorites\", \"today\" or \"older_than\")", other)),
+    };
+
+    let query = format!("SELECT id FROM clips WHERE locked = 0 AND {}", condition);
+    let mut sql_query = sqlx::query_as::<_, (String,)>(&query);
+    if let Some(cutoff) = &cutoff {
+        sql_query = sql_query.bind(cutoff);
+    }
+    let ids: Vec<String> = sql_query
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(id,)| id)
+        .collect();
+
+    let deleted = trash::delete_forever(&state.pool, &ids).await?;
+    state.recent.invalidate();
+    let _ = app_handle.emit("clipboard-changed", ());
+    let _ = app_handle.emit("trash-changed", ());
+    Ok(deleted)
+}
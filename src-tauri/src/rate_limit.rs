@@ -0,0 +1,86 @@
+// Protection against apps that spam the clipboard (broken apps rewriting it
+// every 100 ms, clipboard hijackers). Captures are counted per clipboard owner
+// over the last minute, since a background app can write while another has
+// focus; where the owner is unknown, repeats of the same content are counted.
+// Above `capture_rate_limit` (per minute, 0 = off) the monitor stops storing
+// from that source until its rate drops. The UI gets a `capture-rate-limited`
+// event naming the foreground app, at most once per few minutes.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use sqlx::{Pool, Sqlite};
+use serde::{Serialize, Deserialize};
+
+use crate::foreground::ForegroundWindow;
+use crate::{setting_value, source};
+
+pub const DEFAULT_RATE_LIMIT: u32 = 60;
+const WINDOW: Duration = Duration::from_secs(60);
+const WARN_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Default)]
+struct AppRate {
+    captures: VecDeque<Instant>,
+    warned_at: Option<Instant>,
+}
+
+static RATES: Mutex<Option<HashMap<String, AppRate>>> = Mutex::new(None);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RateLimitWarning {
+    app: String,
+    captures_per_minute: usize,
+    limit: u32,
+}
+
+pub async fn rate_limit(pool: &Pool<Sqlite>) -> u32 {
+    setting_value(pool, "capture_rate_limit").await.and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_RATE_LIMIT)
+}
+
+/// Counts a capture from the clipboard owner, or of `content_key` when the
+/// owner is unknown, and returns true when that source is over the limit, so
+/// the capture should be dropped. Dropped copies count too: a source stays
+/// limited for as long as it keeps spamming.
+pub async fn is_limited(app: &AppHandle, pool: &Pool<Sqlite>, window: Option<&ForegroundWindow>, content_key: impl FnOnce() -> String) -> bool {
+    let limit = rate_limit(pool).await;
+    if limit == 0 {
+        return false;
+    }
+    let key = match source::clipboard_owner() {
+        Some(owner) => format!("owner:{}", owner),
+        None => format!("content:{}", content_key()),
+    };
+    let source = window
+        .and_then(|w| w.app_name.clone().or_else(|| w.app_id.clone()))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let now = Instant::now();
+    let warning = {
+        let mut rates = RATES.lock().unwrap();
+        let rates = rates.get_or_insert_with(HashMap::new);
+        // Forget apps that went quiet
+        rates.retain(|_, rate| rate.captures.back().is_some_and(|last| now.duration_since(*last) < WINDOW));
+
+        let rate = rates.entry(key).or_default();
+        rate.captures.push_back(now);
+        while rate.captures.front().is_some_and(|first| now.duration_since(*first) >= WINDOW) {
+            rate.captures.pop_front();
+        }
+        if rate.captures.len() <= limit as usize {
+            return false;
+        }
+        let warn = rate.warned_at.is_none_or(|at| now.duration_since(at) >= WARN_INTERVAL);
+        if warn {
+            rate.warned_at = Some(now);
+        }
+        warn.then_some(RateLimitWarning { app: source, captures_per_minute: rate.captures.len(), limit })
+    };
+
+    if let Some(warning) = warning {
+        eprintln!("Clipboard rate limit hit by {} ({} captures in the last minute)", warning.app, warning.captures_per_minute);
+        let _ = app.emit("capture-rate-limited", warning);
+    }
+    true
+}
@@ -0,0 +1,82 @@
+use tauri::{AppHandle, Emitter};
+use chrono::prelude::*;
+use regex::Regex;
+use uuid::Uuid;
+
+use crate::{relations, store, DbState};
+
+const REDACTION_CHAR: char = '█';
+
+/// Built-in patterns that can be passed by name instead of a raw regex.
+fn preset_pattern(name: &str) -> Option<&'static str> {
+    match name {
+        "email" => Some(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}"),
+        "ipv4" => Some(r"\b(?:\d{1,3}\.){3}\d{1,3}\b"),
+        // Common token shapes: AWS keys, GitHub/Slack tokens, JWTs, long hex/base64 secrets
+        "secret" => Some(r"AKIA[0-9A-Z]{16}|gh[pousr]_[A-Za-z0-9]{36,}|xox[abprs]-[A-Za-z0-9-]{10,}|eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+|\b[A-Fa-f0-9]{32,}\b|\b[A-Za-z0-9+/]{40,}={0,2}"),
+        _ => None,
+    }
+}
+
+fn mask(len: usize) -> String {
+    std::iter::repeat_n(REDACTION_CHAR, len).collect()
+}
+
+/// Replaces the given character ranges (start inclusive, end exclusive) with redaction blocks.
+fn redact_ranges(text: &str, ranges: &[(usize, usize)]) -> String {
+    text.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if !c.is_whitespace() && ranges.iter().any(|(start, end)| i >= *start && i < *end) {
+                REDACTION_CHAR
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+fn redact_pattern(text: &str, pattern: &str) -> Result<String, String> {
+    let re = Regex::new(preset_pattern(pattern).unwrap_or(pattern)).map_err(|e| e.to_string())?;
+    Ok(re
+        .replace_all(text, |caps: &regex::Captures| mask(caps[0].chars().count()))
+        .into_owned())
+}
+
+/// Creates a redacted copy of a text clip and flags the original as sensitive.
+/// Either `ranges` (character offsets) or `pattern` (regex or preset name:
+/// "email", "ipv4", "secret") must be given. Returns the new clip id.
+#[tauri::command]
+pub async fn redact_clip(app_handle: AppHandle, state: tauri::State<'_, DbState>, id: String, ranges: Option<Vec<(usize, usize)>>, pattern: Option<String>) -> Result<String, String> {
+    let row: Option<(String,)> = sqlx::query_as("SELECT content FROM clips WHERE id = ? AND clip_type IN ('text', 'url')")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let (content,) = row.ok_or_else(|| "Clip not found".to_string())?;
+
+    let redacted = match (ranges, pattern) {
+        (Some(ranges), _) if !ranges.is_empty() => redact_ranges(&content, &ranges),
+        (_, Some(pattern)) if !pattern.is_empty() => redact_pattern(&content, &pattern)?,
+        _ => return Err("Nothing to redact: pass ranges or a pattern".to_string()),
+    };
+
+    if redacted == content {
+        return Err("No matches to redact".to_string());
+    }
+
+    let new_id = Uuid::new_v4().to_string();
+    store::insert_text_clip(&state.pool, &new_id, &redacted, &Utc::now().to_rfc3339(), false).await?;
+
+    sqlx::query("UPDATE clips SET is_sensitive = 1 WHERE id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    relations::record_relation(&state.pool, &new_id, &id, relations::REDACTED_FROM).await?;
+    state.recent.invalidate();
+    let _ = app_handle.emit("clipboard-changed", ());
+    Ok(new_id)
+}
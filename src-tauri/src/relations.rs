@@ -0,0 +1,129 @@
+// Links between clips, read as "<from> is <kind> <to>", e.g. a redacted copy
+// is "redacted_from" its original. Derived clips record their provenance here.
+
+use sqlx::{Pool, Sqlite};
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::{Clip, DbState, CLIP_COLUMNS};
+
+pub const ANNOTATED_FROM: &str = "annotated_from";
+pub const REDACTED_FROM: &str = "redacted_from";
+pub const CONFLICT_COPY_OF: &str = "conflict_copy_of";
+pub const SPLIT_FROM: &str = "split_from";
+
+#[derive(Debug, sqlx::FromRow)]
+struct RelationRow {
+    id: i64,
+    from_id: String,
+    to_id: String,
+    kind: String,
+    created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelatedClip {
+    relation_id: i64,
+    kind: String,
+    direction: String, // "outgoing" when the queried clip is `from`, otherwise "incoming"
+    created_at: String,
+    clip: Clip,
+}
+
+pub async fn record_relation(pool: &Pool<Sqlite>, from_id: &str, to_id: &str, kind: &str) -> Result<i64, String> {
+    let (id,): (i64,) = sqlx::query_as(
+        "INSERT INTO clip_relations (from_id, to_id, kind, created_at) VALUES (?, ?, ?, ?)
+         ON CONFLICT (from_id, to_id, kind) DO UPDATE SET created_at = created_at
+         RETURNING id"
+    )
+    .bind(from_id)
+    .bind(to_id)
+    .bind(kind)
+    .bind(Utc::now().to_rfc3339())
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+pub async fn remove_relations(pool: &Pool<Sqlite>, clip_id: &str) -> Result<(), String> {
+    sqlx::query("DELETE FROM clip_relations WHERE from_id = ? OR to_id = ?")
+        .bind(clip_id)
+        .bind(clip_id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Links two clips, e.g. `link_clips(b, a, "translated_from")`. Returns the relation id.
+#[tauri::command]
+pub async fn link_clips(state: tauri::State<'_, DbState>, from_id: String, to_id: String, kind: String) -> Result<i64, String> {
+    let kind = kind.trim().to_lowercase().replace(' ', "_");
+    if kind.is_empty() {
+        return Err("Relation kind is required".to_string());
+    }
+    if from_id == to_id {
+        return Err("A clip can't be linked to itself".to_string());
+    }
+
+    let (found,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM clips WHERE id IN (?, ?)")
+        .bind(&from_id)
+        .bind(&to_id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if found < 2 {
+        return Err("Clip not found".to_string());
+    }
+
+    record_relation(&state.pool, &from_id, &to_id, &kind).await
+}
+
+#[tauri::command]
+pub async fn unlink_clips(state: tauri::State<'_, DbState>, relation_id: i64) -> Result<(), String> {
+    sqlx::query("DELETE FROM clip_relations WHERE id = ?")
+        .bind(relation_id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Clips linked to `id` in either direction, newest link first.
+#[tauri::command]
+pub async fn get_related_clips(state: tauri::State<'_, DbState>, id: String) -> Result<Vec<RelatedClip>, String> {
+    let relations = sqlx::query_as::<_, RelationRow>(
+        "SELECT id, from_id, to_id, kind, created_at FROM clip_relations
+         WHERE from_id = ? OR to_id = ? ORDER BY created_at DESC"
+    )
+    .bind(&id)
+    .bind(&id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let query = format!("SELECT {} FROM clips WHERE id = ?", CLIP_COLUMNS);
+    let mut related = Vec::with_capacity(relations.len());
+    for relation in relations {
+        let outgoing = relation.from_id == id;
+        let other_id = if outgoing { &relation.to_id } else { &relation.from_id };
+        let clip = sqlx::query_as::<_, Clip>(&query)
+            .bind(other_id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if let Some(clip) = clip {
+            related.push(RelatedClip {
+                relation_id: relation.id,
+                kind: relation.kind,
+                direction: if outgoing { "outgoing" } else { "incoming" }.to_string(),
+                created_at: relation.created_at,
+                clip,
+            });
+        }
+    }
+
+    Ok(related)
+}
@@ -0,0 +1,143 @@
+// Putting stored clips back on the system clipboard, by id or by time: the
+// tray's "copy last clip" and time-travel restore ("what was on the clipboard
+// at 14:05?", after an app overwrote it right before you pasted). Whatever
+// Klip overwrites is kept in memory, so picking the wrong clip is undone with
+// `restore_previous_clipboard`.
+
+use arboard::{Clipboard, ImageData};
+use sqlx::{Pool, Sqlite};
+use std::borrow::Cow;
+use std::sync::Mutex;
+use chrono::prelude::*;
+
+use crate::sounds::{self, Sound};
+use crate::{files, images, rich, source, usage, DbState};
+
+enum Contents {
+    Text(String),
+    Image { width: usize, height: usize, bytes: Vec<u8> },
+}
+
+// What was on the clipboard before Klip last wrote to it
+static PREVIOUS: Mutex<Option<Contents>> = Mutex::new(None);
+
+fn read_contents(clipboard: &mut Clipboard) -> Option<Contents> {
+    if let Ok(text) = clipboard.get_text() {
+        return (!text.is_empty()).then_some(Contents::Text(text));
+    }
+    clipboard
+        .get_image()
+        .ok()
+        .map(|image| Contents::Image { width: image.width, height: image.height, bytes: image.bytes.into_owned() })
+}
+
+/// Keeps the current clipboard contents for `restore_previous_clipboard`.
+/// Call right before Klip writes to the clipboard. Password-manager copies
+/// aren't kept, and nothing older is restored in their place.
+pub fn remember_current(clipboard: &mut Clipboard) {
+    if source::is_concealed() {
+        *PREVIOUS.lock().unwrap() = None;
+        return;
+    }
+    if let Some(contents) = read_contents(clipboard) {
+        *PREVIOUS.lock().unwrap() = Some(contents);
+    }
+}
+
+/// Puts back what was on the clipboard before Klip's last write. Returns false
+/// when there's nothing to restore.
+pub fn restore_previous() -> Result<bool, String> {
+    let Some(previous) = PREVIOUS.lock().unwrap().take() else { return Ok(false) };
+    let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
+    // Kept in turn, so restoring again swaps back
+    remember_current(&mut clipboard);
+    match previous {
+        Contents::Text(text) => clipboard.set_text(text),
+        Contents::Image { width, height, bytes } => clipboard.set_image(ImageData { width, height, bytes: Cow::Owned(bytes) }),
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Copies a clip to the clipboard, text or image, and records the use.
+pub async fn copy_clip(pool: &Pool<Sqlite>, id: &str) -> Result<(), String> {
+    let clip: Option<(String, String, Option<String>)> = sqlx::query_as("SELECT content, clip_type, image_path FROM clips WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (content, clip_type, image_path) = clip.ok_or_else(|| "Clip not found".to_string())?;
+
+    let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
+    remember_current(&mut clipboard);
+    sounds::play(pool, Sound::Copy).await;
+    match (clip_type.as_str(), image_path) {
+        ("image", Some(path)) => {
+            let rgba = images::open_rgba(&path)?;
+            let (width, height) = rgba.dimensions();
+            clipboard
+                .set_image(ImageData {
+                    width: width as usize,
+                    height: height as usize,
+                    bytes: Cow::Owned(rgba.into_raw()),
+                })
+                .map_err(|e| e.to_string())?;
+            usage::record_usage(pool, id, "copy_image").await
+        }
+        ("files", _) => {
+            files::write(&mut clipboard, &content)?;
+            usage::record_usage(pool, id, "copy_files").await
+        }
+        _ => {
+            match rich::rich_content(pool, id, &content).await? {
+                Some(rich) => rich::write(&mut clipboard, &content, &rich)?,
+                None => clipboard.set_text(content).map_err(|e| e.to_string())?,
+            }
+            usage::record_usage(pool, id, "copy_text").await
+        }
+    }
+}
+
+/// Parses an RFC 3339 timestamp, or a local "YYYY-MM-DDTHH:MM[:SS]" as sent
+/// by a datetime input.
+fn parse_moment(timestamp: &str) -> Result<DateTime<Utc>, String> {
+    let timestamp = timestamp.trim();
+    if let Ok(moment) = DateTime::parse_from_rfc3339(timestamp) {
+        return Ok(moment.with_timezone(&Utc));
+    }
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(timestamp, format).ok())
+        .and_then(|naive| naive.and_local_timezone(Local).earliest())
+        .map(|moment| moment.with_timezone(&Utc))
+        .ok_or_else(|| format!("Invalid timestamp: {}", timestamp))
+}
+
+/// The clip that was on the clipboard at `moment`: the last one captured at or before it.
+pub async fn clip_at(pool: &Pool<Sqlite>, moment: DateTime<Utc>) -> Result<Option<String>, String> {
+    // Nanosecond precision compares correctly against stored timestamps of any precision
+    let moment = moment.to_rfc3339_opts(SecondsFormat::Nanos, false);
+    let row: Option<(String,)> = sqlx::query_as("SELECT id FROM clips WHERE created_at <= ? AND deleted_at IS NULL ORDER BY created_at DESC, id DESC LIMIT 1")
+        .bind(moment)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(row.map(|(id,)| id))
+}
+
+/// Restores the clipboard as it was at `timestamp`. Returns the id of the
+/// restored clip, or None when history doesn't reach back that far.
+#[tauri::command]
+pub async fn restore_clipboard_at(state: tauri::State<'_, DbState>, timestamp: String) -> Result<Option<String>, String> {
+    let Some(id) = clip_at(&state.pool, parse_moment(&timestamp)?).await? else {
+        return Ok(None);
+    };
+    copy_clip(&state.pool, &id).await?;
+    Ok(Some(id))
+}
+
+/// Undoes Klip's last clipboard write. Running it again swaps back.
+#[tauri::command]
+pub fn restore_previous_clipboard() -> Result<bool, String> {
+    restore_previous()
+}
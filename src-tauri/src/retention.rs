@@ -0,0 +1,170 @@
+// Retention: deletes unkept clips past `retention_days` or beyond `max_history`.
+// Runs from the scheduler; with `confirm_purges` on, the user confirms first.
+
+use tauri::{AppHandle, Emitter, Manager};
+use sqlx::{Pool, Sqlite};
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::digest::format_bytes;
+use crate::{images, setting_enabled, setting_value, DbState};
+
+pub const DEFAULT_RETENTION_DAYS: i64 = 90;
+pub const MAX_RETENTION_DAYS: i64 = 36500;
+
+// Bound to (cutoff, max_history); SQLite treats LIMIT -1 as no limit
+const EXPIRED: &str = "is_favorite = 0 AND pinned = 0 AND locked = 0 AND collection_id IS NULL AND (created_at < ?1 OR id NOT IN (
+    SELECT id FROM clips WHERE is_favorite = 0 AND pinned = 0 AND locked = 0 AND collection_id IS NULL AND deleted_at IS NULL ORDER BY created_at DESC, id DESC LIMIT ?2
+))";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PurgeSummary {
+    pub clips: i64,
+    pub bytes: u64, // text plus image files
+    pub oldest: Option<String>, // ISO 8601
+    pub description: String,
+}
+
+impl PurgeSummary {
+    pub fn new(clips: i64, bytes: u64, oldest: Option<String>) -> PurgeSummary {
+        let description = match &oldest {
+            _ if clips == 0 => "Nothing to delete".to_string(),
+            Some(oldest) => format!(
+                "Will delete {} clips, {}, oldest from {}",
+                clips,
+                format_bytes(bytes),
+                oldest.get(..10).unwrap_or(oldest)
+            ),
+            None => format!("Will delete {} clips, {}", clips, format_bytes(bytes)),
+        };
+        PurgeSummary { clips, bytes, oldest, description }
+    }
+}
+
+/// (cutoff timestamp, max non-favorites to keep or -1)
+async fn policy(pool: &Pool<Sqlite>) -> Result<(String, i64), String> {
+    let days = setting_value(pool, "retention_days")
+        .await
+        .and_then(|v| v.parse().ok())
+        .filter(|d| (1..=MAX_RETENTION_DAYS).contains(d))
+        .unwrap_or(DEFAULT_RETENTION_DAYS);
+    let keep = setting_value(pool, "max_history")
+        .await
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(-1);
+    let cutoff = chrono::Duration::try_days(days)
+        .and_then(|d| Utc::now().checked_sub_signed(d))
+        .ok_or_else(|| format!("{} days is out of range", days))?;
+    Ok((cutoff.to_rfc3339(), keep))
+}
+
+fn file_bytes(paths: &[Option<String>]) -> u64 {
+    paths
+        .iter()
+        .flatten()
+        .filter_map(|p| std::fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+async fn expired_images(pool: &Pool<Sqlite>, (cutoff, keep): &(String, i64)) -> Result<Vec<Option<String>>, String> {
+    let rows: Vec<(Option<String>,)> = sqlx::query_as(&format!("SELECT image_path FROM clips WHERE {} AND clip_type = 'image'", EXPIRED))
+        .bind(cutoff)
+        .bind(keep)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(rows.into_iter().map(|(p,)| p).collect())
+}
+
+/// What the retention policy would delete right now.
+pub async fn summarize(pool: &Pool<Sqlite>) -> Result<PurgeSummary, String> {
+    let policy = policy(pool).await?;
+    let (clips, text_bytes, oldest): (i64, i64, Option<String>) = sqlx::query_as(&format!(
+        "SELECT COUNT(*), COALESCE(SUM(length(CAST(content AS BLOB))), 0), MIN(created_at) FROM clips WHERE {}",
+        EXPIRED
+    ))
+    .bind(&policy.0)
+    .bind(policy.1)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let image_bytes = file_bytes(&expired_images(pool, &policy).await?);
+    Ok(PurgeSummary::new(clips, text_bytes as u64 + image_bytes, oldest))
+}
+
+async fn purge(pool: &Pool<Sqlite>) -> Result<(), String> {
+    let policy = policy(pool).await?;
+    let expired = expired_images(pool, &policy).await?;
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let expired_ids = format!("SELECT id FROM clips WHERE {}", EXPIRED);
+    for statement in [
+        format!("DELETE FROM clip_usage WHERE clip_id IN ({})", expired_ids),
+        format!("DELETE FROM clip_relations WHERE from_id IN ({0}) OR to_id IN ({0})", expired_ids),
+        format!("DELETE FROM clips WHERE {}", EXPIRED),
+    ] {
+        sqlx::query(&statement)
+            .bind(&policy.0)
+            .bind(policy.1)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    // Files go only after the rows are gone, as in purge_where
+    for path in expired.into_iter().flatten() {
+        let _ = images::remove_image(path);
+    }
+    Ok(())
+}
+
+/// Applies the retention policy, or only announces it (`retention-pending`)
+/// when purges need confirmation. Returns the summary either way.
+pub async fn run_retention(app: &AppHandle, pool: &Pool<Sqlite>) -> Result<PurgeSummary, String> {
+    let summary = summarize(pool).await?;
+    if summary.clips == 0 {
+        return Ok(summary);
+    }
+
+    if setting_enabled(pool, "confirm_purges", false).await {
+        let _ = app.emit("retention-pending", summary.clone());
+        return Ok(summary);
+    }
+
+    purge(pool).await?;
+    if let Some(state) = app.try_state::<DbState>() {
+        state.recent.invalidate();
+    }
+    let _ = app.emit("retention-applied", summary.clone());
+    let _ = app.emit("clipboard-changed", ());
+    Ok(summary)
+}
+
+/// Pending retention purge awaiting confirmation, if any.
+#[tauri::command]
+pub async fn get_pending_retention(state: tauri::State<'_, DbState>) -> Result<Option<PurgeSummary>, String> {
+    if !setting_enabled(&state.pool, "confirm_purges", false).await {
+        return Ok(None);
+    }
+    let summary = summarize(&state.pool).await?;
+    Ok(if summary.clips > 0 { Some(summary) } else { None })
+}
+
+#[tauri::command]
+pub async fn preview_retention(state: tauri::State<'_, DbState>) -> Result<PurgeSummary, String> {
+    summarize(&state.pool).await
+}
+
+/// Runs the retention purge now, regardless of confirmation mode.
+#[tauri::command]
+pub async fn confirm_retention(app_handle: AppHandle, state: tauri::State<'_, DbState>) -> Result<PurgeSummary, String> {
+    let summary = summarize(&state.pool).await?;
+    purge(&state.pool).await?;
+    state.recent.invalidate();
+    let _ = app_handle.emit("clipboard-changed", ());
+    Ok(summary)
+}
@@ -0,0 +1,109 @@
+// Formatted flavors of copied text. Browsers and word processors put HTML
+// and/or RTF on the clipboard next to the plain text; the richer one is kept
+// in `rich_content` (HTML preferred) so copying the clip back restores the
+// formatting. HTML works everywhere arboard does; RTF is read and written on
+// Windows and macOS only. Capture can be turned off with `capture_rich_text`.
+
+use arboard::Clipboard;
+use sqlx::{Pool, Sqlite};
+
+// Bigger flavors (whole pages with inline images) aren't worth the space
+const MAX_RICH_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RichContent {
+    pub format: String, // "html" or "rtf"
+    pub content: String,
+}
+
+/// The formatted flavor of what's on the clipboard now, if any.
+pub fn read() -> Option<RichContent> {
+    let html = Clipboard::new().ok().and_then(|mut clipboard| clipboard.get().html().ok());
+    let rich = match html.filter(|html| !html.trim().is_empty()) {
+        Some(html) => RichContent { format: "html".to_string(), content: html },
+        None => RichContent { format: "rtf".to_string(), content: read_rtf()? },
+    };
+    (rich.content.len() <= MAX_RICH_BYTES).then_some(rich)
+}
+
+#[cfg(target_os = "windows")]
+fn read_rtf() -> Option<String> {
+    let _clipboard = clipboard_win::Clipboard::new_attempts(10).ok()?;
+    let format = clipboard_win::register_format("Rich Text Format")?;
+    let mut data = Vec::new();
+    clipboard_win::raw::get_vec(format.get(), &mut data).ok()?;
+    let rtf = String::from_utf8_lossy(&data).trim_end_matches('\0').to_string();
+    (!rtf.is_empty()).then_some(rtf)
+}
+
+#[cfg(target_os = "macos")]
+fn read_rtf() -> Option<String> {
+    use objc2_app_kit::{NSPasteboard, NSPasteboardTypeRTF};
+
+    let rtf = NSPasteboard::generalPasteboard().stringForType(unsafe { NSPasteboardTypeRTF })?.to_string();
+    (!rtf.is_empty()).then_some(rtf)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn read_rtf() -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "windows")]
+fn write_rtf(text: &str, rtf: &str) -> Result<(), String> {
+    use clipboard_win::{options::NoClear, raw};
+
+    let _clipboard = clipboard_win::Clipboard::new_attempts(10).map_err(|e| e.to_string())?;
+    let format = clipboard_win::register_format("Rich Text Format").ok_or_else(|| "Can't register the RTF format".to_string())?;
+    raw::empty().map_err(|e| e.to_string())?;
+    raw::set_string_with(text, NoClear).map_err(|e| e.to_string())?;
+    let mut data = rtf.as_bytes().to_vec();
+    data.push(0);
+    raw::set_without_clear(format.get(), &data).map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn write_rtf(text: &str, rtf: &str) -> Result<(), String> {
+    use objc2_app_kit::{NSPasteboard, NSPasteboardTypeRTF, NSPasteboardTypeString};
+    use objc2_foundation::NSString;
+
+    let pasteboard = NSPasteboard::generalPasteboard();
+    pasteboard.clearContents();
+    let written = unsafe {
+        pasteboard.setString_forType(&NSString::from_str(text), NSPasteboardTypeString)
+            && pasteboard.setString_forType(&NSString::from_str(rtf), NSPasteboardTypeRTF)
+    };
+    if !written {
+        return Err("Couldn't write RTF to the pasteboard".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn write_rtf(text: &str, _rtf: &str) -> Result<(), String> {
+    Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text)).map_err(|e| e.to_string())
+}
+
+/// Puts `text` on the clipboard together with its formatted flavor.
+pub fn write(clipboard: &mut Clipboard, text: &str, rich: &RichContent) -> Result<(), String> {
+    match rich.format.as_str() {
+        "html" => clipboard.set().html(rich.content.as_str(), Some(text)).map_err(|e| e.to_string()),
+        "rtf" => write_rtf(text, &rich.content),
+        _ => clipboard.set_text(text).map_err(|e| e.to_string()),
+    }
+}
+
+/// The stored formatted flavor of a clip, if its text is still `text` (the UI
+/// may copy an edited or transformed version).
+pub async fn rich_content(pool: &Pool<Sqlite>, id: &str, text: &str) -> Result<Option<RichContent>, String> {
+    let row: Option<(Option<String>, Option<String>)> = sqlx::query_as("SELECT rich_format, rich_content FROM clips WHERE id = ? AND content = ?")
+        .bind(id)
+        .bind(text)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(match row {
+        Some((Some(format), Some(content))) => Some(RichContent { format, content }),
+        _ => None,
+    })
+}
@@ -0,0 +1,142 @@
+use tauri::{AppHandle, Manager};
+use sqlx::{Pool, Sqlite};
+use std::thread;
+use std::time::Duration;
+use chrono::prelude::*;
+
+use crate::{backup, digest, focus, journal, link_preview, power, retention, setting_enabled, setting_value, sync, trash, DbState};
+
+// How often due jobs are checked; individual jobs decide their own period.
+const TICK: Duration = Duration::from_secs(60);
+pub const DEFAULT_RETENTION_INTERVAL_HOURS: i64 = 1;
+pub const MAX_RETENTION_INTERVAL_HOURS: i64 = 24 * 30;
+// Wait before retrying a network job that failed (server down, offline)
+const RETRY_BACKOFF: chrono::Duration = chrono::Duration::minutes(30);
+
+/// A job is due when it has never run or its last run is older than `period`.
+/// Last-run timestamps live in the settings table as `job_last_run:<name>`.
+async fn job_due(pool: &Pool<Sqlite>, name: &str, period: chrono::Duration) -> bool {
+    let last_run = setting_value(pool, &format!("job_last_run:{}", name))
+        .await
+        .and_then(|v| DateTime::parse_from_rfc3339(&v).ok());
+
+    match last_run {
+        Some(last) => Utc::now() - last.with_timezone(&Utc) >= period,
+        None => true,
+    }
+}
+
+async fn mark_run(pool: &Pool<Sqlite>, name: &str) {
+    let _ = sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
+        .bind(format!("job_last_run:{}", name))
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await;
+}
+
+/// Records a failed run as `job_last_failure:<name>`, holding off retries
+/// for `RETRY_BACKOFF`. A later success doesn't need to clear it.
+async fn mark_failed(pool: &Pool<Sqlite>, name: &str) {
+    let _ = sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
+        .bind(format!("job_last_failure:{}", name))
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await;
+}
+
+/// False while a job that failed recently is backing off.
+async fn retry_allowed(pool: &Pool<Sqlite>, name: &str) -> bool {
+    setting_value(pool, &format!("job_last_failure:{}", name))
+        .await
+        .and_then(|v| DateTime::parse_from_rfc3339(&v).ok())
+        .is_none_or(|failed| Utc::now() - failed.with_timezone(&Utc) >= RETRY_BACKOFF)
+}
+
+async fn run_due_jobs(app: &AppHandle) {
+    let state = match app.try_state::<DbState>() {
+        Some(s) => s,
+        None => return,
+    };
+    let pool = &state.pool;
+
+    focus::evaluate(app, pool).await;
+    power::refresh(app, pool).await;
+
+    let mut retention_hours = setting_value(pool, "retention_interval_hours")
+        .await
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|h| (1..=MAX_RETENTION_INTERVAL_HOURS).contains(h))
+        .unwrap_or(DEFAULT_RETENTION_INTERVAL_HOURS);
+    if power::is_low_power() {
+        // Fewer, larger purges on battery
+        retention_hours = retention_hours.max(power::MIN_RETENTION_INTERVAL_HOURS);
+    }
+    if job_due(pool, "retention", chrono::Duration::hours(retention_hours)).await {
+        match retention::run_retention(app, pool).await {
+            Ok(_) => mark_run(pool, "retention").await,
+            Err(e) => eprintln!("Retention failed: {}", e),
+        }
+    }
+
+    if job_due(pool, "trash", chrono::Duration::days(1)).await {
+        match trash::purge_expired(app, pool).await {
+            Ok(_) => mark_run(pool, "trash").await,
+            Err(e) => eprintln!("Emptying the trash failed: {}", e),
+        }
+    }
+
+    if setting_value(pool, "weekly_digest").await.as_deref() == Some("true")
+        && job_due(pool, "weekly_digest", chrono::Duration::days(7)).await
+    {
+        match digest::run_weekly_digest(app).await {
+            Ok(()) => mark_run(pool, "weekly_digest").await,
+            Err(e) => eprintln!("Weekly digest failed: {}", e),
+        }
+    }
+
+    if journal::enabled(pool).await && job_due(pool, "journal", chrono::Duration::hours(1)).await {
+        match journal::write_new_favorites(pool).await {
+            Ok(_) => mark_run(pool, "journal").await,
+            Err(e) => eprintln!("Journal failed: {}", e),
+        }
+    }
+
+    if link_preview::enabled(pool).await && job_due(pool, "link_previews", chrono::Duration::hours(1)).await {
+        match link_preview::catch_up(app, pool).await {
+            Ok(_) => mark_run(pool, "link_previews").await,
+            Err(e) => eprintln!("Link preview catch-up failed: {}", e),
+        }
+    }
+
+    if sync::enabled(pool).await && job_due(pool, "sync", sync::interval()).await && retry_allowed(pool, "sync").await {
+        match sync::run_sync(app).await {
+            Ok(_) => mark_run(pool, "sync").await,
+            Err(e) => {
+                eprintln!("Sync failed: {}", e);
+                mark_failed(pool, "sync").await;
+            }
+        }
+    }
+
+    // Uploads can wait until the machine is back on power
+    if setting_enabled(pool, "backup_enabled", false).await
+        && !power::is_low_power()
+        && job_due(pool, "backup", chrono::Duration::days(1)).await
+        && retry_allowed(pool, "backup").await
+    {
+        match backup::run_backup(pool).await {
+            Ok(_) => mark_run(pool, "backup").await,
+            Err(e) => {
+                eprintln!("Backup failed: {}", e);
+                mark_failed(pool, "backup").await;
+            }
+        }
+    }
+}
+
+pub fn start_scheduler(app_handle: AppHandle) {
+    thread::spawn(move || loop {
+        tauri::async_runtime::block_on(run_due_jobs(&app_handle));
+        thread::sleep(TICK);
+    });
+}
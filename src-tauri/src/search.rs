@@ -0,0 +1,158 @@
+// Full-text search over clips with SQLite FTS5. `clips_fts` mirrors the
+// searchable columns of `clips` (keyed by the clips rowid) and is kept in sync
+// by triggers, so no insert or update path has to know about it.
+
+use sqlx::{Pool, Sqlite};
+
+use crate::{normalize_text, Clip};
+
+const SCHEMA: &[&str] = &[
+    "CREATE VIRTUAL TABLE IF NOT EXISTS clips_fts USING fts5(body, title, tokenize = 'unicode61 remove_diacritics 2')",
+    "CREATE TRIGGER IF NOT EXISTS clips_fts_insert AFTER INSERT ON clips BEGIN
+        INSERT INTO clips_fts (rowid, body, title) VALUES (new.rowid, new.search_content, new.window_title);
+    END",
+    "CREATE TRIGGER IF NOT EXISTS clips_fts_delete AFTER DELETE ON clips BEGIN
+        DELETE FROM clips_fts WHERE rowid = old.rowid;
+    END",
+    "CREATE TRIGGER IF NOT EXISTS clips_fts_update AFTER UPDATE OF search_content, window_title ON clips BEGIN
+        UPDATE clips_fts SET body = new.search_content, title = new.window_title WHERE rowid = new.rowid;
+    END",
+];
+
+/// Creates the index and triggers, and rebuilds the index when it has drifted
+/// from `clips` (first run after upgrading, or a database edited by hand).
+/// Must run after the `window_title` and `search_content` migrations.
+pub async fn init_fts(pool: &Pool<Sqlite>) -> Result<(), String> {
+    for statement in SCHEMA {
+        sqlx::query(statement).execute(pool).await.map_err(|e| e.to_string())?;
+    }
+
+    let (clips, indexed): (i64, i64) = sqlx::query_as("SELECT (SELECT COUNT(*) FROM clips), (SELECT COUNT(*) FROM clips_fts)")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if clips != indexed {
+        rebuild_fts(pool).await?;
+    }
+    Ok(())
+}
+
+pub async fn rebuild_fts(pool: &Pool<Sqlite>) -> Result<(), String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM clips_fts").execute(&mut *tx).await.map_err(|e| e.to_string())?;
+    sqlx::query("INSERT INTO clips_fts (rowid, body, title) SELECT rowid, search_content, window_title FROM clips")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+/// Turns what the user typed into an FTS5 query: every word must match, each
+/// as a prefix ("clip man" finds "clipboard manager"). Words are quoted so
+/// FTS5 operators and punctuation in the input can't produce syntax errors.
+/// None when the input has nothing the tokenizer would index.
+pub fn fts_query(search: &str) -> Option<String> {
+    let terms: Vec<String> = normalize_text(search)
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .map(|word| format!("\"{}\"*", word.replace('"', "")))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+const EXCERPT_CONTEXT_LINES: usize = 1;
+const EXCERPT_LINE_CHARS: usize = 160;
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty())
+}
+
+/// Mirrors the prefix matching of `fts_query` on a single piece of text,
+/// falling back to a substring check for punctuation-only searches.
+struct Matcher {
+    query: String,
+    terms: Vec<String>,
+}
+
+impl Matcher {
+    fn new(search: &str) -> Matcher {
+        let query = normalize_text(search).trim().to_string();
+        let terms = words(&query).map(str::to_string).collect();
+        Matcher { query, terms }
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        let text = normalize_text(text);
+        if self.terms.is_empty() {
+            !self.query.is_empty() && text.contains(&self.query)
+        } else {
+            words(&text).any(|word| self.terms.iter().any(|term| word.starts_with(term.as_str())))
+        }
+    }
+
+    /// Char offset of the first term in `line`, when it can be found without
+    /// normalizing (diacritics can make it miss; callers then use the start).
+    fn position_in(&self, line: &str) -> Option<usize> {
+        let lower = line.to_lowercase();
+        let needle = self.terms.first().unwrap_or(&self.query);
+        lower.find(needle.as_str()).map(|byte| lower[..byte].chars().count())
+    }
+}
+
+/// Which fields of a search result the search matched: "content", "ocr" (the
+/// extracted text stored as an image clip's content) or "title".
+pub fn matched_in(clip: &Clip, search: &str) -> Vec<String> {
+    let matcher = Matcher::new(search);
+    let mut fields = Vec::new();
+    if matcher.matches(&clip.content) {
+        fields.push(if clip.clip_type == "image" { "ocr" } else { "content" }.to_string());
+    }
+    if clip.window_title.as_deref().is_some_and(|title| matcher.matches(title)) {
+        fields.push("title".to_string());
+    }
+    fields
+}
+
+fn shorten(line: &str, around: usize) -> String {
+    let length = line.chars().count();
+    if length <= EXCERPT_LINE_CHARS {
+        return line.to_string();
+    }
+    let start = around.saturating_sub(EXCERPT_LINE_CHARS / 3).min(length - EXCERPT_LINE_CHARS);
+    let mut shortened: String = line.chars().skip(start).take(EXCERPT_LINE_CHARS).collect();
+    if start > 0 {
+        shortened.insert(0, '…');
+    }
+    if start + EXCERPT_LINE_CHARS < length {
+        shortened.push('…');
+    }
+    shortened
+}
+
+/// The first matching line of `content` with a line of context on each side,
+/// like `grep -C1`; long lines are cut down around the match. None when the
+/// match wasn't in the content (e.g. only the window title matched).
+pub fn excerpt(content: &str, search: &str) -> Option<String> {
+    let matcher = Matcher::new(search);
+    let lines: Vec<&str> = content.lines().collect();
+    let hit = lines.iter().position(|line| matcher.matches(line))?;
+    let start = hit.saturating_sub(EXCERPT_CONTEXT_LINES);
+    let end = (hit + EXCERPT_CONTEXT_LINES + 1).min(lines.len());
+
+    let mut excerpt = Vec::new();
+    if start > 0 {
+        excerpt.push("…".to_string());
+    }
+    for (i, line) in lines[start..end].iter().enumerate() {
+        let around = if start + i == hit { matcher.position_in(line).unwrap_or(0) } else { 0 };
+        excerpt.push(shorten(line, around));
+    }
+    if end < lines.len() {
+        excerpt.push("…".to_string());
+    }
+    Some(excerpt.join("\n"))
+}
@@ -0,0 +1,220 @@
+// Sessions: clips captured in a burst from the same app, e.g. a round of
+// copying quotes out of a browser. A clip joins the latest session when it
+// comes from the same app within `session_gap_minutes` (default 5) of that
+// session's last clip; otherwise it starts a new one. The app is only known
+// when window titles or context are captured; without it, time alone decides.
+
+use sqlx::{Pool, Sqlite};
+use arboard::Clipboard;
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+use crate::classify::{is_text_type, looks_like_code};
+use crate::{crypto, restore, setting_value, Clip, DbState, CLIP_COLUMNS};
+
+const DEFAULT_GAP_MINUTES: i64 = 5;
+const DEFAULT_PAGE_SIZE: i64 = 30;
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct SessionSummary {
+    id: String,
+    app: Option<String>,
+    started_at: String, // ISO 8601, first clip
+    ended_at: String,   // ISO 8601, last clip
+    clip_count: i64,
+}
+
+async fn session_gap(pool: &Pool<Sqlite>) -> chrono::Duration {
+    let minutes = setting_value(pool, "session_gap_minutes")
+        .await
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|m| *m > 0)
+        .unwrap_or(DEFAULT_GAP_MINUTES);
+    chrono::Duration::minutes(minutes)
+}
+
+fn continues(app: Option<&str>, previous_app: Option<&str>, at: DateTime<Utc>, previous_at: &str, gap: chrono::Duration) -> bool {
+    let Ok(previous_at) = DateTime::parse_from_rfc3339(previous_at) else { return false };
+    app == previous_app && at.signed_duration_since(previous_at) <= gap
+}
+
+/// Returns the session a clip captured now from `app` belongs to, extending
+/// the latest session or creating a new one.
+pub async fn assign(pool: &Pool<Sqlite>, app: Option<&str>, at: DateTime<Utc>) -> Result<String, String> {
+    let latest: Option<(String, Option<String>, String)> = sqlx::query_as("SELECT id, app, ended_at FROM sessions ORDER BY ended_at DESC LIMIT 1")
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let at_text = at.to_rfc3339();
+
+    if let Some((id, previous_app, ended_at)) = latest {
+        if continues(app, previous_app.as_deref(), at, &ended_at, session_gap(pool).await) {
+            sqlx::query("UPDATE sessions SET ended_at = ? WHERE id = ?")
+                .bind(&at_text)
+                .bind(&id)
+                .execute(pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            return Ok(id);
+        }
+    }
+
+    let id = Uuid::new_v4().to_string();
+    sqlx::query("INSERT INTO sessions (id, app, started_at, ended_at) VALUES (?, ?, ?, ?)")
+        .bind(&id)
+        .bind(app)
+        .bind(&at_text)
+        .bind(&at_text)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+/// Groups clips that have no session yet (stored before sessions existed),
+/// using the app recorded in their context when there is one, and drops
+/// sessions whose clips are all gone.
+pub async fn backfill_sessions(pool: &Pool<Sqlite>) -> Result<(), String> {
+    let rows: Vec<(String, String, Option<String>)> = sqlx::query_as(
+        "SELECT id, created_at, json_extract(context, '$.app') FROM clips WHERE session_id IS NULL ORDER BY created_at ASC, id ASC"
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let gap = session_gap(pool).await;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let mut current: Option<(String, Option<String>, String)> = None; // (id, app, ended_at)
+
+    for (clip_id, created_at, app) in rows {
+        let Ok(at) = DateTime::parse_from_rfc3339(&created_at).map(|d| d.with_timezone(&Utc)) else { continue };
+        let joins = current
+            .as_ref()
+            .is_some_and(|(_, previous_app, ended_at)| continues(app.as_deref(), previous_app.as_deref(), at, ended_at, gap));
+
+        if joins {
+            let (id, _, ended_at) = current.as_mut().unwrap();
+            *ended_at = created_at.clone();
+            sqlx::query("UPDATE sessions SET ended_at = ? WHERE id = ?")
+                .bind(&created_at)
+                .bind(&*id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        } else {
+            let id = Uuid::new_v4().to_string();
+            sqlx::query("INSERT INTO sessions (id, app, started_at, ended_at) VALUES (?, ?, ?, ?)")
+                .bind(&id)
+                .bind(&app)
+                .bind(&created_at)
+                .bind(&created_at)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+            current = Some((id, app, created_at));
+        }
+
+        sqlx::query("UPDATE clips SET session_id = ? WHERE id = ?")
+            .bind(current.as_ref().map(|(id, _, _)| id.as_str()))
+            .bind(&clip_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    sqlx::query("DELETE FROM sessions WHERE id NOT IN (SELECT session_id FROM clips WHERE session_id IS NOT NULL)")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+async fn session_clips(pool: &Pool<Sqlite>, id: &str) -> Result<Vec<Clip>, String> {
+    sqlx::query_as::<_, Clip>(&format!("SELECT {} FROM clips WHERE session_id = ? AND deleted_at IS NULL ORDER BY created_at ASC, id ASC", CLIP_COLUMNS))
+        .bind(id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Text of a session's clips in capture order, separated by `separator`.
+fn joined_text(clips: &[Clip], separator: &str) -> String {
+    clips
+        .iter()
+        .filter(|c| is_text_type(&c.clip_type) && !c.content.is_empty())
+        .map(|c| c.content.as_str())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Sessions newest first, paged by the `ended_at` of the last one the client has.
+#[tauri::command]
+pub async fn get_sessions(state: tauri::State<'_, DbState>, cursor_ended_at: Option<String>, page_size: Option<i64>) -> Result<Vec<SessionSummary>, String> {
+    let limit = page_size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, 200);
+    sqlx::query_as::<_, SessionSummary>(
+        "SELECT s.id, s.app, MIN(c.created_at) AS started_at, MAX(c.created_at) AS ended_at, COUNT(*) AS clip_count
+         FROM clips c JOIN sessions s ON s.id = c.session_id
+         WHERE c.deleted_at IS NULL
+         GROUP BY s.id
+         HAVING ?1 IS NULL OR MAX(c.created_at) < ?1
+         ORDER BY ended_at DESC
+         LIMIT ?2"
+    )
+    .bind(cursor_ended_at.filter(|c| !c.is_empty()))
+    .bind(limit)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// The clips of one session, oldest first, for expanding a group.
+#[tauri::command]
+pub async fn get_session_clips(state: tauri::State<'_, DbState>, id: String) -> Result<Vec<Clip>, String> {
+    session_clips(&state.pool, &id).await
+}
+
+/// Copies all text clips of a session as one block. Returns how many were joined.
+#[tauri::command]
+pub async fn copy_session(state: tauri::State<'_, DbState>, id: String, separator: Option<String>) -> Result<usize, String> {
+    let clips = session_clips(&state.pool, &id).await?;
+    let text = joined_text(&clips, separator.as_deref().unwrap_or("\n\n"));
+    if text.is_empty() {
+        return Ok(0);
+    }
+
+    let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
+    restore::remember_current(&mut clipboard);
+    clipboard.set_text(text).map_err(|e| e.to_string())?;
+    Ok(clips.iter().filter(|c| is_text_type(&c.clip_type)).count())
+}
+
+/// Writes a session as Markdown, code clips fenced, encrypted when `passphrase`
+/// is given. Returns the clip count.
+#[tauri::command]
+pub async fn export_session(state: tauri::State<'_, DbState>, id: String, path: String, passphrase: Option<String>) -> Result<usize, String> {
+    let clips = session_clips(&state.pool, &id).await?;
+    let app: Option<(Option<String>,)> = sqlx::query_as("SELECT app FROM sessions WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let local = |at: &str| {
+        DateTime::parse_from_rfc3339(at)
+            .map(|d| d.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|_| at.to_string())
+    };
+
+    let mut markdown = format!("# Session: {}\n\n", app.and_then(|(a,)| a).unwrap_or_else(|| "Unknown app".to_string()));
+    for clip in &clips {
+        markdown.push_str(&format!("## {}\n\n", local(&clip.created_at)));
+        match (clip.clip_type.as_str(), &clip.image_path) {
+            ("image", Some(path)) => markdown.push_str(&format!("![image]({})\n\n", path)),
+            _ if looks_like_code(&clip.content) => markdown.push_str(&format!("```\n{}\n```\n\n", clip.content.trim_end())),
+            _ => markdown.push_str(&format!("{}\n\n", clip.content.trim_end())),
+        }
+    }
+
+    crypto::write_export(&path, markdown.as_bytes(), passphrase.as_deref())?;
+    Ok(clips.len())
+}
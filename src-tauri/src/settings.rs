@@ -0,0 +1,264 @@
+// Typed view over the key/value `settings` table for the preferences screen.
+// Each field is stored under its own name, so modules that read a single key
+// with `setting_value`/`setting_enabled` keep working unchanged; this module
+// owns the defaults and validation for the keys it exposes.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::AppHandle;
+use sqlx::{Pool, Sqlite};
+use serde::{Serialize, Deserialize};
+
+use crate::capture::DedupWindow;
+use crate::sounds::{self, Sound};
+use crate::{journal, line_endings, link_preview, power, rate_limit, trash};
+use crate::retention::{DEFAULT_RETENTION_DAYS, MAX_RETENTION_DAYS};
+use crate::scheduler::{DEFAULT_RETENTION_INTERVAL_HOURS, MAX_RETENTION_INTERVAL_HOURS};
+use crate::{setting_enabled, setting_value, DbState, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+
+pub const DEFAULT_POLL_INTERVAL_MS: u64 = 1000;
+const POLL_INTERVAL_RANGE: std::ops::RangeInclusive<u64> = 100..=10_000;
+
+// Read on every tick of the polling watcher, the fallback when no native
+// clipboard notifications are available
+static POLL_INTERVAL_MS: AtomicU64 = AtomicU64::new(DEFAULT_POLL_INTERVAL_MS);
+
+/// Monitor poll interval, stretched while in low-power mode.
+pub fn poll_interval() -> std::time::Duration {
+    let interval = std::time::Duration::from_millis(POLL_INTERVAL_MS.load(Ordering::Relaxed));
+    if power::is_low_power() {
+        interval * power::POLL_FACTOR
+    } else {
+        interval
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Settings {
+    pub retention_days: i64,
+    pub max_history: i64, // non-favorite clips kept; 0 = no limit
+    pub trash_days: i64, // days a deleted clip stays in the trash
+    pub retention_interval_hours: i64,
+    pub poll_interval_ms: u64,
+    pub page_size: i64,
+    pub dedup_window: String, // "30m", "12h", "7d" or "forever"
+    pub capture_window_title: bool,
+    pub capture_context: bool,
+    pub capture_source_app: bool, // only with window title or context capture on
+    pub capture_rich_text: bool, // HTML/RTF flavors
+    pub strip_ansi: bool, // terminal escape codes
+    pub normalize_line_endings: String, // "off", "lf" or "crlf"
+    pub keep_ansi_original: bool,
+    pub fetch_link_previews: bool, // page title and favicon for URL clips
+    pub ocr_images: bool,
+    pub capture_rate_limit: u32, // captures per minute from one app; 0 = no limit
+    pub capture_notifications: bool,
+    pub sound_on_capture: bool,
+    pub sound_on_copy: bool,
+    pub sound_volume: u32, // 0-100
+    pub ignore_own_window: bool,
+    pub startup_catch_up: bool,
+    pub skip_concealed: bool, // password-manager copies
+    pub confirm_purges: bool,
+    pub low_power_mode: String, // "auto", "on" or "off"
+    pub daily_journal: bool,
+    pub journal_folder: String, // absolute path; empty until chosen
+}
+
+/// Partial update: only the fields present are written.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SettingsPatch {
+    pub retention_days: Option<i64>,
+    pub max_history: Option<i64>,
+    pub trash_days: Option<i64>,
+    pub retention_interval_hours: Option<i64>,
+    pub poll_interval_ms: Option<u64>,
+    pub page_size: Option<i64>,
+    pub dedup_window: Option<String>,
+    pub capture_window_title: Option<bool>,
+    pub capture_context: Option<bool>,
+    pub capture_source_app: Option<bool>,
+    pub capture_rich_text: Option<bool>,
+    pub strip_ansi: Option<bool>,
+    pub normalize_line_endings: Option<String>,
+    pub keep_ansi_original: Option<bool>,
+    pub fetch_link_previews: Option<bool>,
+    pub ocr_images: Option<bool>,
+    pub capture_rate_limit: Option<u32>,
+    pub capture_notifications: Option<bool>,
+    pub sound_on_capture: Option<bool>,
+    pub sound_on_copy: Option<bool>,
+    pub sound_volume: Option<u32>,
+    pub ignore_own_window: Option<bool>,
+    pub startup_catch_up: Option<bool>,
+    pub skip_concealed: Option<bool>,
+    pub confirm_purges: Option<bool>,
+    pub low_power_mode: Option<String>,
+    pub daily_journal: Option<bool>,
+    pub journal_folder: Option<String>,
+}
+
+async fn number<T: std::str::FromStr>(pool: &Pool<Sqlite>, key: &str, default: T) -> T {
+    setting_value(pool, key).await.and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+pub async fn load(pool: &Pool<Sqlite>) -> Settings {
+    Settings {
+        retention_days: number(pool, "retention_days", DEFAULT_RETENTION_DAYS).await,
+        max_history: number(pool, "max_history", 0).await,
+        trash_days: trash::trash_days(pool).await,
+        retention_interval_hours: number(pool, "retention_interval_hours", DEFAULT_RETENTION_INTERVAL_HOURS).await,
+        poll_interval_ms: number(pool, "poll_interval_ms", DEFAULT_POLL_INTERVAL_MS).await,
+        page_size: page_size(pool).await,
+        dedup_window: setting_value(pool, "dedup_window")
+            .await
+            .filter(|v| DedupWindow::parse(v).is_some())
+            .unwrap_or_else(|| "1d".to_string()),
+        capture_window_title: setting_enabled(pool, "capture_window_title", false).await,
+        capture_context: setting_enabled(pool, "capture_context", false).await,
+        capture_source_app: setting_enabled(pool, "capture_source_app", true).await,
+        capture_rich_text: setting_enabled(pool, "capture_rich_text", true).await,
+        strip_ansi: setting_enabled(pool, "strip_ansi", true).await,
+        normalize_line_endings: line_endings::mode(pool).await,
+        keep_ansi_original: setting_enabled(pool, "keep_ansi_original", false).await,
+        fetch_link_previews: link_preview::enabled(pool).await,
+        ocr_images: setting_enabled(pool, "ocr_images", true).await,
+        capture_rate_limit: rate_limit::rate_limit(pool).await,
+        capture_notifications: setting_enabled(pool, "capture_notifications", false).await,
+        sound_on_capture: sounds::enabled(pool, Sound::Capture).await,
+        sound_on_copy: sounds::enabled(pool, Sound::Copy).await,
+        sound_volume: sounds::volume(pool).await,
+        ignore_own_window: setting_enabled(pool, "ignore_own_window", true).await,
+        startup_catch_up: setting_enabled(pool, "startup_catch_up", true).await,
+        skip_concealed: setting_enabled(pool, "skip_concealed", true).await,
+        confirm_purges: setting_enabled(pool, "confirm_purges", false).await,
+        low_power_mode: power::mode(pool).await,
+        daily_journal: journal::enabled(pool).await,
+        journal_folder: journal::folder(pool).await.map(|f| f.to_string_lossy().to_string()).unwrap_or_default(),
+    }
+}
+
+/// Default number of clips per `get_clips` page.
+pub async fn page_size(pool: &Pool<Sqlite>) -> i64 {
+    number(pool, "page_size", DEFAULT_PAGE_SIZE).await.clamp(1, MAX_PAGE_SIZE)
+}
+
+/// Loads the values that are cached in memory at startup.
+pub async fn restore(pool: &Pool<Sqlite>) {
+    let interval = number(pool, "poll_interval_ms", DEFAULT_POLL_INTERVAL_MS).await;
+    POLL_INTERVAL_MS.store(interval.clamp(*POLL_INTERVAL_RANGE.start(), *POLL_INTERVAL_RANGE.end()), Ordering::Relaxed);
+}
+
+fn validate(patch: &SettingsPatch) -> Result<(), String> {
+    if patch.retention_days.is_some_and(|d| !(1..=MAX_RETENTION_DAYS).contains(&d)) {
+        return Err(format!("retention_days must be between 1 and {}", MAX_RETENTION_DAYS));
+    }
+    if patch.max_history.is_some_and(|n| n < 0) {
+        return Err("max_history can't be negative".to_string());
+    }
+    if patch.trash_days.is_some_and(|d| !(1..=trash::MAX_TRASH_DAYS).contains(&d)) {
+        return Err(format!("trash_days must be between 1 and {}", trash::MAX_TRASH_DAYS));
+    }
+    if patch.retention_interval_hours.is_some_and(|h| !(1..=MAX_RETENTION_INTERVAL_HOURS).contains(&h)) {
+        return Err(format!("retention_interval_hours must be between 1 and {}", MAX_RETENTION_INTERVAL_HOURS));
+    }
+    if patch.poll_interval_ms.is_some_and(|ms| !POLL_INTERVAL_RANGE.contains(&ms)) {
+        return Err(format!("poll_interval_ms must be between {} and {}", POLL_INTERVAL_RANGE.start(), POLL_INTERVAL_RANGE.end()));
+    }
+    if patch.page_size.is_some_and(|n| !(1..=MAX_PAGE_SIZE).contains(&n)) {
+        return Err(format!("page_size must be between 1 and {}", MAX_PAGE_SIZE));
+    }
+    if patch.sound_volume.is_some_and(|v| v > 100) {
+        return Err("sound_volume must be between 0 and 100".to_string());
+    }
+    if let Some(window) = &patch.dedup_window {
+        DedupWindow::parse(window).ok_or_else(|| format!("Invalid dedup window: {}", window))?;
+    }
+    if let Some(mode) = &patch.normalize_line_endings {
+        if !line_endings::MODES.contains(&mode.trim().to_lowercase().as_str()) {
+            return Err(format!("Invalid line ending mode: {}", mode));
+        }
+    }
+    if let Some(mode) = &patch.low_power_mode {
+        if !power::MODES.contains(&mode.trim().to_lowercase().as_str()) {
+            return Err(format!("Invalid low-power mode: {}", mode));
+        }
+    }
+    if let Some(folder) = patch.journal_folder.as_deref().map(str::trim).filter(|f| !f.is_empty()) {
+        if !std::path::Path::new(folder).is_absolute() {
+            return Err("journal_folder must be an absolute path".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Checks one stored `key`/`value` pair the way `update_settings` would.
+/// Keys this module doesn't own are accepted as they are.
+pub fn validate_value(key: &str, value: &str) -> Result<(), String> {
+    let typed = serde_json::from_str(value).unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+    let patch: SettingsPatch = serde_json::from_value(serde_json::json!({ key: typed }))
+        .map_err(|_| format!("Invalid value for {}: {}", key, value))?;
+    validate(&patch)
+}
+
+#[tauri::command]
+pub async fn get_settings(state: tauri::State<'_, DbState>) -> Result<Settings, String> {
+    Ok(load(&state.pool).await)
+}
+
+/// Validates and saves the given fields in one transaction, then returns the
+/// full settings as stored.
+#[tauri::command]
+pub async fn update_settings(app_handle: AppHandle, state: tauri::State<'_, DbState>, patch: SettingsPatch) -> Result<Settings, String> {
+    validate(&patch)?;
+
+    let values: Vec<(&str, Option<String>)> = vec![
+        ("retention_days", patch.retention_days.map(|v| v.to_string())),
+        ("max_history", patch.max_history.map(|v| v.to_string())),
+        ("trash_days", patch.trash_days.map(|v| v.to_string())),
+        ("retention_interval_hours", patch.retention_interval_hours.map(|v| v.to_string())),
+        ("poll_interval_ms", patch.poll_interval_ms.map(|v| v.to_string())),
+        ("page_size", patch.page_size.map(|v| v.to_string())),
+        ("dedup_window", patch.dedup_window.map(|v| v.trim().to_lowercase())),
+        ("capture_window_title", patch.capture_window_title.map(|v| v.to_string())),
+        ("capture_context", patch.capture_context.map(|v| v.to_string())),
+        ("capture_source_app", patch.capture_source_app.map(|v| v.to_string())),
+        ("capture_rich_text", patch.capture_rich_text.map(|v| v.to_string())),
+        ("strip_ansi", patch.strip_ansi.map(|v| v.to_string())),
+        ("normalize_line_endings", patch.normalize_line_endings.as_ref().map(|v| v.trim().to_lowercase())),
+        ("keep_ansi_original", patch.keep_ansi_original.map(|v| v.to_string())),
+        ("fetch_link_previews", patch.fetch_link_previews.map(|v| v.to_string())),
+        ("ocr_images", patch.ocr_images.map(|v| v.to_string())),
+        ("capture_rate_limit", patch.capture_rate_limit.map(|v| v.to_string())),
+        ("capture_notifications", patch.capture_notifications.map(|v| v.to_string())),
+        ("sound_on_capture", patch.sound_on_capture.map(|v| v.to_string())),
+        ("sound_on_copy", patch.sound_on_copy.map(|v| v.to_string())),
+        ("sound_volume", patch.sound_volume.map(|v| v.to_string())),
+        ("ignore_own_window", patch.ignore_own_window.map(|v| v.to_string())),
+        ("startup_catch_up", patch.startup_catch_up.map(|v| v.to_string())),
+        ("skip_concealed", patch.skip_concealed.map(|v| v.to_string())),
+        ("confirm_purges", patch.confirm_purges.map(|v| v.to_string())),
+        ("low_power_mode", patch.low_power_mode.as_ref().map(|v| v.trim().to_lowercase())),
+        ("daily_journal", patch.daily_journal.map(|v| v.to_string())),
+        ("journal_folder", patch.journal_folder.as_ref().map(|v| v.trim().to_string())),
+    ];
+
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    for (key, value) in values {
+        let Some(value) = value else { continue };
+        sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
+            .bind(key)
+            .bind(value)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    if let Some(interval) = patch.poll_interval_ms {
+        POLL_INTERVAL_MS.store(interval, Ordering::Relaxed);
+    }
+    if patch.low_power_mode.is_some() {
+        power::refresh(&app_handle, &state.pool).await;
+    }
+    Ok(load(&state.pool).await)
+}
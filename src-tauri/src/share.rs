@@ -0,0 +1,135 @@
+// Hands a clip to the native share sheet (the sharing picker on macOS, the
+// share UI on Windows). Text and links are shared as-is, image clips as their
+// PNG. Other platforms have no share sheet and get an error.
+
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, WebviewWindow};
+
+use crate::{classify, usage, DbState};
+
+#[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+enum ShareItem {
+    Text(String),
+    Url(String),
+    File(PathBuf),
+}
+
+#[cfg(target_os = "macos")]
+fn show_share_sheet(window: &WebviewWindow, item: ShareItem) -> Result<(), String> {
+    let ns_window = window.ns_window().map_err(|e| e.to_string())? as usize;
+    window
+        .run_on_main_thread(move || {
+            use objc2::rc::Retained;
+            use objc2::runtime::AnyObject;
+            use objc2::MainThreadMarker;
+            use objc2_app_kit::{NSSharingServicePicker, NSWindow};
+            use objc2_foundation::{NSArray, NSPoint, NSRect, NSRectEdge, NSSize, NSString, NSURL};
+
+            let Some(mtm) = MainThreadMarker::new() else { return };
+            let object: Retained<AnyObject> = match item {
+                ShareItem::Text(text) => Retained::into_super(Retained::into_super(NSString::from_str(&text))),
+                ShareItem::Url(url) => match NSURL::URLWithString(&NSString::from_str(&url)) {
+                    Some(url) => Retained::into_super(Retained::into_super(url)),
+                    None => Retained::into_super(Retained::into_super(NSString::from_str(&url))),
+                },
+                ShareItem::File(path) => {
+                    let url = NSURL::fileURLWithPath(&NSString::from_str(&path.to_string_lossy()));
+                    Retained::into_super(Retained::into_super(url))
+                }
+            };
+
+            let ns_window = unsafe { &*(ns_window as *const NSWindow) };
+            let Some(view) = ns_window.contentView() else { return };
+            let items = NSArray::from_retained_slice(&[object]);
+            let picker = unsafe { NSSharingServicePicker::initWithItems(mtm.alloc(), &items) };
+
+            // Anchor at the top center of the window, where the search bar is
+            let bounds = view.bounds();
+            let anchor = NSRect::new(NSPoint::new(bounds.size.width / 2.0, bounds.size.height - 1.0), NSSize::new(1.0, 1.0));
+            picker.showRelativeToRect_ofView_preferredEdge(anchor, &view, NSRectEdge::MinY);
+        })
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn show_share_sheet(window: &WebviewWindow, item: ShareItem) -> Result<(), String> {
+    use std::sync::Mutex;
+    use windows::core::{factory, Ref, HSTRING};
+    use windows::ApplicationModel::DataTransfer::{DataRequestedEventArgs, DataTransferManager};
+    use windows::Foundation::{TypedEventHandler, Uri};
+    use windows::Storage::StorageFile;
+    use windows::Storage::Streams::RandomAccessStreamReference;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Shell::IDataTransferManagerInterop;
+
+    // The window keeps one DataRequested handler: the one for the latest share
+    static HANDLER_TOKEN: Mutex<Option<i64>> = Mutex::new(None);
+
+    let hwnd = window.hwnd().map_err(|e| e.to_string())?.0 as isize;
+    window
+        .run_on_main_thread(move || {
+            let result = (|| -> windows::core::Result<()> {
+                let hwnd = HWND(hwnd as *mut _);
+                let interop = factory::<DataTransferManager, IDataTransferManagerInterop>()?;
+                let manager: DataTransferManager = unsafe { interop.GetForWindow(hwnd)? };
+
+                // Load the image before the share UI asks for it; the handler must not block
+                let bitmap = match &item {
+                    ShareItem::File(path) => {
+                        let file = StorageFile::GetFileFromPathAsync(&HSTRING::from(path.as_os_str()))?.get()?;
+                        Some(RandomAccessStreamReference::CreateFromFile(&file)?)
+                    }
+                    _ => None,
+                };
+
+                let mut token = HANDLER_TOKEN.lock().unwrap();
+                if let Some(previous) = token.take() {
+                    let _ = manager.RemoveDataRequested(previous);
+                }
+                *token = Some(manager.DataRequested(&TypedEventHandler::new(
+                    move |_, args: Ref<DataRequestedEventArgs>| {
+                        let Some(args) = args.as_ref() else { return Ok(()) };
+                        let data = args.Request()?.Data()?;
+                        data.Properties()?.SetTitle(&HSTRING::from("Klip"))?;
+                        match (&item, &bitmap) {
+                            (ShareItem::Text(text), _) => data.SetText(&HSTRING::from(text.as_str())),
+                            (ShareItem::Url(url), _) => data.SetWebLink(&Uri::CreateUri(&HSTRING::from(url.as_str()))?),
+                            (ShareItem::File(_), Some(bitmap)) => data.SetBitmap(bitmap),
+                            (ShareItem::File(_), None) => Ok(()),
+                        }
+                    },
+                ))?);
+
+                unsafe { interop.ShowShareUIForWindow(hwnd) }
+            })();
+            if let Err(e) = result {
+                eprintln!("Failed to open share UI: {}", e);
+            }
+        })
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn show_share_sheet(_window: &WebviewWindow, _item: ShareItem) -> Result<(), String> {
+    Err("Sharing is not supported on this platform".to_string())
+}
+
+#[tauri::command]
+pub async fn share_clip(app_handle: AppHandle, state: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
+    let clip: Option<(String, String, Option<String>)> = sqlx::query_as("SELECT content, clip_type, image_path FROM clips WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (content, clip_type, image_path) = clip.ok_or_else(|| "Clip not found".to_string())?;
+
+    let item = match (clip_type.as_str(), image_path) {
+        ("image", Some(path)) => ShareItem::File(PathBuf::from(path)),
+        _ if classify::is_url(&content) => ShareItem::Url(content.trim().to_string()),
+        _ => ShareItem::Text(content),
+    };
+
+    let window = app_handle.get_webview_window("main").ok_or_else(|| "Main window is not available".to_string())?;
+    show_share_sheet(&window, item)?;
+    usage::record_usage(&state.pool, &id, "share").await
+}
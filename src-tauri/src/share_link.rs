@@ -0,0 +1,127 @@
+// Read-only share links for single text clips. The clip is encrypted here
+// with a fresh AES-256-GCM key, only the ciphertext is uploaded to the paste
+// endpoint in the `share_link_endpoint` setting, and the key travels in the
+// URL fragment, which browsers never send to the server. Opt-in: nothing is
+// uploaded until an endpoint is configured.
+//
+// Endpoint protocol: `POST <endpoint>?ttl=<seconds>` with the blob as an
+// octet-stream body (nonce (12) | ciphertext of the JSON payload); the server
+// answers with the paste URL, as plain text or `{"url": ...}`. An optional
+// bearer token is read from the keychain.
+
+use aes_gcm::aead::{rand_core::RngCore, Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::classify::is_text_type;
+use crate::{keychain, setting_value, usage, DbState};
+
+pub const TOKEN_SECRET: &str = "share_link_token"; // keychain entry
+const NONCE_LEN: usize = 12;
+const MAX_SHARED_BYTES: usize = 1024 * 1024;
+const DEFAULT_TTL_SECS: u64 = 7 * 24 * 3600;
+const MAX_TTL_SECS: u64 = 30 * 24 * 3600;
+const UPLOAD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct SharedClip {
+    content: String,
+    clip_type: String,
+    created_at: String, // ISO 8601
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ShareLink {
+    url: String, // includes the key fragment
+    expires_at: String, // ISO 8601
+}
+
+#[derive(Deserialize)]
+struct PasteResponse {
+    url: String,
+}
+
+fn encrypt(plaintext: &[u8]) -> Result<(Vec<u8>, Key<Aes256Gcm>), String> {
+    let key = Aes256Gcm::generate_key(OsRng);
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = Aes256Gcm::new(&key)
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|e| e.to_string())?;
+
+    let mut blob = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    blob.extend_from_slice(&nonce);
+    blob.extend_from_slice(&ciphertext);
+    Ok((blob, key))
+}
+
+/// The paste URL from the server's answer: a JSON object or the bare URL.
+fn paste_url(body: &str) -> Result<reqwest::Url, String> {
+    let url = match serde_json::from_str::<PasteResponse>(body) {
+        Ok(response) => response.url,
+        Err(_) => body.trim().to_string(),
+    };
+    reqwest::Url::parse(&url).map_err(|_| "The share endpoint didn't return a URL".to_string())
+}
+
+/// Uploads an encrypted copy of a text clip and returns a link that opens for
+/// `ttl_secs` seconds (a week by default, at most 30 days).
+#[tauri::command]
+pub async fn share_clip_link(state: tauri::State<'_, DbState>, id: String, ttl_secs: Option<u64>) -> Result<ShareLink, String> {
+    let endpoint = setting_value(&state.pool, "share_link_endpoint")
+        .await
+        .filter(|e| !e.trim().is_empty())
+        .ok_or_else(|| "Set a share endpoint (share_link_endpoint) before creating links".to_string())?;
+    let mut endpoint = reqwest::Url::parse(endpoint.trim()).map_err(|e| format!("Invalid share endpoint: {}", e))?;
+    if endpoint.scheme() != "https" {
+        return Err("The share endpoint must use https".to_string());
+    }
+
+    let clip: Option<(String, String, String, bool)> = sqlx::query_as("SELECT content, clip_type, created_at, is_sensitive FROM clips WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (content, clip_type, created_at, is_sensitive) = clip.ok_or_else(|| "Clip not found".to_string())?;
+    if !is_text_type(&clip_type) {
+        return Err("Only text clips can be shared as a link".to_string());
+    }
+    if is_sensitive {
+        return Err("Sensitive clips can't be shared as a link".to_string());
+    }
+    if content.len() > MAX_SHARED_BYTES {
+        return Err(format!("Clips over {} KB can't be shared as a link", MAX_SHARED_BYTES / 1024));
+    }
+
+    let ttl = ttl_secs.unwrap_or(DEFAULT_TTL_SECS).clamp(60, MAX_TTL_SECS);
+    let payload = serde_json::to_vec(&SharedClip { content, clip_type, created_at }).map_err(|e| e.to_string())?;
+    let (blob, key) = encrypt(&payload)?;
+
+    endpoint.query_pairs_mut().append_pair("ttl", &ttl.to_string());
+    let mut request = reqwest::Client::builder()
+        .timeout(UPLOAD_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?
+        .post(endpoint)
+        .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+        .body(blob);
+    if let Some(token) = keychain::get_secret(TOKEN_SECRET)? {
+        request = request.bearer_auth(token);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .error_for_status()
+        .map_err(|e| e.to_string())?;
+    let mut url = paste_url(&response.text().await.map_err(|e| e.to_string())?)?;
+    url.set_fragment(Some(&base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(key)));
+
+    usage::record_usage(&state.pool, &id, "share_link").await?;
+    Ok(ShareLink {
+        url: url.to_string(),
+        expires_at: (Utc::now() + chrono::Duration::seconds(ttl as i64)).to_rfc3339(),
+    })
+}
@@ -0,0 +1,159 @@
+// Named, frozen copies of the database for demos and screenshots. A loaded
+// snapshot serves the history list (get_clips, counts, dates, facets) while
+// capture keeps writing to the live database; unloading switches back.
+// Snapshots live in `snapshots/<name>.db`, encrypted like the database.
+
+use tauri::{AppHandle, Emitter};
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::{Pool, Sqlite};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::encryption::{self, DB_ENCRYPTION_KEY};
+use crate::{app_data_dir, keychain, DbState};
+
+const MAX_NAME_LENGTH: usize = 64;
+
+// Name and read-only pool of the loaded snapshot
+static ACTIVE: Mutex<Option<(String, Pool<Sqlite>)>> = Mutex::new(None);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SnapshotInfo {
+    name: String,
+    created_at: String, // ISO 8601
+    bytes: u64,
+    active: bool,
+}
+
+fn snapshots_dir(app: &AppHandle) -> PathBuf {
+    app_data_dir(app).join("snapshots")
+}
+
+/// Snapshot names become file names: letters, digits, '-' and '_' only.
+fn snapshot_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LENGTH
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!("Snapshot names are 1-{} letters, digits, '-' or '_'", MAX_NAME_LENGTH));
+    }
+    Ok(snapshots_dir(app).join(format!("{}.db", name)))
+}
+
+fn active_name() -> Option<String> {
+    ACTIVE.lock().unwrap().as_ref().map(|(name, _)| name.clone())
+}
+
+/// Pool the history list reads from: the loaded snapshot, or the live database.
+pub fn read_pool(state: &DbState) -> Pool<Sqlite> {
+    match ACTIVE.lock().unwrap().as_ref() {
+        Some((_, pool)) => pool.clone(),
+        None => state.pool.clone(),
+    }
+}
+
+fn info(name: String, path: &std::path::Path, active: Option<&str>) -> Result<SnapshotInfo, String> {
+    let metadata = std::fs::metadata(path).map_err(|e| e.to_string())?;
+    let created_at: DateTime<Utc> = metadata.modified().map_err(|e| e.to_string())?.into();
+    Ok(SnapshotInfo { active: active == Some(name.as_str()), name, created_at: created_at.to_rfc3339(), bytes: metadata.len() })
+}
+
+fn notify_changed(app: &AppHandle) {
+    let _ = app.emit("snapshot-changed", active_name());
+    let _ = app.emit("clipboard-changed", ());
+}
+
+/// Copies the live database into a snapshot named `name`, replacing an
+/// existing one unless it's loaded.
+#[tauri::command]
+pub async fn create_snapshot(app_handle: AppHandle, state: tauri::State<'_, DbState>, name: String) -> Result<SnapshotInfo, String> {
+    let name = name.trim().to_string();
+    let path = snapshot_path(&app_handle, &name)?;
+    if active_name().as_deref() == Some(name.as_str()) {
+        return Err(format!("Snapshot {} is loaded; unload it before replacing it", name));
+    }
+    std::fs::create_dir_all(snapshots_dir(&app_handle)).map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_file(&path);
+
+    // VACUUM INTO writes a consistent copy without pausing capture
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.to_string_lossy().to_string())
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    info(name, &path, None)
+}
+
+/// Serves the history list from snapshot `name` until `unload_snapshot`.
+#[tauri::command]
+pub async fn load_snapshot(app_handle: AppHandle, name: String) -> Result<SnapshotInfo, String> {
+    let name = name.trim().to_string();
+    let path = snapshot_path(&app_handle, &name)?;
+    if !path.exists() {
+        return Err(format!("No snapshot named {}", name));
+    }
+
+    let key = if encryption::is_encrypted(&path) { keychain::get_secret(DB_ENCRYPTION_KEY)? } else { None };
+    let options = encryption::connect_options(&path, key.as_deref()).create_if_missing(false).read_only(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(2)
+        .connect_with(options)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some((_, previous)) = ACTIVE.lock().unwrap().replace((name.clone(), pool)) {
+        tauri::async_runtime::spawn(async move { previous.close().await });
+    }
+    notify_changed(&app_handle);
+    info(name.clone(), &path, Some(&name))
+}
+
+/// Switches the history list back to the live database.
+#[tauri::command]
+pub async fn unload_snapshot(app_handle: AppHandle) -> Result<(), String> {
+    let previous = ACTIVE.lock().unwrap().take();
+    if let Some((_, pool)) = previous {
+        pool.close().await;
+        notify_changed(&app_handle);
+    }
+    Ok(())
+}
+
+/// Name of the loaded snapshot, if any.
+#[tauri::command]
+pub fn get_active_snapshot() -> Option<String> {
+    active_name()
+}
+
+#[tauri::command]
+pub fn list_snapshots(app_handle: AppHandle) -> Result<Vec<SnapshotInfo>, String> {
+    let dir = snapshots_dir(&app_handle);
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.to_string()),
+    };
+    let active = active_name();
+    let mut snapshots = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("db") {
+            continue;
+        }
+        let Some(name) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else { continue };
+        snapshots.push(info(name, &path, active.as_deref())?);
+    }
+    snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(snapshots)
+}
+
+#[tauri::command]
+pub async fn delete_snapshot(app_handle: AppHandle, name: String) -> Result<(), String> {
+    let path = snapshot_path(&app_handle, name.trim())?;
+    if active_name().as_deref() == Some(name.trim()) {
+        unload_snapshot(app_handle.clone()).await?;
+    }
+    std::fs::remove_file(path).map_err(|e| e.to_string())
+}
@@ -0,0 +1,122 @@
+// Snippets: named, user-defined templates ("Meeting notes", "Bug report").
+// Bodies use the template placeholders (`{date}`, `{time}`, `{counter:...}`,
+// see templates.rs) plus `{clipboard}` for the current clipboard text and
+// `{cursor}` to mark where the caret belongs once the result is pasted.
+
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use arboard::Clipboard;
+use uuid::Uuid;
+
+use crate::{restore, templates, DbState};
+
+const CLIPBOARD: &str = "{clipboard}";
+const CURSOR: &str = "{cursor}";
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Snippet {
+    id: String,
+    name: String,
+    body: String,
+    created_at: String, // ISO 8601
+    updated_at: String, // ISO 8601
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExpandedSnippet {
+    text: String,
+    cursor: Option<usize>, // character offset of {cursor} in `text`
+}
+
+fn clean_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Snippet name can't be empty".to_string());
+    }
+    Ok(name)
+}
+
+/// Fills `{clipboard}` and `{cursor}` after the template placeholders, so
+/// braces in the clipboard text are never expanded. Only the first `{cursor}`
+/// counts; any others are dropped.
+fn fill(expanded: &str, clipboard: &str) -> ExpandedSnippet {
+    match expanded.split_once(CURSOR) {
+        Some((before, after)) => {
+            let before = before.replace(CLIPBOARD, clipboard);
+            let after = after.replace(CURSOR, "").replace(CLIPBOARD, clipboard);
+            ExpandedSnippet { cursor: Some(before.chars().count()), text: before + &after }
+        }
+        None => ExpandedSnippet { text: expanded.replace(CLIPBOARD, clipboard), cursor: None },
+    }
+}
+
+#[tauri::command]
+pub async fn list_snippets(state: tauri::State<'_, DbState>) -> Result<Vec<Snippet>, String> {
+    sqlx::query_as::<_, Snippet>("SELECT id, name, body, created_at, updated_at FROM snippets ORDER BY name COLLATE NOCASE")
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_snippet(state: tauri::State<'_, DbState>, name: String, body: String) -> Result<String, String> {
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    sqlx::query("INSERT INTO snippets (id, name, body, created_at, updated_at) VALUES (?, ?, ?, ?, ?)")
+        .bind(&id)
+        .bind(clean_name(&name)?)
+        .bind(&body)
+        .bind(&now)
+        .bind(&now)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+#[tauri::command]
+pub async fn update_snippet(state: tauri::State<'_, DbState>, id: String, name: String, body: String) -> Result<(), String> {
+    let result = sqlx::query("UPDATE snippets SET name = ?, body = ?, updated_at = ? WHERE id = ?")
+        .bind(clean_name(&name)?)
+        .bind(&body)
+        .bind(Utc::now().to_rfc3339())
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if result.rows_affected() == 0 {
+        return Err("Snippet not found".to_string());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_snippet(state: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
+    sqlx::query("DELETE FROM snippets WHERE id = ?")
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Expands a snippet and copies the result. Returns the text and where the
+/// caret should go after pasting it.
+#[tauri::command]
+pub async fn expand_snippet(state: tauri::State<'_, DbState>, id: String) -> Result<ExpandedSnippet, String> {
+    let body: Option<(String,)> = sqlx::query_as("SELECT body FROM snippets WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (body,) = body.ok_or_else(|| "Snippet not found".to_string())?;
+
+    let expanded = templates::expand(&state.pool, &body).await?;
+    let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
+    let current = if expanded.contains(CLIPBOARD) { clipboard.get_text().unwrap_or_default() } else { String::new() };
+    let snippet = fill(&expanded, &current);
+
+    restore::remember_current(&mut clipboard);
+    clipboard.set_text(snippet.text.clone()).map_err(|e| e.to_string())?;
+    Ok(snippet)
+}
@@ -0,0 +1,114 @@
+// Optional sound feedback: one sound when a clip is captured, another when a
+// clip is copied back from history. Played with the system's own sounds
+// through the platform's command-line player (afplay, paplay, PowerShell),
+// so nothing is bundled. Both are off by default; `sound_volume` is 0-100.
+
+use sqlx::{Pool, Sqlite};
+
+use crate::{setting_enabled, setting_value};
+
+pub const DEFAULT_VOLUME: u32 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sound {
+    Capture,
+    Copy,
+}
+
+impl Sound {
+    pub fn parse(value: &str) -> Option<Sound> {
+        match value.trim().to_lowercase().as_str() {
+            "capture" => Some(Sound::Capture),
+            "copy" => Some(Sound::Copy),
+            _ => None,
+        }
+    }
+
+    fn setting_key(self) -> &'static str {
+        match self {
+            Sound::Capture => "sound_on_capture",
+            Sound::Copy => "sound_on_copy",
+        }
+    }
+}
+
+pub async fn enabled(pool: &Pool<Sqlite>, sound: Sound) -> bool {
+    setting_enabled(pool, sound.setting_key(), false).await
+}
+
+pub async fn volume(pool: &Pool<Sqlite>) -> u32 {
+    setting_value(pool, "sound_volume").await.and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_VOLUME).min(100)
+}
+
+#[cfg(target_os = "macos")]
+fn command(sound: Sound, volume: u32) -> std::process::Command {
+    let file = match sound {
+        Sound::Capture => "/System/Library/Sounds/Pop.aiff",
+        Sound::Copy => "/System/Library/Sounds/Tink.aiff",
+    };
+    let mut command = std::process::Command::new("afplay");
+    command.args(["-v", &format!("{:.2}", volume as f32 / 100.0), file]);
+    command
+}
+
+#[cfg(target_os = "windows")]
+fn command(sound: Sound, volume: u32) -> std::process::Command {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let file = match sound {
+        Sound::Capture => r"C:\Windows\Media\Windows Notify System Generic.wav",
+        Sound::Copy => r"C:\Windows\Media\Speech On.wav",
+    };
+    // MediaPlayer plays asynchronously, so keep the process alive until it's done
+    let script = format!(
+        "Add-Type -AssemblyName PresentationCore; $p = New-Object System.Windows.Media.MediaPlayer; \
+         $p.Volume = {:.2}; $p.Open([uri]'{}'); $p.Play(); Start-Sleep -Milliseconds 1500",
+        volume as f32 / 100.0,
+        file
+    );
+    let mut command = std::process::Command::new("powershell");
+    command.args(["-NoProfile", "-NonInteractive", "-Command", &script]).creation_flags(CREATE_NO_WINDOW);
+    command
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn command(sound: Sound, volume: u32) -> std::process::Command {
+    // freedesktop sound theme, installed with most desktops
+    let file = match sound {
+        Sound::Capture => "/usr/share/sounds/freedesktop/stereo/message-new-instant.oga",
+        Sound::Copy => "/usr/share/sounds/freedesktop/stereo/complete.oga",
+    };
+    let mut command = std::process::Command::new("paplay");
+    command.arg(format!("--volume={}", volume * 65536 / 100)).arg(file);
+    command
+}
+
+/// Plays `sound` in the background at the given volume.
+fn spawn(sound: Sound, volume: u32) {
+    std::thread::spawn(move || {
+        if let Err(e) = command(sound, volume).output() {
+            eprintln!("Could not play {:?} sound: {}", sound, e);
+        }
+    });
+}
+
+/// Plays `sound` if it's turned on in settings.
+pub async fn play(pool: &Pool<Sqlite>, sound: Sound) {
+    if enabled(pool, sound).await {
+        spawn(sound, volume(pool).await);
+    }
+}
+
+/// Plays a sound regardless of its toggle, so the settings screen can preview
+/// it; `volume` overrides the saved one while the slider is being dragged.
+#[tauri::command]
+pub async fn preview_sound(state: tauri::State<'_, crate::DbState>, sound: String, volume: Option<u32>) -> Result<(), String> {
+    let sound = Sound::parse(&sound).ok_or_else(|| format!("Unknown sound: {}", sound))?;
+    let volume = match volume {
+        Some(volume) => volume.min(100),
+        None => self::volume(&state.pool).await,
+    };
+    spawn(sound, volume);
+    Ok(())
+}
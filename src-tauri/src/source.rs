@@ -0,0 +1,165 @@
+// Best-effort metadata about where the current clipboard content came from.
+// Everything here is platform-specific and must fail quietly: a missing
+// source never prevents a clip from being stored.
+
+/// Chromium-based browsers put the page URL in the CF_HTML header
+/// (`SourceURL:...`), which arboard strips, so read the raw flavor.
+#[cfg(target_os = "windows")]
+pub fn source_url() -> Option<String> {
+    let _clipboard = clipboard_win::Clipboard::new_attempts(10).ok()?;
+    let format = clipboard_win::register_format("HTML Format")?;
+    let mut data = Vec::new();
+    clipboard_win::raw::get_vec(format.get(), &mut data).ok()?;
+
+    let raw = String::from_utf8_lossy(&data);
+    raw.lines()
+        .take_while(|line| !line.starts_with('<'))
+        .find_map(|line| line.strip_prefix("SourceURL:"))
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty() && url != "about:blank")
+}
+
+/// Chromium-based browsers add a dedicated pasteboard type with the page URL.
+#[cfg(target_os = "macos")]
+pub fn source_url() -> Option<String> {
+    use objc2_app_kit::NSPasteboard;
+    use objc2_foundation::ns_string;
+
+    let pasteboard = NSPasteboard::generalPasteboard();
+    pasteboard
+        .stringForType(ns_string!("org.chromium.source-url"))
+        .map(|url| url.to_string())
+        .filter(|url| !url.is_empty())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub fn source_url() -> Option<String> {
+    None
+}
+
+/// Password managers mark secrets with a format that clipboard tools should
+/// not record (`ExcludeClipboardContentFromMonitorProcessing`, or
+/// `CanIncludeInClipboardHistory` set to 0 by KeePass).
+#[cfg(target_os = "windows")]
+pub fn is_concealed() -> bool {
+    let Ok(_clipboard) = clipboard_win::Clipboard::new_attempts(10) else {
+        return false;
+    };
+    let marked = ["ExcludeClipboardContentFromMonitorProcessing", "Clipboard Viewer Ignore"]
+        .iter()
+        .filter_map(|name| clipboard_win::register_format(name))
+        .any(|format| clipboard_win::is_format_avail(format.get()));
+    if marked {
+        return true;
+    }
+
+    let Some(format) = clipboard_win::register_format("CanIncludeInClipboardHistory") else {
+        return false;
+    };
+    let mut data = Vec::new();
+    clipboard_win::raw::get_vec(format.get(), &mut data).is_ok() && data.get(..4) == Some(&[0, 0, 0, 0])
+}
+
+/// nspasteboard.org markers used by 1Password, Bitwarden, KeePassXC and others.
+#[cfg(target_os = "macos")]
+pub fn is_concealed() -> bool {
+    use objc2_app_kit::NSPasteboard;
+    use objc2_foundation::ns_string;
+
+    let Some(types) = NSPasteboard::generalPasteboard().types() else {
+        return false;
+    };
+    [ns_string!("org.nspasteboard.ConcealedType"), ns_string!("org.nspasteboard.TransientType")]
+        .into_iter()
+        .any(|marker| types.containsObject(marker))
+}
+
+/// KeePassXC and KDE password tools offer an `x-kde-passwordManagerHint` target.
+#[cfg(target_os = "linux")]
+pub fn is_concealed() -> bool {
+    x11_targets().is_some_and(|targets| targets.iter().any(|t| t == "x-kde-passwordManagerHint"))
+}
+
+/// The process that owns the clipboard, as "pid:<n>", from `GetClipboardOwner`.
+#[cfg(target_os = "windows")]
+pub fn clipboard_owner() -> Option<String> {
+    use windows::Win32::System::DataExchange::GetClipboardOwner;
+    use windows::Win32::UI::WindowsAndMessaging::GetWindowThreadProcessId;
+
+    unsafe {
+        let hwnd = GetClipboardOwner().ok()?;
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        (pid != 0).then(|| format!("pid:{}", pid))
+    }
+}
+
+/// The CLIPBOARD selection owner: its process as "pid:<n>" when the window
+/// carries `_NET_WM_PID`, otherwise the owner window itself.
+#[cfg(target_os = "linux")]
+pub fn clipboard_owner() -> Option<String> {
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _};
+
+    let (conn, _) = x11rb::connect(None).ok()?;
+    let atom = |name: &[u8]| conn.intern_atom(false, name).ok()?.reply().ok().map(|r| r.atom);
+    let owner = conn.get_selection_owner(atom(b"CLIPBOARD")?).ok()?.reply().ok()?.owner;
+    if owner == x11rb::NONE {
+        return None;
+    }
+    let pid = atom(b"_NET_WM_PID")
+        .and_then(|property| conn.get_property(false, owner, property, AtomEnum::CARDINAL, 0, 1).ok()?.reply().ok())
+        .and_then(|reply| reply.value32()?.next());
+    Some(match pid {
+        Some(pid) => format!("pid:{}", pid),
+        None => format!("x11:{:#x}", owner),
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn clipboard_owner() -> Option<String> {
+    None
+}
+
+/// Target names the current CLIPBOARD owner offers, asked for with a short
+/// timeout so a slow owner can't stall the monitor.
+#[cfg(target_os = "linux")]
+fn x11_targets() -> Option<Vec<String>> {
+    use std::time::{Duration, Instant};
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt as _, CreateWindowAux, WindowClass};
+    use x11rb::protocol::Event;
+
+    let (conn, screen) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots.get(screen)?.root;
+    let window = conn.generate_id().ok()?;
+    conn.create_window(0, window, root, 0, 0, 1, 1, 0, WindowClass::INPUT_ONLY, x11rb::COPY_FROM_PARENT, &CreateWindowAux::new())
+        .ok()?;
+    let atom = |name: &[u8]| conn.intern_atom(false, name).ok()?.reply().ok().map(|r| r.atom);
+    let (clipboard, targets, property) = (atom(b"CLIPBOARD")?, atom(b"TARGETS")?, atom(b"KLIP_TARGETS")?);
+    conn.convert_selection(window, clipboard, targets, property, x11rb::CURRENT_TIME).ok()?;
+    conn.flush().ok()?;
+
+    let deadline = Instant::now() + Duration::from_millis(200);
+    loop {
+        match conn.poll_for_event().ok()? {
+            Some(Event::SelectionNotify(e)) if e.property == x11rb::NONE => return None,
+            Some(Event::SelectionNotify(_)) => break,
+            Some(_) => {}
+            None if Instant::now() >= deadline => return None,
+            None => std::thread::sleep(Duration::from_millis(5)),
+        }
+    }
+
+    let reply = conn.get_property(true, window, property, AtomEnum::ATOM, 0, 1024).ok()?.reply().ok()?;
+    let names = reply
+        .value32()?
+        .filter_map(|target| conn.get_atom_name(target).ok()?.reply().ok())
+        .map(|name| String::from_utf8_lossy(&name.name).into_owned())
+        .collect();
+    Some(names)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn is_concealed() -> bool {
+    false
+}
@@ -0,0 +1,104 @@
+// Splitting a large clip into smaller ones, e.g. for form fields or chat
+// messages with a length limit. The original stays; each piece becomes a new
+// clip recorded as "split_from" it.
+
+use tauri::{AppHandle, Emitter};
+use chrono::prelude::*;
+use uuid::Uuid;
+
+use crate::classify::is_text_type;
+use crate::{relations, store, DbState};
+
+const MAX_PIECES: usize = 1000;
+
+/// Chunks of at most `max_chars` characters, broken after whitespace when
+/// there's some in the second half of the chunk.
+fn split_chars(text: &str, max_chars: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = (start + max_chars).min(chars.len());
+        if end < chars.len() {
+            if let Some(space) = chars[start..end].iter().rposition(|c| c.is_whitespace()).filter(|i| *i >= max_chars / 2) {
+                end = start + space + 1;
+            }
+        }
+        pieces.push(chars[start..end].iter().collect());
+        start = end;
+    }
+    pieces
+}
+
+fn split_text(text: &str, strategy: &str, max_chars: Option<usize>) -> Result<Vec<String>, String> {
+    let pieces: Vec<String> = match strategy {
+        "lines" => text.lines().filter(|l| !l.trim().is_empty()).map(str::to_string).collect(),
+        "paragraphs" => {
+            let mut paragraphs = Vec::new();
+            let mut current: Vec<&str> = Vec::new();
+            for line in text.lines() {
+                if line.trim().is_empty() {
+                    if !current.is_empty() {
+                        paragraphs.push(current.join("\n"));
+                        current.clear();
+                    }
+                } else {
+                    current.push(line);
+                }
+            }
+            if !current.is_empty() {
+                paragraphs.push(current.join("\n"));
+            }
+            paragraphs
+        }
+        "chars" => match max_chars {
+            Some(max) if max > 0 => split_chars(text, max),
+            _ => return Err("Splitting by characters needs max_chars".to_string()),
+        },
+        other => return Err(format!("Unknown split strategy: {}", other)),
+    };
+    if pieces.len() > MAX_PIECES {
+        return Err(format!("That would make {} clips; the limit is {}", pieces.len(), MAX_PIECES));
+    }
+    Ok(pieces)
+}
+
+/// Splits a text clip by "lines", "paragraphs" or "chars" (at most
+/// `max_chars` each) into new clips. Returns their ids in order; splitting
+/// into a single piece creates nothing.
+#[tauri::command]
+pub async fn split_clip(app_handle: AppHandle, state: tauri::State<'_, DbState>, id: String, strategy: String, max_chars: Option<usize>) -> Result<Vec<String>, String> {
+    let clip: Option<(String, String)> = sqlx::query_as("SELECT content, clip_type FROM clips WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (content, clip_type) = clip.ok_or_else(|| "Clip not found".to_string())?;
+    if !is_text_type(&clip_type) {
+        return Err("Only text clips can be split".to_string());
+    }
+
+    let pieces = split_text(&content, &strategy, max_chars)?;
+    if pieces.len() < 2 {
+        return Ok(Vec::new());
+    }
+
+    // Microseconds apart, so the pieces keep their order in history
+    let now = Utc::now();
+    let mut ids = Vec::with_capacity(pieces.len());
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    for (i, piece) in pieces.iter().enumerate() {
+        let new_id = Uuid::new_v4().to_string();
+        let created_at = (now + chrono::Duration::microseconds(i as i64)).to_rfc3339();
+        store::insert_text_clip(&mut *tx, &new_id, piece, &created_at, false).await?;
+        ids.push(new_id);
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    for new_id in &ids {
+        relations::record_relation(&state.pool, new_id, &id, relations::SPLIT_FROM).await?;
+    }
+    state.recent.invalidate();
+    let _ = app_handle.emit("clipboard-changed", ());
+    Ok(ids)
+}
@@ -0,0 +1,406 @@
+// Stores clipboard content as clips. Shared by the background monitor,
+// `capture_now` and the startup catch-up, so they apply the same dedup,
+// filters, metadata and events.
+
+use std::path::PathBuf;
+use std::time::Instant;
+use arboard::Clipboard;
+use tauri::{AppHandle, Emitter, Manager};
+use sqlx::{Pool, Sqlite};
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+use crate::rich::{self, RichContent};
+use crate::sounds::{self, Sound};
+use crate::{ansi, app_data_dir, app_icons, capture, capture_log, classify, context, files, focus, foreground, images, line_endings, link_preview, normalize_text, notify, ocr, power, projects, rate_limit, sessions, setting_enabled, source, trash, urls, Clip, DbState};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "outcome", content = "id", rename_all = "snake_case")]
+pub enum CaptureOutcome {
+    Saved(String),
+    Duplicate,
+    Filtered,
+    OwnWindow,
+    Paused,
+    OutsideSchedule,
+    Concealed,
+    IgnoredApp,
+    RateLimited,
+    Empty,
+}
+
+/// True while one of Klip's own windows has focus.
+pub fn klip_has_focus(app: &AppHandle) -> bool {
+    app.webview_windows().values().any(|w| w.is_focused().unwrap_or(false))
+}
+
+/// Whether a copy flagged `concealed` by a password manager is skipped
+/// (the default; `skip_concealed` turned off records it anyway).
+async fn skip_concealed(pool: &Pool<Sqlite>, concealed: bool) -> bool {
+    concealed && setting_enabled(pool, "skip_concealed", true).await
+}
+
+/// Why the monitor shouldn't store the current copy, if it shouldn't. Nothing
+/// is stored while capture is paused or outside the focus-time rules, and
+/// copies made inside Klip itself (e.g. while editing a clip) are skipped
+/// unless `ignore_own_window` is off, as are copies from ignored apps and
+/// from sources over the capture rate limit (`content_key` identifies the
+/// copy when the clipboard owner is unknown).
+async fn monitor_skip(app: &AppHandle, concealed: bool, content_key: impl FnOnce() -> String) -> Option<(CaptureOutcome, &'static str)> {
+    let state = app.try_state::<DbState>()?;
+    if skip_concealed(&state.pool, concealed).await {
+        return Some((CaptureOutcome::Concealed, "concealed"));
+    }
+    if capture::is_paused() {
+        return Some((CaptureOutcome::Paused, "paused"));
+    }
+    if focus::outside_schedule() {
+        return Some((CaptureOutcome::OutsideSchedule, "outside_schedule"));
+    }
+    if klip_has_focus(app) && setting_enabled(&state.pool, "ignore_own_window", true).await {
+        return Some((CaptureOutcome::OwnWindow, "own_window"));
+    }
+    let window = foreground::foreground_window();
+    if capture::is_ignored_app(&state.pool, window.as_ref()).await {
+        return Some((CaptureOutcome::IgnoredApp, "ignored_app"));
+    }
+    if rate_limit::is_limited(app, &state.pool, window.as_ref(), content_key).await {
+        return Some((CaptureOutcome::RateLimited, "rate_limited"));
+    }
+    None
+}
+
+/// Entry point for the background monitor; see `monitor_skip` for what isn't stored.
+pub async fn store_monitored_text(app: &AppHandle, text: &str, source_url: Option<String>, rich: Option<RichContent>, concealed: bool, seen_at: Instant) -> Result<CaptureOutcome, String> {
+    if let Some((outcome, reason)) = monitor_skip(app, concealed, || capture::text_hash(text)).await {
+        capture_log::record_text(text, reason, seen_at.elapsed().as_millis() as u64);
+        return Ok(outcome);
+    }
+    let outcome = store_text(app, text, source_url, rich, true, seen_at).await?;
+    announce(app, &outcome).await;
+    Ok(outcome)
+}
+
+/// Capture sound for a clip the monitor just stored.
+async fn announce(app: &AppHandle, outcome: &CaptureOutcome) {
+    if matches!(outcome, CaptureOutcome::Saved(_)) {
+        if let Some(state) = app.try_state::<DbState>() {
+            sounds::play(&state.pool, Sound::Capture).await;
+        }
+    }
+}
+
+/// Whether anything about the foreground app may be stored with a clip: only
+/// when window-title or context capture is on.
+async fn may_record_window(pool: &Pool<Sqlite>) -> bool {
+    setting_enabled(pool, "capture_window_title", false).await || setting_enabled(pool, "capture_context", false).await
+}
+
+/// Name and cached icon of the app a capture came from, when window details
+/// are recorded at all and unless turned off with `capture_source_app`.
+async fn source_app(app: &AppHandle, pool: &Pool<Sqlite>, window: Option<&foreground::ForegroundWindow>) -> (Option<String>, Option<String>) {
+    match window {
+        Some(window) if may_record_window(pool).await && setting_enabled(pool, "capture_source_app", true).await => {
+            (window.app_name.clone(), app_icons::icon_path(app, window))
+        }
+        _ => (None, None),
+    }
+}
+
+/// Captured text as it would be stored: escape codes stripped and line
+/// endings normalized per settings, plus the raw text when it's kept.
+pub async fn stored_text(pool: &Pool<Sqlite>, text: &str) -> (String, Option<String>) {
+    let (text, ansi_original) = ansi::clean_capture(pool, text).await;
+    (line_endings::normalize_capture(pool, text).await, ansi_original)
+}
+
+/// Inserts a text clip that isn't a capture (imports, edits made into new
+/// clips) with the derived columns captures get. Returns its clip type.
+pub async fn insert_text_clip<'c>(executor: impl sqlx::SqliteExecutor<'c>, id: &str, content: &str, created_at: &str, is_favorite: bool) -> Result<&'static str, String> {
+    let clip_type = classify::text_clip_type(content);
+    sqlx::query("INSERT INTO clips (id, content, created_at, is_favorite, search_content, clip_type, image_path, content_hash, canonical_url, detected_kind) VALUES (?, ?, ?, ?, ?, ?, NULL, ?, ?, ?)")
+        .bind(id)
+        .bind(content)
+        .bind(created_at)
+        .bind(is_favorite)
+        .bind(normalize_text(content))
+        .bind(clip_type)
+        .bind(capture::text_hash(content))
+        .bind(urls::canonical_url(content).unwrap_or_default())
+        .bind(classify::detect_kind(content))
+        .execute(executor)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(clip_type)
+}
+
+/// Stores a text capture, with its HTML/RTF flavor unless `capture_rich_text`
+/// is off. `automatic` captures (the monitor, startup catch-up) also go
+/// through the capture filters and may post a notification.
+pub async fn store_text(app: &AppHandle, text: &str, source_url: Option<String>, rich: Option<RichContent>, automatic: bool, seen_at: Instant) -> Result<CaptureOutcome, String> {
+    let state = app.try_state::<DbState>().ok_or_else(|| "Database is not available".to_string())?;
+    let elapsed = || seen_at.elapsed().as_millis() as u64;
+    let (text, ansi_original) = stored_text(&state.pool, text).await;
+    let text = text.as_str();
+
+    // Check duplicates within the dedup window before inserting
+    if capture::is_duplicate(&state, text).await? {
+        capture_log::record_text(text, "duplicate", elapsed());
+        return Ok(CaptureOutcome::Duplicate);
+    }
+    if automatic && !capture::should_capture(&state.pool, text).await {
+        capture_log::record_text(text, "filtered", elapsed());
+        return Ok(CaptureOutcome::Filtered);
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now();
+    let created_at = now.to_rfc3339();
+    let want_title = setting_enabled(&state.pool, "capture_window_title", false).await;
+    let want_context = setting_enabled(&state.pool, "capture_context", false).await;
+    let window = foreground::foreground_window();
+    let window_title = if want_title { window.as_ref().and_then(|w| w.title.clone()) } else { None };
+    let context = if want_context { context::context_json(window.as_ref(), source_url.as_deref()) } else { None };
+    let app_name = window.as_ref().and_then(|w| w.app_name.as_deref());
+    let (source_app, source_app_icon) = source_app(app, &state.pool, window.as_ref()).await;
+    let session_app = if want_title || want_context { app_name } else { None };
+    let session_id = sessions::assign(&state.pool, session_app, now).await?;
+    let project_id = projects::project_for_capture(&state.pool, app_name).await;
+    let rich = if setting_enabled(&state.pool, "capture_rich_text", true).await { rich } else { None };
+
+    let clip_type = classify::text_clip_type(text);
+    let detected_kind = classify::detect_kind(text);
+
+    let inserted = sqlx::query("INSERT INTO clips (id, content, created_at, is_favorite, search_content, clip_type, image_path, source_url, window_title, context, source_app, source_app_icon, session_id, project_id, content_hash, rich_format, rich_content, canonical_url, detected_kind, ansi_original) VALUES (?, ?, ?, ?, ?, ?, NULL, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+        .bind(&id)
+        .bind(text)
+        .bind(&created_at)
+        .bind(false)
+        .bind(normalize_text(text))
+        .bind(clip_type)
+        .bind(&source_url)
+        .bind(&window_title)
+        .bind(context)
+        .bind(&source_app)
+        .bind(&source_app_icon)
+        .bind(&session_id)
+        .bind(&project_id)
+        .bind(capture::text_hash(text))
+        .bind(rich.as_ref().map(|r| r.format.clone()))
+        .bind(rich.map(|r| r.content))
+        .bind(urls::canonical_url(text).unwrap_or_default())
+        .bind(detected_kind)
+        .bind(&ansi_original)
+        .execute(&state.pool)
+        .await;
+
+    if let Err(e) = inserted {
+        capture_log::record_text(text, "insert_failed", elapsed());
+        return Err(e.to_string());
+    }
+    capture_log::record_text(text, "saved", elapsed());
+
+    let mut clip = Clip::new_text(id.clone(), text.to_string(), created_at);
+    clip.clip_type = clip_type.to_string();
+    clip.detected_kind = Some(detected_kind.to_string());
+    clip.source_url = source_url;
+    clip.window_title = window_title;
+    clip.source_app = source_app;
+    clip.source_app_icon = source_app_icon;
+    clip.session_id = Some(session_id);
+    clip.project_id = project_id;
+    state.recent.insert(clip);
+    if clip_type == "url" {
+        link_preview::queue(app, id.clone(), text.to_string());
+    }
+
+    let _ = app.emit("clipboard-changed", ());
+    if automatic && setting_enabled(&state.pool, "capture_notifications", false).await {
+        notify::notify_capture(app, id.clone(), text);
+    }
+    Ok(CaptureOutcome::Saved(id))
+}
+
+/// Stores the clipboard found at launch unless it's already anywhere in history
+/// (the same text would otherwise be re-added on every start once the dedup
+/// window has passed). Disabled with the `startup_catch_up` setting.
+pub async fn catch_up(app: &AppHandle, text: &str, concealed: bool) -> Result<CaptureOutcome, String> {
+    let state = app.try_state::<DbState>().ok_or_else(|| "Database is not available".to_string())?;
+    if !setting_enabled(&state.pool, "startup_catch_up", true).await {
+        return Ok(CaptureOutcome::Filtered);
+    }
+    if skip_concealed(&state.pool, concealed).await {
+        return Ok(CaptureOutcome::Concealed);
+    }
+
+    let (stored, _) = stored_text(&state.pool, text).await;
+    let exists: Option<(i32,)> = sqlx::query_as(&format!("SELECT 1 FROM clips WHERE content = ? AND {} LIMIT 1", trash::NOT_DELETED))
+        .bind(&stored)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if exists.is_some() {
+        return Ok(CaptureOutcome::Duplicate);
+    }
+
+    store_text(app, text, source::source_url(), rich::read(), true, Instant::now()).await
+}
+
+/// Saves raw RGBA pixels as a PNG in `images/` and stores an image clip,
+/// unless an identical image (by content hash) is inside the dedup window.
+pub async fn store_image(app: &AppHandle, width: u32, height: u32, rgba: Vec<u8>, seen_at: Instant) -> Result<CaptureOutcome, String> {
+    let state = app.try_state::<DbState>().ok_or_else(|| "Database is not available".to_string())?;
+    let size = rgba.len();
+    let elapsed = || seen_at.elapsed().as_millis() as u64;
+
+    let hash = images::content_hash(width, height, &rgba);
+    if capture::is_duplicate_image(&state.pool, &hash).await? {
+        capture_log::record_image(size, "duplicate", elapsed());
+        return Ok(CaptureOutcome::Duplicate);
+    }
+
+    let img = image::RgbaImage::from_raw(width, height, rgba).ok_or_else(|| "Invalid image data".to_string())?;
+    let id = Uuid::new_v4().to_string();
+    let images_dir = app_data_dir(app).join("images");
+    std::fs::create_dir_all(&images_dir).map_err(|e| e.to_string())?;
+    let file_path = images_dir.join(format!("{}.png", id));
+    if let Err(e) = images::save_png(&file_path, &img) {
+        let _ = std::fs::remove_file(&file_path);
+        return Err(e);
+    }
+    // Left for maintenance to fill in once back on power
+    let low_power = power::is_low_power();
+    let dominant_color = (!low_power).then(|| images::dominant_color(&img));
+    let thumbnail_path = if low_power { None } else { images::save_thumbnail(&file_path, &img).ok() };
+    let now = Utc::now();
+    let window = foreground::foreground_window();
+    let app_name = window.as_ref().and_then(|w| w.app_name.clone());
+    let (source_app, source_app_icon) = source_app(app, &state.pool, window.as_ref()).await;
+    let session_app = if may_record_window(&state.pool).await { app_name.as_deref() } else { None };
+    let session_id = sessions::assign(&state.pool, session_app, now).await?;
+    let project_id = projects::project_for_capture(&state.pool, app_name.as_deref()).await;
+
+    let inserted = sqlx::query("INSERT INTO clips (id, content, created_at, is_favorite, search_content, clip_type, image_path, image_width, image_height, dominant_color, thumbnail_path, content_hash, source_app, source_app_icon, session_id, project_id) VALUES (?, '', ?, ?, NULL, 'image', ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+        .bind(&id)
+        .bind(now.to_rfc3339())
+        .bind(false)
+        .bind(file_path.to_string_lossy().to_string())
+        .bind(width)
+        .bind(height)
+        .bind(dominant_color)
+        .bind(thumbnail_path.map(|p| p.to_string_lossy().to_string()))
+        .bind(&hash)
+        .bind(&source_app)
+        .bind(&source_app_icon)
+        .bind(&session_id)
+        .bind(&project_id)
+        .execute(&state.pool)
+        .await;
+
+    if let Err(e) = inserted {
+        let _ = images::remove_image(&file_path);
+        capture_log::record_image(size, "insert_failed", elapsed());
+        return Err(e.to_string());
+    }
+    capture_log::record_image(size, "saved", elapsed());
+    ocr::queue(app, id.clone(), file_path);
+
+    state.recent.invalidate();
+    let _ = app.emit("clipboard-changed", ());
+    Ok(CaptureOutcome::Saved(id))
+}
+
+/// Monitor entry point for images; skipped in the same cases as text.
+pub async fn store_monitored_image(app: &AppHandle, width: u32, height: u32, rgba: Vec<u8>, concealed: bool, seen_at: Instant) -> Result<CaptureOutcome, String> {
+    if let Some((outcome, reason)) = monitor_skip(app, concealed, || images::content_hash(width, height, &rgba)).await {
+        capture_log::record_image(rgba.len(), reason, seen_at.elapsed().as_millis() as u64);
+        return Ok(outcome);
+    }
+    let outcome = store_image(app, width, height, rgba, seen_at).await?;
+    announce(app, &outcome).await;
+    Ok(outcome)
+}
+
+/// Stores files copied in a file manager as a "files" clip holding their
+/// paths, unless the same files are inside the dedup window.
+pub async fn store_files(app: &AppHandle, paths: &[PathBuf], seen_at: Instant) -> Result<CaptureOutcome, String> {
+    let state = app.try_state::<DbState>().ok_or_else(|| "Database is not available".to_string())?;
+    let elapsed = || seen_at.elapsed().as_millis() as u64;
+    let content = files::encode(paths)?;
+
+    if capture::is_duplicate(&state, &content).await? {
+        capture_log::record_files(paths.len(), "duplicate", elapsed());
+        return Ok(CaptureOutcome::Duplicate);
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now();
+    let window = foreground::foreground_window();
+    let app_name = window.as_ref().and_then(|w| w.app_name.clone());
+    let (source_app, source_app_icon) = source_app(app, &state.pool, window.as_ref()).await;
+    let session_app = if may_record_window(&state.pool).await { app_name.as_deref() } else { None };
+    let session_id = sessions::assign(&state.pool, session_app, now).await?;
+    let project_id = projects::project_for_capture(&state.pool, app_name.as_deref()).await;
+
+    let inserted = sqlx::query("INSERT INTO clips (id, content, created_at, is_favorite, search_content, clip_type, image_path, content_hash, source_app, source_app_icon, session_id, project_id) VALUES (?, ?, ?, ?, ?, 'files', NULL, ?, ?, ?, ?, ?)")
+        .bind(&id)
+        .bind(&content)
+        .bind(now.to_rfc3339())
+        .bind(false)
+        .bind(normalize_text(&files::search_text(paths)))
+        .bind(capture::text_hash(&content))
+        .bind(&source_app)
+        .bind(&source_app_icon)
+        .bind(&session_id)
+        .bind(&project_id)
+        .execute(&state.pool)
+        .await;
+
+    if let Err(e) = inserted {
+        capture_log::record_files(paths.len(), "insert_failed", elapsed());
+        return Err(e.to_string());
+    }
+    capture_log::record_files(paths.len(), "saved", elapsed());
+
+    state.recent.invalidate();
+    let _ = app.emit("clipboard-changed", ());
+    Ok(CaptureOutcome::Saved(id))
+}
+
+/// Monitor entry point for copied files; skipped in the same cases as text.
+pub async fn store_monitored_files(app: &AppHandle, paths: &[PathBuf], concealed: bool, seen_at: Instant) -> Result<CaptureOutcome, String> {
+    if let Some((outcome, reason)) = monitor_skip(app, concealed, || capture::text_hash(&files::search_text(paths))).await {
+        capture_log::record_files(paths.len(), reason, seen_at.elapsed().as_millis() as u64);
+        return Ok(outcome);
+    }
+    let outcome = store_files(app, paths, seen_at).await?;
+    announce(app, &outcome).await;
+    Ok(outcome)
+}
+
+/// Reads the system clipboard right now and stores what it holds (text and/or
+/// image, or copied files), whether or not the monitor is running. Filters
+/// are skipped since this is an explicit request; dedup still applies.
+#[tauri::command]
+pub async fn capture_now(app_handle: AppHandle) -> Result<Vec<CaptureOutcome>, String> {
+    let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
+    // The file names and icon offered next to copied files aren't clips of their own
+    if let Some(paths) = files::read(&mut clipboard) {
+        return Ok(vec![store_files(&app_handle, &paths, Instant::now()).await?]);
+    }
+    let text = clipboard.get_text().ok().filter(|t| !t.is_empty());
+    let image = clipboard.get_image().ok().filter(|i| !i.bytes.is_empty());
+
+    let mut outcomes = Vec::new();
+    if let Some(text) = text {
+        outcomes.push(store_text(&app_handle, &text, source::source_url(), rich::read(), false, Instant::now()).await?);
+    }
+    if let Some(image) = image {
+        outcomes.push(store_image(&app_handle, image.width as u32, image.height as u32, image.bytes.into_owned(), Instant::now()).await?);
+    }
+    if outcomes.is_empty() {
+        outcomes.push(CaptureOutcome::Empty);
+    }
+    Ok(outcomes)
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+
+use crate::{diagnostics, Clip, DbState, CLIP_COLUMNS};
+use crate::classify::summary_kind;
+
+const TOP_ITEMS_PER_KIND: usize = 3;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KindSummary {
+    kind: String, // "link", "code", "text", "image" or "files"
+    count: i64,
+    top: Vec<Clip>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TodaySummary {
+    total: i64,
+    groups: Vec<KindSummary>,
+}
+
+#[tauri::command]
+pub async fn get_today_summary(state: tauri::State<'_, DbState>) -> Result<TodaySummary, String> {
+    // Most reused first, then most recent, so "top" reflects what actually mattered today
+    let query = format!(
+        "SELECT {} FROM clips
+         WHERE strftime('%Y-%m-%d', created_at, 'localtime') = strftime('%Y-%m-%d', 'now', 'localtime') AND deleted_at IS NULL
+         ORDER BY (SELECT COUNT(*) FROM clip_usage u WHERE u.clip_id = clips.id) DESC, created_at DESC",
+        CLIP_COLUMNS
+    );
+    let clips = diagnostics::timed("get_today_summary", sqlx::query_as::<_, Clip>(&query).fetch_all(&state.pool))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let total = clips.len() as i64;
+    let mut groups: HashMap<&'static str, KindSummary> = HashMap::new();
+
+    for clip in clips {
+        let kind = summary_kind(&clip.clip_type, &clip.content);
+        let group = groups.entry(kind).or_insert_with(|| KindSummary {
+            kind: kind.to_string(),
+            count: 0,
+            top: Vec::new(),
+        });
+        group.count += 1;
+        if group.top.len() < TOP_ITEMS_PER_KIND {
+            group.top.push(clip);
+        }
+    }
+
+    let mut groups: Vec<KindSummary> = groups.into_values().collect();
+    groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.kind.cmp(&b.kind)));
+
+    Ok(TodaySummary { total, groups })
+}
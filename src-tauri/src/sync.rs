@@ -0,0 +1,694 @@
+// Cross-machine sync. The scope decides which clips leave this machine at all:
+// everything, or only the curated ones. Concurrent edits are never merged
+// silently; they land in `sync_conflicts` for the user to resolve.
+//
+// Transport: every machine appends encrypted changelog files
+// (`klip-sync-<device>-<time>.klipsync`) to a shared WebDAV collection or
+// folder (Syncthing, Dropbox, a network share) and applies the files written
+// by the others. Triggers queue changed and deleted clips in `sync_outbox`
+// while sync is on; deletions travel as tombstones and land in the trash on
+// the other machines. Only text clips sync, since image and file clips point
+// at files on this machine.
+
+use tauri::{AppHandle, Emitter, Manager};
+use sqlx::{Pool, Sqlite};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+use crate::backup::Destination;
+use crate::classify::{self, is_text_type};
+use crate::{crypto, keychain, locking, normalize_text, power, relations, setting_enabled, setting_value, store, urls, DbState};
+
+pub const DESTINATIONS: &[&str] = &["folder", "webdav"];
+const FILE_PREFIX: &str = "klip-sync-";
+const FILE_SUFFIX: &str = ".klipsync";
+const NAME_TIME_FORMAT: &str = "%Y%m%d-%H%M%S%3f";
+const KEEP_CHANGELOG_DAYS: i64 = 90; // our own files older than this are pruned
+const SYNC_INTERVAL_MINUTES: i64 = 5;
+const LOW_POWER_SYNC_INTERVAL_MINUTES: i64 = 30;
+// Keychain entries
+pub const PASSPHRASE_SECRET: &str = "sync_passphrase"; // the same on every machine
+pub const PASSWORD_SECRET: &str = "sync_password"; // WebDAV password
+
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS sync_outbox (
+        clip_id TEXT PRIMARY KEY,
+        deleted BOOLEAN NOT NULL,
+        changed_at DATETIME NOT NULL
+    )",
+    // Changelog files from other machines that have been applied here
+    "CREATE TABLE IF NOT EXISTS sync_applied (
+        name TEXT PRIMARY KEY,
+        applied_at DATETIME NOT NULL
+    )",
+    // Clips the other machines have, so one leaving the scope still gets a last update
+    "CREATE TABLE IF NOT EXISTS sync_sent (
+        clip_id TEXT PRIMARY KEY
+    )",
+    "CREATE TRIGGER IF NOT EXISTS clips_sync_insert AFTER INSERT ON clips
+     WHEN EXISTS (SELECT 1 FROM settings WHERE key = 'sync_enabled' AND value = 'true') BEGIN
+        INSERT OR REPLACE INTO sync_outbox (clip_id, deleted, changed_at) VALUES (new.id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+    END",
+    // Recreated so filing a clip in a collection queues it too
+    "DROP TRIGGER IF EXISTS clips_sync_update",
+    "CREATE TRIGGER IF NOT EXISTS clips_sync_update AFTER UPDATE OF content, is_favorite, pinned, deleted_at, collection_id ON clips
+     WHEN EXISTS (SELECT 1 FROM settings WHERE key = 'sync_enabled' AND value = 'true') BEGIN
+        INSERT OR REPLACE INTO sync_outbox (clip_id, deleted, changed_at) VALUES (new.id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+    END",
+    "CREATE TRIGGER IF NOT EXISTS clips_sync_delete AFTER DELETE ON clips
+     WHEN EXISTS (SELECT 1 FROM settings WHERE key = 'sync_enabled' AND value = 'true') BEGIN
+        INSERT OR REPLACE INTO sync_outbox (clip_id, deleted, changed_at) VALUES (old.id, 1, strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
+    END",
+];
+
+pub async fn init_sync(pool: &Pool<Sqlite>) -> Result<(), String> {
+    for statement in SCHEMA {
+        sqlx::query(statement).execute(pool).await.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncScope {
+    All,
+    Favorites,
+    Saved, // favorites and pinned clips plus clips filed in the chosen collections
+}
+
+impl SyncScope {
+    fn parse(value: &str) -> Option<SyncScope> {
+        match value {
+            "all" => Some(SyncScope::All),
+            "favorites" => Some(SyncScope::Favorites),
+            "saved" => Some(SyncScope::Saved),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            SyncScope::All => "all",
+            SyncScope::Favorites => "favorites",
+            SyncScope::Saved => "saved",
+        }
+    }
+
+    /// SQL condition on `clips` selecting the clips this scope syncs.
+    pub fn condition(self) -> &'static str {
+        match self {
+            SyncScope::All => "1=1",
+            SyncScope::Favorites => "(is_favorite = 1 OR pinned = 1)",
+            // `sync_collections` is a JSON array of collection ids; without it every collection counts
+            SyncScope::Saved => "(is_favorite = 1 OR pinned = 1 OR (collection_id IS NOT NULL AND (
+                NOT EXISTS (SELECT 1 FROM settings WHERE key = 'sync_collections')
+                OR collection_id IN (SELECT value FROM json_each((SELECT value FROM settings WHERE key = 'sync_collections'))))))",
+        }
+    }
+}
+
+pub async fn sync_scope(pool: &Pool<Sqlite>) -> SyncScope {
+    setting_value(pool, "sync_scope")
+        .await
+        .and_then(|v| SyncScope::parse(&v))
+        .unwrap_or(SyncScope::All)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncScopeStatus {
+    scope: String,
+    collections: Option<Vec<String>>, // ids synced by the "saved" scope; None for all of them
+    clip_count: i64, // clips currently inside the scope
+}
+
+async fn sync_collections(pool: &Pool<Sqlite>) -> Option<Vec<String>> {
+    setting_value(pool, "sync_collections").await.and_then(|v| serde_json::from_str(&v).ok())
+}
+
+#[tauri::command]
+pub async fn get_sync_scope(state: tauri::State<'_, DbState>) -> Result<SyncScopeStatus, String> {
+    let scope = sync_scope(&state.pool).await;
+    let (clip_count,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM clips WHERE {}", scope.condition()))
+        .fetch_one(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let collections = sync_collections(&state.pool).await;
+    Ok(SyncScopeStatus { scope: scope.as_str().to_string(), collections, clip_count })
+}
+
+/// Sets the sync scope: "all", "favorites" or "saved". With "saved",
+/// `collections` picks the collections to sync (all of them when omitted).
+#[tauri::command]
+pub async fn set_sync_scope(state: tauri::State<'_, DbState>, scope: String, collections: Option<Vec<String>>) -> Result<SyncScopeStatus, String> {
+    let parsed = SyncScope::parse(&scope).ok_or_else(|| format!("Unknown sync scope: {}", scope))?;
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES ('sync_scope', ?)")
+        .bind(parsed.as_str())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    match collections {
+        Some(ids) => sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES ('sync_collections', ?)")
+            .bind(serde_json::to_string(&ids).map_err(|e| e.to_string())?),
+        None => sqlx::query("DELETE FROM settings WHERE key = 'sync_collections'"),
+    }
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    // Clips that just came into scope haven't been sent yet
+    if enabled(&state.pool).await {
+        sqlx::query(&format!(
+            "INSERT OR IGNORE INTO sync_outbox (clip_id, deleted, changed_at)
+             SELECT id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now') FROM clips
+             WHERE clip_type IN ('text', 'url') AND deleted_at IS NULL AND {} AND id NOT IN (SELECT clip_id FROM sync_sent)",
+            parsed.condition()
+        ))
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    get_sync_scope(state).await
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct SyncConflict {
+    id: i64,
+    clip_id: String,
+    local_content: String,
+    remote_content: String,
+    remote_device: Option<String>,
+    detected_at: String, // ISO 8601
+}
+
+#[tauri::command]
+pub async fn list_sync_conflicts(state: tauri::State<'_, DbState>) -> Result<Vec<SyncConflict>, String> {
+    sqlx::query_as::<_, SyncConflict>(
+        "SELECT id, clip_id, local_content, remote_content, remote_device, detected_at
+         FROM sync_conflicts ORDER BY detected_at DESC"
+    )
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Resolves a conflict. `choice` is "local" (keep this machine's text),
+/// "remote" (take the other machine's) or "both" (keep local, save remote as a new linked clip).
+#[tauri::command]
+pub async fn resolve_conflict(app_handle: AppHandle, state: tauri::State<'_, DbState>, id: i64, choice: String) -> Result<(), String> {
+    let conflict = sqlx::query_as::<_, SyncConflict>(
+        "SELECT id, clip_id, local_content, remote_content, remote_device, detected_at FROM sync_conflicts WHERE id = ?"
+    )
+    .bind(id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| "Conflict not found".to_string())?;
+
+    match choice.as_str() {
+        "local" => {}
+        "remote" => {
+            locking::ensure_unlocked(&state.pool, &conflict.clip_id).await?;
+            sqlx::query(
+                "UPDATE clips SET content = ?, search_content = ?,
+                 clip_type = CASE WHEN clip_type IN ('text', 'url') THEN ? ELSE clip_type END,
+                 canonical_url = CASE WHEN clip_type IN ('text', 'url') THEN ? ELSE canonical_url END,
+                 detected_kind = CASE WHEN clip_type IN ('text', 'url') THEN ? ELSE detected_kind END
+                 WHERE id = ?"
+            )
+            .bind(&conflict.remote_content)
+            .bind(normalize_text(&conflict.remote_content))
+            .bind(classify::text_clip_type(&conflict.remote_content))
+            .bind(urls::canonical_url(&conflict.remote_content).unwrap_or_default())
+            .bind(classify::detect_kind(&conflict.remote_content))
+            .bind(&conflict.clip_id)
+            .execute(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+            let content = conflict.remote_content.clone();
+            state.recent.update(&conflict.clip_id, |clip| {
+                if is_text_type(&clip.clip_type) {
+                    clip.clip_type = classify::text_clip_type(&content).to_string();
+                    clip.detected_kind = Some(classify::detect_kind(&content).to_string());
+                }
+                clip.content = content;
+            });
+        }
+        "both" => {
+            let new_id = Uuid::new_v4().to_string();
+            store::insert_text_clip(&state.pool, &new_id, &conflict.remote_content, &Utc::now().to_rfc3339(), false).await?;
+            relations::record_relation(&state.pool, &new_id, &conflict.clip_id, relations::CONFLICT_COPY_OF).await?;
+            state.recent.invalidate();
+        }
+        other => return Err(format!("Unknown resolution: {}", other)),
+    }
+
+    sqlx::query("DELETE FROM sync_conflicts WHERE id = ?")
+        .bind(id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let _ = app_handle.emit("clipboard-changed", ());
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SyncConfig {
+    pub enabled: bool,
+    pub destination: Option<String>, // one of DESTINATIONS
+    pub location: Option<String>,    // folder path or WebDAV collection URL
+    pub username: Option<String>,    // WebDAV only
+    pub device_name: Option<String>, // shown in conflicts on the other machines
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SyncReport {
+    pushed: usize,
+    applied: usize,
+    conflicts: usize,
+}
+
+/// One clip's state, or its tombstone, as written to a changelog.
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+struct ClipChange {
+    id: String,
+    deleted: bool,
+    changed_at: String, // ISO 8601
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    created_at: Option<String>,
+    #[serde(default)]
+    is_favorite: Option<bool>,
+    #[serde(default)]
+    pinned: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct Changelog {
+    device_id: String,
+    device_name: String,
+    created_at: String, // ISO 8601
+    changes: Vec<ClipChange>,
+}
+
+pub async fn enabled(pool: &Pool<Sqlite>) -> bool {
+    setting_enabled(pool, "sync_enabled", false).await
+}
+
+/// How often the scheduler syncs; less often on battery.
+pub fn interval() -> chrono::Duration {
+    if power::is_low_power() {
+        chrono::Duration::minutes(LOW_POWER_SYNC_INTERVAL_MINUTES)
+    } else {
+        chrono::Duration::minutes(SYNC_INTERVAL_MINUTES)
+    }
+}
+
+pub async fn load_config(pool: &Pool<Sqlite>) -> SyncConfig {
+    let value = |key: &'static str| async move { setting_value(pool, key).await.filter(|v| !v.is_empty()) };
+    SyncConfig {
+        enabled: enabled(pool).await,
+        destination: value("sync_destination").await,
+        location: value("sync_location").await,
+        username: value("sync_username").await,
+        device_name: value("sync_device_name").await,
+    }
+}
+
+fn destination(config: &SyncConfig) -> Result<Destination, String> {
+    let location = config.location.clone().ok_or_else(|| "No sync location configured".to_string())?;
+    match config.destination.as_deref() {
+        Some("folder") => Ok(Destination::Folder(PathBuf::from(location))),
+        Some("webdav") => Ok(Destination::WebDav {
+            url: location.trim_end_matches('/').to_string(),
+            username: config.username.clone(),
+            password: keychain::get_secret(PASSWORD_SECRET)?,
+        }),
+        Some(other) => Err(format!("Unknown sync destination: {}", other)),
+        None => Err("No sync destination configured".to_string()),
+    }
+}
+
+fn passphrase() -> Result<String, String> {
+    keychain::get_secret(PASSPHRASE_SECRET)?
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| "Set a sync passphrase first".to_string())
+}
+
+/// This machine's id in changelog names, created on first use.
+async fn device_id(pool: &Pool<Sqlite>) -> Result<String, String> {
+    if let Some(id) = setting_value(pool, "sync_device_id").await.filter(|v| !v.is_empty()) {
+        return Ok(id);
+    }
+    let id = Uuid::new_v4().simple().to_string();
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES ('sync_device_id', ?)")
+        .bind(&id)
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+fn default_device_name(device_id: &str) -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(|| format!("Klip {}", device_id.chars().take(8).collect::<String>()))
+}
+
+/// Device id and timestamp from a changelog name.
+fn parse_name(name: &str) -> Option<(&str, &str)> {
+    name.strip_prefix(FILE_PREFIX)?.strip_suffix(FILE_SUFFIX)?.split_once('-')
+}
+
+/// Queues every clip in the sync scope, so history from before sync was
+/// turned on reaches the other machines too.
+async fn queue_existing(pool: &Pool<Sqlite>) -> Result<(), String> {
+    let scope = sync_scope(pool).await;
+    sqlx::query(&format!(
+        "INSERT OR IGNORE INTO sync_outbox (clip_id, deleted, changed_at)
+         SELECT id, 0, strftime('%Y-%m-%dT%H:%M:%fZ', 'now') FROM clips WHERE clip_type IN ('text', 'url') AND deleted_at IS NULL AND {}",
+        scope.condition()
+    ))
+    .execute(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Writes the queued changes to a new changelog. Clips outside the sync scope
+/// and non-text clips are dropped from the queue without being sent, except
+/// for a last update to clips that left the scope after being sent.
+async fn push(pool: &Pool<Sqlite>, destination: &Destination, passphrase: &str, device_id: &str, device_name: &str) -> Result<usize, String> {
+    let scope = sync_scope(pool).await;
+    let queued: Vec<ClipChange> = sqlx::query_as(&format!(
+        "SELECT o.clip_id AS id, (o.deleted = 1 OR c.deleted_at IS NOT NULL) AS deleted, o.changed_at, c.content, c.created_at, c.is_favorite, c.pinned
+         FROM sync_outbox o LEFT JOIN clips c ON c.id = o.clip_id
+         WHERE o.deleted = 1 OR c.deleted_at IS NOT NULL OR o.clip_id IN (SELECT clip_id FROM sync_sent)
+            OR o.clip_id IN (SELECT id FROM clips WHERE clip_type IN ('text', 'url') AND {})
+         ORDER BY o.changed_at",
+        scope.condition()
+    ))
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if !queued.is_empty() {
+        let now = Utc::now();
+        let changelog = Changelog {
+            device_id: device_id.to_string(),
+            device_name: device_name.to_string(),
+            created_at: now.to_rfc3339(),
+            changes: queued.clone(),
+        };
+        let json = serde_json::to_vec(&changelog).map_err(|e| e.to_string())?;
+        let name = format!("{}{}-{}{}", FILE_PREFIX, device_id, now.format(NAME_TIME_FORMAT), FILE_SUFFIX);
+        destination.put(&name, crypto::encrypt(passphrase, &json)?).await?;
+    }
+
+    // Rows changed again during the upload stay queued for the next run
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    for change in &queued {
+        sqlx::query("DELETE FROM sync_outbox WHERE clip_id = ? AND changed_at = ?")
+            .bind(&change.id)
+            .bind(&change.changed_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        sqlx::query("INSERT OR IGNORE INTO sync_sent (clip_id) VALUES (?)")
+            .bind(&change.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    // Deleted clips and those that just had their last update leave sync_sent
+    sqlx::query(&format!(
+        "DELETE FROM sync_sent WHERE clip_id NOT IN (SELECT id FROM clips WHERE clip_type IN ('text', 'url') AND deleted_at IS NULL AND {})",
+        scope.condition()
+    ))
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    sqlx::query(&format!(
+        "DELETE FROM sync_outbox WHERE deleted = 0 AND clip_id NOT IN (SELECT id FROM clips WHERE clip_type IN ('text', 'url') AND {})",
+        scope.condition()
+    ))
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(queued.len())
+}
+
+/// Applies one remote change. A clip edited here and there since the last
+/// sync becomes a conflict instead; locked clips are left alone. Returns
+/// whether it was a conflict.
+async fn apply_change(tx: &mut sqlx::SqliteConnection, change: &ClipChange, pending: &HashSet<String>, remote_device: &str) -> Result<bool, String> {
+    let local: Option<(String, bool)> = sqlx::query_as("SELECT content, locked FROM clips WHERE id = ?")
+        .bind(&change.id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    let edited_here = pending.contains(&change.id);
+
+    if change.deleted {
+        // A local edit outlives a remote delete; it'll come back on the other machines.
+        // Otherwise the clip goes to the trash here too, so it can still be restored.
+        if let Some((_, false)) = local.filter(|_| !edited_here) {
+            sqlx::query("UPDATE clips SET deleted_at = COALESCE(deleted_at, ?) WHERE id = ?")
+                .bind(&change.changed_at)
+                .bind(&change.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        return Ok(false);
+    }
+
+    let Some(content) = &change.content else { return Ok(false) };
+    match local {
+        Some((_, true)) => {}
+        Some((local_content, false)) if edited_here && &local_content != content => {
+            sqlx::query(
+                "INSERT INTO sync_conflicts (clip_id, local_content, remote_content, remote_device, detected_at)
+                 SELECT ?1, ?2, ?3, ?4, ?5 WHERE NOT EXISTS (
+                     SELECT 1 FROM sync_conflicts WHERE clip_id = ?1 AND local_content = ?2 AND remote_content = ?3
+                 )"
+            )
+            .bind(&change.id)
+            .bind(&local_content)
+            .bind(content)
+            .bind(remote_device)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+            return Ok(true);
+        }
+        Some(_) => {
+            sqlx::query(
+                "UPDATE clips SET content = ?, search_content = ?,
+                 clip_type = CASE WHEN clip_type IN ('text', 'url') THEN ? ELSE clip_type END,
+                 canonical_url = CASE WHEN clip_type IN ('text', 'url') THEN ? ELSE canonical_url END,
+                 detected_kind = CASE WHEN clip_type IN ('text', 'url') THEN ? ELSE detected_kind END,
+                 is_favorite = COALESCE(?, is_favorite), pinned = COALESCE(?, pinned), deleted_at = NULL
+                 WHERE id = ?"
+            )
+            .bind(content)
+            .bind(normalize_text(content))
+            .bind(classify::text_clip_type(content))
+            .bind(urls::canonical_url(content).unwrap_or_default())
+            .bind(classify::detect_kind(content))
+            .bind(change.is_favorite)
+            .bind(change.pinned)
+            .bind(&change.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+        None => {
+            sqlx::query(
+                "INSERT INTO clips (id, content, created_at, is_favorite, pinned, search_content, clip_type, image_path, canonical_url, detected_kind)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, NULL, ?, ?)"
+            )
+            .bind(&change.id)
+            .bind(content)
+            .bind(change.created_at.clone().unwrap_or_else(|| change.changed_at.clone()))
+            .bind(change.is_favorite.unwrap_or(false))
+            .bind(change.pinned.unwrap_or(false))
+            .bind(normalize_text(content))
+            .bind(classify::text_clip_type(content))
+            .bind(urls::canonical_url(content).unwrap_or_default())
+            .bind(classify::detect_kind(content))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(false)
+}
+
+/// Applies changelogs from other machines that haven't been applied yet,
+/// oldest first. Returns (changes applied, conflicts found).
+async fn pull(pool: &Pool<Sqlite>, destination: &Destination, passphrase: &str, device_id: &str) -> Result<(usize, usize), String> {
+    let applied: HashSet<String> = sqlx::query_as::<_, (String,)>("SELECT name FROM sync_applied")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(name,)| name)
+        .collect();
+    let mut names: Vec<String> = destination
+        .list(FILE_PREFIX, FILE_SUFFIX)
+        .await?
+        .into_iter()
+        .filter(|name| !applied.contains(name))
+        .filter(|name| parse_name(name).is_some_and(|(device, _)| device != device_id))
+        .collect();
+    names.sort_by(|a, b| parse_name(a).map(|(_, t)| t).cmp(&parse_name(b).map(|(_, t)| t)));
+
+    let (mut changes, mut conflicts) = (0, 0);
+    for name in names {
+        let data = destination.get(&name).await?;
+        // A wrong passphrase on one machine or a half-synced upload shouldn't hold
+        // up everything after it; the file is retried on the next run
+        let changelog = match crypto::decrypt(passphrase, &data)
+            .and_then(|json| serde_json::from_slice::<Changelog>(&json).map_err(|e| e.to_string()))
+        {
+            Ok(changelog) => changelog,
+            Err(e) => {
+                eprintln!("Skipping changelog {}: {}", name, e);
+                continue;
+            }
+        };
+
+        let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+        let pending: HashSet<String> = sqlx::query_as::<_, (String,)>("SELECT clip_id FROM sync_outbox")
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|(id,)| id)
+            .collect();
+        for change in &changelog.changes {
+            if apply_change(&mut tx, change, &pending, &changelog.device_name).await? {
+                conflicts += 1;
+            } else {
+                changes += 1;
+            }
+            if !change.deleted {
+                sqlx::query("INSERT OR IGNORE INTO sync_sent (clip_id) VALUES (?)")
+                    .bind(&change.id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            // The triggers queued what we just applied; sending it back would echo forever
+            if !pending.contains(&change.id) {
+                sqlx::query("DELETE FROM sync_outbox WHERE clip_id = ?")
+                    .bind(&change.id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        sqlx::query("INSERT OR REPLACE INTO sync_applied (name, applied_at) VALUES (?, ?)")
+            .bind(&name)
+            .bind(Utc::now().to_rfc3339())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        tx.commit().await.map_err(|e| e.to_string())?;
+    }
+    Ok((changes, conflicts))
+}
+
+/// Removes this machine's changelogs older than `KEEP_CHANGELOG_DAYS`.
+async fn prune(destination: &Destination, device_id: &str) -> Result<(), String> {
+    let cutoff = (Utc::now() - chrono::Duration::days(KEEP_CHANGELOG_DAYS)).format(NAME_TIME_FORMAT).to_string();
+    for name in destination.list(FILE_PREFIX, FILE_SUFFIX).await? {
+        if parse_name(&name).is_some_and(|(device, time)| device == device_id && time < cutoff.as_str()) {
+            if let Err(e) = destination.delete(&name).await {
+                eprintln!("Failed to remove old changelog {}: {}", name, e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Pulls other machines' changes, then pushes ours. Pulling first means an
+/// edit made on both sides is caught as a conflict before ours goes out.
+pub async fn run_sync(app: &AppHandle) -> Result<SyncReport, String> {
+    let state = app.try_state::<DbState>().ok_or_else(|| "Database is not available".to_string())?;
+    let pool = &state.pool;
+    let config = load_config(pool).await;
+    let destination = destination(&config)?;
+    let passphrase = passphrase()?;
+    let device_id = device_id(pool).await?;
+    let device_name = config.device_name.clone().unwrap_or_else(|| default_device_name(&device_id));
+
+    let (applied, conflicts) = pull(pool, &destination, &passphrase, &device_id).await?;
+    let pushed = push(pool, &destination, &passphrase, &device_id, &device_name).await?;
+    prune(&destination, &device_id).await?;
+
+    if applied > 0 || conflicts > 0 {
+        state.recent.invalidate();
+        let _ = app.emit("clipboard-changed", ());
+    }
+    Ok(SyncReport { pushed, applied, conflicts })
+}
+
+#[tauri::command]
+pub async fn get_sync_config(state: tauri::State<'_, DbState>) -> Result<SyncConfig, String> {
+    Ok(load_config(&state.pool).await)
+}
+
+/// Saves the sync settings. The passphrase (the same on every machine) and
+/// the WebDAV password are stored separately through `set_secret`
+/// ("sync_passphrase", "sync_password"). Turning sync on queues the existing
+/// history in scope.
+#[tauri::command]
+pub async fn set_sync_config(state: tauri::State<'_, DbState>, config: SyncConfig) -> Result<(), String> {
+    let clean = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let destination = clean(config.destination);
+    if let Some(bad) = destination.as_deref().filter(|d| !DESTINATIONS.contains(d)) {
+        return Err(format!("Unknown sync destination: {}", bad));
+    }
+    let was_enabled = enabled(&state.pool).await;
+
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    for (key, value) in [
+        ("sync_enabled", Some(config.enabled.to_string())),
+        ("sync_destination", destination),
+        ("sync_location", clean(config.location)),
+        ("sync_username", clean(config.username)),
+        ("sync_device_name", clean(config.device_name)),
+    ] {
+        sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")
+            .bind(key)
+            .bind(value.unwrap_or_default())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    if config.enabled && !was_enabled {
+        queue_existing(&state.pool).await?;
+    }
+    Ok(())
+}
+
+/// Syncs right away instead of waiting for the scheduler.
+#[tauri::command]
+pub async fn sync_now(app_handle: AppHandle) -> Result<SyncReport, String> {
+    run_sync(&app_handle).await
+}
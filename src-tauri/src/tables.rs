@@ -0,0 +1,136 @@
+// Tables copied from spreadsheets and docs: TSV (what Excel, Numbers and
+// Sheets put on the clipboard), CSV and Markdown pipe tables. A single row,
+// a single column or the transposed table can be copied back as TSV without
+// pasting the whole thing into a spreadsheet first.
+
+use serde::{Serialize, Deserialize};
+use arboard::Clipboard;
+
+use crate::classify::is_text_type;
+use crate::{restore, usage, DbState};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TableShape {
+    format: &'static str, // "tsv", "csv" or "markdown"
+    rows: usize,
+    columns: usize,
+    header: Vec<String>, // first row, for labelling column actions
+}
+
+/// Splits one CSV line, honouring double-quoted fields and `""` escapes.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+fn markdown_cells(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    line.split('|').map(|cell| cell.trim().to_string()).collect()
+}
+
+fn is_markdown_separator(line: &str) -> bool {
+    markdown_cells(line).iter().all(|cell| !cell.is_empty() && cell.chars().all(|c| matches!(c, '-' | ':' | ' ')))
+}
+
+/// Parses `text` as a table of at least two rows and two columns. Short rows
+/// are padded so every row has the same number of cells.
+fn parse(text: &str) -> Option<(&'static str, Vec<Vec<String>>)> {
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    if lines.len() < 2 {
+        return None;
+    }
+
+    let (format, mut rows): (&'static str, Vec<Vec<String>>) = if lines.iter().all(|l| l.contains('\t')) {
+        ("tsv", lines.iter().map(|l| l.split('\t').map(|c| c.trim().to_string()).collect()).collect())
+    } else if lines.iter().all(|l| l.trim_start().starts_with('|')) {
+        ("markdown", lines.iter().filter(|l| !is_markdown_separator(l)).map(|l| markdown_cells(l)).collect())
+    } else {
+        // CSV only when every line splits into the same number of fields,
+        // otherwise any prose with a comma would look like a table
+        let rows: Vec<Vec<String>> = lines.iter().map(|l| csv_fields(l).into_iter().map(|c| c.trim().to_string()).collect()).collect();
+        if rows.iter().any(|r| r.len() != rows[0].len()) {
+            return None;
+        }
+        ("csv", rows)
+    };
+
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if rows.len() < 2 || columns < 2 {
+        return None;
+    }
+    for row in &mut rows {
+        row.resize(columns, String::new());
+    }
+    Some((format, rows))
+}
+
+/// Tabs and line breaks inside a cell would break the TSV grid.
+fn to_tsv(rows: &[Vec<String>]) -> String {
+    rows.iter()
+        .map(|row| row.iter().map(|cell| cell.replace(['\t', '\r', '\n'], " ")).collect::<Vec<_>>().join("\t"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+async fn load_table(state: &DbState, id: &str) -> Result<Option<(&'static str, Vec<Vec<String>>)>, String> {
+    let clip: Option<(String, String)> = sqlx::query_as("SELECT content, clip_type FROM clips WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (content, clip_type) = clip.ok_or_else(|| "Clip not found".to_string())?;
+    Ok(if is_text_type(&clip_type) { parse(&content) } else { None })
+}
+
+/// Rows, columns and header of a tabular clip, or None when the clip isn't
+/// a table; the list uses it to offer the row/column actions.
+#[tauri::command]
+pub async fn get_table_shape(state: tauri::State<'_, DbState>, id: String) -> Result<Option<TableShape>, String> {
+    Ok(load_table(&state, &id).await?.map(|(format, rows)| TableShape {
+        format,
+        rows: rows.len(),
+        columns: rows[0].len(),
+        header: rows[0].clone(),
+    }))
+}
+
+/// Copies part of a tabular clip as TSV: `mode` "row" or "column" with a
+/// zero-based `index`, or "transpose" for the whole table turned on its side.
+/// Returns the copied text.
+#[tauri::command]
+pub async fn copy_table_part(state: tauri::State<'_, DbState>, id: String, mode: String, index: Option<usize>) -> Result<String, String> {
+    let (_, rows) = load_table(&state, &id).await?.ok_or_else(|| "This clip isn't a table".to_string())?;
+    let columns = rows[0].len();
+
+    let part: Vec<Vec<String>> = match (mode.as_str(), index) {
+        ("row", Some(i)) if i < rows.len() => vec![rows[i].clone()],
+        ("column", Some(i)) if i < columns => rows.iter().map(|row| vec![row[i].clone()]).collect(),
+        ("row", _) | ("column", _) => return Err(format!("No {} {} in this table", mode, index.map_or("index".to_string(), |i| i.to_string()))),
+        ("transpose", _) => (0..columns).map(|i| rows.iter().map(|row| row[i].clone()).collect()).collect(),
+        (other, _) => return Err(format!("Unknown table mode: {} (expected \"row\", \"column\" or \"transpose\")", other)),
+    };
+
+    let text = to_tsv(&part);
+    let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
+    restore::remember_current(&mut clipboard);
+    clipboard.set_text(text.clone()).map_err(|e| e.to_string())?;
+    usage::record_usage(&state.pool, &id, "copy_text").await?;
+    Ok(text)
+}
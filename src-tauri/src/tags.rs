@@ -0,0 +1,225 @@
+// Free-form tags on clips. Names are unique case-insensitively and a tag only
+// exists while at least one clip carries it. `clip_tags` rows go away with
+// their clip through a trigger, so the delete paths don't need to know.
+
+use sqlx::{Pool, Sqlite, SqliteConnection};
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+use crate::DbState;
+
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS tags (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+        created_at DATETIME NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS clip_tags (
+        clip_id TEXT NOT NULL,
+        tag_id TEXT NOT NULL,
+        PRIMARY KEY (clip_id, tag_id)
+    )",
+    "CREATE INDEX IF NOT EXISTS idx_clip_tags_tag_id ON clip_tags (tag_id)",
+    "CREATE TRIGGER IF NOT EXISTS clip_tags_delete AFTER DELETE ON clips BEGIN
+        DELETE FROM clip_tags WHERE clip_id = old.id;
+    END",
+];
+
+// Bulk statements bind ids in chunks, well under SQLite's variable limit
+const IDS_PER_STATEMENT: usize = 500;
+
+/// Filter condition on `clips` for clips carrying the tag bound to `?`.
+pub const HAS_TAG: &str = "clips.id IN (SELECT ct.clip_id FROM clip_tags ct JOIN tags t ON t.id = ct.tag_id WHERE t.name = ?)";
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Tag {
+    id: String,
+    name: String,
+    clip_count: i64,
+}
+
+pub async fn init_tags(pool: &Pool<Sqlite>) -> Result<(), String> {
+    for statement in SCHEMA {
+        sqlx::query(statement).execute(pool).await.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn clean_name(name: &str) -> Result<String, String> {
+    let name = name.trim().trim_start_matches('#').trim();
+    if name.is_empty() {
+        return Err("Tag name can't be empty".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// Id of the tag called `name`, creating it if needed.
+pub async fn tag_id(conn: &mut SqliteConnection, name: &str) -> Result<String, String> {
+    sqlx::query("INSERT OR IGNORE INTO tags (id, name, created_at) VALUES (?, ?, ?)")
+        .bind(Uuid::new_v4().to_string())
+        .bind(name)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (id,): (String,) = sqlx::query_as("SELECT id FROM tags WHERE name = ?")
+        .bind(name)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+/// Drops the tag if no clip carries it anymore.
+async fn drop_if_unused(conn: &mut SqliteConnection, name: &str) -> Result<(), String> {
+    sqlx::query("DELETE FROM tags WHERE name = ? AND id NOT IN (SELECT tag_id FROM clip_tags)")
+        .bind(name)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Tags a clip, creating the tag on first use. Tagging twice is a no-op.
+/// Returns the tag id.
+#[tauri::command]
+pub async fn add_tag(state: tauri::State<'_, DbState>, clip_id: String, name: String) -> Result<String, String> {
+    let name = clean_name(&name)?;
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+
+    let exists: Option<(i32,)> = sqlx::query_as("SELECT 1 FROM clips WHERE id = ?")
+        .bind(&clip_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    if exists.is_none() {
+        return Err("Clip not found".to_string());
+    }
+
+    let tag_id = tag_id(&mut tx, &name).await?;
+    sqlx::query("INSERT OR IGNORE INTO clip_tags (clip_id, tag_id) VALUES (?, ?)")
+        .bind(&clip_id)
+        .bind(&tag_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(tag_id)
+}
+
+/// Untags a clip; the tag itself is dropped once no clip carries it.
+#[tauri::command]
+pub async fn remove_tag(state: tauri::State<'_, DbState>, clip_id: String, name: String) -> Result<(), String> {
+    let name = clean_name(&name)?;
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query("DELETE FROM clip_tags WHERE clip_id = ? AND tag_id IN (SELECT id FROM tags WHERE name = ?)")
+        .bind(&clip_id)
+        .bind(&name)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    drop_if_unused(&mut tx, &name).await?;
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+/// Tags many clips at once, all or nothing: an unknown id fails the whole
+/// batch. Returns how many clips newly got the tag.
+#[tauri::command]
+pub async fn add_tag_to_clips(state: tauri::State<'_, DbState>, ids: Vec<String>, tag: String) -> Result<u64, String> {
+    let name = clean_name(&tag)?;
+    let mut ids = ids;
+    ids.sort();
+    ids.dedup();
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    let mut found = 0;
+    for chunk in ids.chunks(IDS_PER_STATEMENT) {
+        let count_sql = format!("SELECT COUNT(*) FROM clips WHERE id IN ({})", placeholders(chunk.len()));
+        let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
+        for id in chunk {
+            count_query = count_query.bind(id);
+        }
+        found += count_query.fetch_one(&mut *tx).await.map_err(|e| e.to_string())?.0 as usize;
+    }
+    if found != ids.len() {
+        return Err(format!("{} of the clips were not found", ids.len() - found));
+    }
+
+    let tag_id = tag_id(&mut tx, &name).await?;
+    let mut tagged = 0;
+    for chunk in ids.chunks(IDS_PER_STATEMENT) {
+        let insert_sql = format!(
+            "INSERT OR IGNORE INTO clip_tags (clip_id, tag_id) SELECT id, ? FROM clips WHERE id IN ({})",
+            placeholders(chunk.len())
+        );
+        let mut insert = sqlx::query(&insert_sql).bind(&tag_id);
+        for id in chunk {
+            insert = insert.bind(id);
+        }
+        tagged += insert.execute(&mut *tx).await.map_err(|e| e.to_string())?.rows_affected();
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(tagged)
+}
+
+/// Untags many clips at once. Returns how many clips lost the tag.
+#[tauri::command]
+pub async fn remove_tag_from_clips(state: tauri::State<'_, DbState>, ids: Vec<String>, tag: String) -> Result<u64, String> {
+    let name = clean_name(&tag)?;
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    let mut tx = state.pool.begin().await.map_err(|e| e.to_string())?;
+    let mut untagged = 0;
+    for chunk in ids.chunks(IDS_PER_STATEMENT) {
+        let delete_sql = format!(
+            "DELETE FROM clip_tags WHERE tag_id IN (SELECT id FROM tags WHERE name = ?) AND clip_id IN ({})",
+            placeholders(chunk.len())
+        );
+        let mut delete = sqlx::query(&delete_sql).bind(&name);
+        for id in chunk {
+            delete = delete.bind(id);
+        }
+        untagged += delete.execute(&mut *tx).await.map_err(|e| e.to_string())?.rows_affected();
+    }
+    drop_if_unused(&mut tx, &name).await?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+    Ok(untagged)
+}
+
+/// Names of the tags on one clip, alphabetically.
+pub async fn clip_tag_names(pool: &Pool<Sqlite>, clip_id: &str) -> Result<Vec<String>, String> {
+    let rows: Vec<(String,)> = sqlx::query_as(
+        "SELECT t.name FROM tags t JOIN clip_tags ct ON ct.tag_id = t.id WHERE ct.clip_id = ? ORDER BY t.name COLLATE NOCASE"
+    )
+    .bind(clip_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(rows.into_iter().map(|(name,)| name).collect())
+}
+
+/// All tags in use with their clip counts, or just those of one clip.
+#[tauri::command]
+pub async fn get_tags(state: tauri::State<'_, DbState>, clip_id: Option<String>) -> Result<Vec<Tag>, String> {
+    sqlx::query_as::<_, Tag>(
+        "SELECT t.id, t.name, COUNT(ct.clip_id) AS clip_count
+         FROM tags t JOIN clip_tags ct ON ct.tag_id = t.id
+         WHERE ?1 IS NULL OR t.id IN (SELECT tag_id FROM clip_tags WHERE clip_id = ?1)
+         GROUP BY t.id
+         ORDER BY t.name COLLATE NOCASE"
+    )
+    .bind(clip_id.filter(|id| !id.is_empty()))
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,146 @@
+// Dynamic text templates: `{date:%Y-%m-%d}`, `{time}`, `{counter:INV-%04d}`.
+// Counters are keyed by their spec and persisted in `template_counters`.
+
+use sqlx::{Pool, Sqlite};
+use chrono::format::{Item, StrftimeItems};
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use arboard::Clipboard;
+
+use crate::{restore, DbState};
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct TemplateCounter {
+    name: String,
+    value: i64,
+}
+
+/// Formats with chrono but refuses invalid specs, which would otherwise panic on Display.
+fn format_now(spec: &str) -> Option<String> {
+    let items = StrftimeItems::new(spec);
+    if items.clone().any(|item| matches!(item, Item::Error)) {
+        return None;
+    }
+    Some(Local::now().format_with_items(items).to_string())
+}
+
+const MAX_COUNTER_WIDTH: usize = 32;
+
+/// Minimal printf-style integer formatting: the first `%d`, `%5d` or `%05d` is replaced.
+fn format_counter(spec: &str, value: i64) -> String {
+    if let Some(start) = spec.find('%') {
+        let rest = &spec[start + 1..];
+        if let Some(d) = rest.find('d') {
+            let flags = &rest[..d];
+            if flags.chars().all(|c| c.is_ascii_digit()) {
+                let width = flags.parse::<usize>().unwrap_or(0).min(MAX_COUNTER_WIDTH);
+                let number = if flags.starts_with('0') {
+                    format!("{:0width$}", value, width = width)
+                } else {
+                    format!("{:width$}", value, width = width)
+                };
+                return format!("{}{}{}", &spec[..start], number, &rest[d + 1..]);
+            }
+        }
+    }
+    format!("{}{}", spec, value)
+}
+
+async fn next_counter(pool: &Pool<Sqlite>, name: &str) -> Result<i64, String> {
+    let (value,): (i64,) = sqlx::query_as(
+        "INSERT INTO template_counters (name, value) VALUES (?, 1)
+         ON CONFLICT(name) DO UPDATE SET value = value + 1
+         RETURNING value"
+    )
+    .bind(name)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(value)
+}
+
+/// Expands a single placeholder body (the text between the braces).
+/// Unknown placeholders return None and are left untouched.
+async fn expand_placeholder(pool: &Pool<Sqlite>, body: &str) -> Result<Option<String>, String> {
+    let (kind, spec) = match body.split_once(':') {
+        Some((kind, spec)) => (kind, Some(spec)),
+        None => (body, None),
+    };
+
+    let value = match kind {
+        "date" => format_now(spec.unwrap_or("%Y-%m-%d")),
+        "time" => format_now(spec.unwrap_or("%H:%M")),
+        "datetime" => format_now(spec.unwrap_or("%Y-%m-%d %H:%M")),
+        "counter" => {
+            let spec = spec.unwrap_or("%d");
+            let value = next_counter(pool, spec).await?;
+            Some(format_counter(spec, value))
+        }
+        _ => None,
+    };
+    Ok(value)
+}
+
+pub async fn expand(pool: &Pool<Sqlite>, template: &str) -> Result<String, String> {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(open) = rest.find('{') {
+        output.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        match after.find('}') {
+            Some(close) => {
+                let body = &after[..close];
+                match expand_placeholder(pool, body).await? {
+                    Some(value) => output.push_str(&value),
+                    None => {
+                        output.push('{');
+                        output.push_str(body);
+                        output.push('}');
+                    }
+                }
+                rest = &after[close + 1..];
+            }
+            None => {
+                output.push_str(&rest[open..]);
+                rest = "";
+            }
+        }
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+/// Expands the template and, when `copy` is set, puts the result on the clipboard.
+#[tauri::command]
+pub async fn expand_template(state: tauri::State<'_, DbState>, template: String, copy: Option<bool>) -> Result<String, String> {
+    let expanded = expand(&state.pool, &template).await?;
+
+    if copy.unwrap_or(false) {
+        let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
+        restore::remember_current(&mut clipboard);
+        clipboard.set_text(expanded.clone()).map_err(|e| e.to_string())?;
+    }
+    Ok(expanded)
+}
+
+#[tauri::command]
+pub async fn get_template_counters(state: tauri::State<'_, DbState>) -> Result<Vec<TemplateCounter>, String> {
+    sqlx::query_as::<_, TemplateCounter>("SELECT name, value FROM template_counters ORDER BY name")
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Sets a counter's current value; the next expansion yields `value + 1`.
+#[tauri::command]
+pub async fn set_template_counter(state: tauri::State<'_, DbState>, name: String, value: i64) -> Result<(), String> {
+    sqlx::query("INSERT OR REPLACE INTO template_counters (name, value) VALUES (?, ?)")
+        .bind(name)
+        .bind(value)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,131 @@
+// Quick text transformations on a clip (case, whitespace, line sorting),
+// applied in place or copied to the clipboard leaving the clip as it was.
+
+use tauri::AppHandle;
+use arboard::Clipboard;
+use std::collections::HashSet;
+
+use crate::classify::is_text_type;
+use crate::{restore, set_clip_content, usage, DbState};
+
+const OPERATIONS: &[&str] = &[
+    "uppercase", "lowercase", "title_case", "trim", "collapse_blank_lines", "sort_lines", "dedupe_lines",
+];
+
+fn title_case(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut at_word_start = true;
+    for c in text.chars() {
+        if c.is_alphanumeric() || c == '\'' {
+            if at_word_start {
+                result.extend(c.to_uppercase());
+            } else {
+                result.extend(c.to_lowercase());
+            }
+            at_word_start = false;
+        } else {
+            result.push(c);
+            at_word_start = true;
+        }
+    }
+    result
+}
+
+/// Runs of blank (or whitespace-only) lines become a single empty line.
+fn collapse_blank_lines(text: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines() {
+        let blank = line.trim().is_empty();
+        if blank && lines.last().is_some_and(|l| l.is_empty()) {
+            continue;
+        }
+        lines.push(if blank { "" } else { line });
+    }
+    lines.join("\n")
+}
+
+/// Applies `op`, one of `OPERATIONS`, to `text`.
+pub fn apply(text: &str, op: &str) -> Result<String, String> {
+    let result = match op {
+        "uppercase" => text.to_uppercase(),
+        "lowercase" => text.to_lowercase(),
+        "title_case" => title_case(text),
+        // Both ends of the text and the end of every line
+        "trim" => text.trim().lines().map(str::trim_end).collect::<Vec<_>>().join("\n"),
+        "collapse_blank_lines" => collapse_blank_lines(text),
+        "sort_lines" => {
+            let mut lines: Vec<&str> = text.lines().collect();
+            lines.sort_by_key(|l| l.to_lowercase());
+            lines.join("\n")
+        }
+        // Keeps the first occurrence of each line, in order
+        "dedupe_lines" => {
+            let mut seen = HashSet::new();
+            text.lines().filter(|l| seen.insert(*l)).collect::<Vec<_>>().join("\n")
+        }
+        _ => return Err(format!("Unknown transformation: {} (expected one of {})", op, OPERATIONS.join(", "))),
+    };
+    Ok(result)
+}
+
+/// Transforms a text clip with `op`. With `target` "update" (the default) the
+/// clip is changed; with "copy" only the clipboard gets the result. Returns
+/// the transformed text.
+#[tauri::command]
+pub async fn transform_clip(app_handle: AppHandle, state: tauri::State<'_, DbState>, id: String, op: String, target: Option<String>) -> Result<String, String> {
+    let clip: Option<(String, String)> = sqlx::query_as("SELECT content, clip_type FROM clips WHERE id = ?")
+        .bind(&id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let (content, clip_type) = clip.ok_or_else(|| "Clip not found".to_string())?;
+    if !is_text_type(&clip_type) {
+        return Err("Only text clips can be transformed".to_string());
+    }
+
+    let transformed = apply(&content, op.trim())?;
+    match target.as_deref().unwrap_or("update") {
+        "update" => {
+            if transformed != content {
+                set_clip_content(&app_handle, &state, &id, transformed.clone()).await?;
+            }
+        }
+        "copy" => {
+            let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
+            restore::remember_current(&mut clipboard);
+            clipboard.set_text(transformed.clone()).map_err(|e| e.to_string())?;
+            usage::record_usage(&state.pool, &id, "copy_text").await?;
+        }
+        other => return Err(format!("Unknown target: {} (expected \"update\" or \"copy\")", other)),
+    }
+    Ok(transformed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_case() {
+        assert_eq!(apply("Hello World", "uppercase").unwrap(), "HELLO WORLD");
+        assert_eq!(apply("Hello World", "lowercase").unwrap(), "hello world");
+        assert_eq!(apply("it's a TEST-case", "title_case").unwrap(), "It's A Test-Case");
+    }
+
+    #[test]
+    fn trims_and_collapses_whitespace() {
+        assert_eq!(apply("  a  \nb \t\n\n", "trim").unwrap(), "a\nb");
+        assert_eq!(apply("a\n\n \n\t\nb\n\nc", "collapse_blank_lines").unwrap(), "a\n\nb\n\nc");
+    }
+
+    #[test]
+    fn sorts_and_dedupes_lines() {
+        assert_eq!(apply("b\nA\nc", "sort_lines").unwrap(), "A\nb\nc");
+        assert_eq!(apply("b\na\nb\na", "dedupe_lines").unwrap(), "b\na");
+    }
+
+    #[test]
+    fn rejects_unknown_operations() {
+        assert!(apply("text", "reverse").is_err());
+    }
+}
@@ -0,0 +1,140 @@
+// Trash: deleting a clip only stamps `deleted_at`, so an accidental delete
+// can be undone. Trashed clips drop out of every list and search, and are
+// deleted for good (image files included) after `trash_days` (default 30),
+// from the scheduler, or when the trash is emptied.
+
+use tauri::{AppHandle, Emitter};
+use sqlx::{Pool, Sqlite};
+use chrono::prelude::*;
+
+use crate::{images, locking, setting_value, Clip, DbState, CLIP_COLUMNS};
+
+pub const DEFAULT_TRASH_DAYS: i64 = 30;
+pub const MAX_TRASH_DAYS: i64 = 3650;
+/// Condition on `clips` for clips that are not in the trash.
+pub const NOT_DELETED: &str = "deleted_at IS NULL";
+
+pub async fn trash_days(pool: &Pool<Sqlite>) -> i64 {
+    setting_value(pool, "trash_days").await.and_then(|v| v.parse().ok()).filter(|d| (1..=MAX_TRASH_DAYS).contains(d)).unwrap_or(DEFAULT_TRASH_DAYS)
+}
+
+/// Moves a clip to the trash. Locked clips can't be deleted, so they can't
+/// be trashed either.
+pub async fn trash_clip(state: &DbState, id: &str) -> Result<(), String> {
+    locking::ensure_unlocked(&state.pool, id).await?;
+    sqlx::query("UPDATE clips SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL")
+        .bind(Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    state.recent.remove(id);
+    Ok(())
+}
+
+/// Deletes clips for good, with their usage, relations and image files, in
+/// one transaction. Locked clips are skipped. Returns how many were deleted.
+pub async fn delete_forever(pool: &Pool<Sqlite>, ids: &[String]) -> Result<usize, String> {
+    let mut doomed: Vec<(String, Option<String>)> = Vec::new();
+    for id in ids {
+        let row: Option<(String, Option<String>)> = sqlx::query_as("SELECT id, image_path FROM clips WHERE id = ? AND locked = 0")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        doomed.extend(row);
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    for (id, _) in &doomed {
+        for statement in [
+            "DELETE FROM clip_usage WHERE clip_id = ?",
+            "DELETE FROM clip_relations WHERE from_id = ?1 OR to_id = ?1",
+            "DELETE FROM clips WHERE id = ?",
+        ] {
+            sqlx::query(statement)
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    // Files go only after the rows are gone, as in purge_where
+    for path in doomed.iter().filter_map(|(_, path)| path.as_ref()) {
+        let _ = images::remove_image(path);
+    }
+    Ok(doomed.len())
+}
+
+async fn trashed_ids(pool: &Pool<Sqlite>, before: Option<String>) -> Result<Vec<String>, String> {
+    let rows: Vec<(String,)> = sqlx::query_as("SELECT id FROM clips WHERE deleted_at IS NOT NULL AND (? IS NULL OR deleted_at < ?)")
+        .bind(&before)
+        .bind(&before)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(rows.into_iter().map(|(id,)| id).collect())
+}
+
+/// Deletes clips that have been in the trash longer than `trash_days`.
+/// Run from the scheduler.
+pub async fn purge_expired(app: &AppHandle, pool: &Pool<Sqlite>) -> Result<usize, String> {
+    let cutoff = chrono::Duration::try_days(trash_days(pool).await)
+        .and_then(|days| Utc::now().checked_sub_signed(days))
+        .ok_or_else(|| "trash_days is out of range".to_string())?
+        .to_rfc3339();
+    let deleted = delete_forever(pool, &trashed_ids(pool, Some(cutoff)).await?).await?;
+    if deleted > 0 {
+        let _ = app.emit("trash-changed", ());
+    }
+    Ok(deleted)
+}
+
+/// Trashed clips, most recently deleted first.
+#[tauri::command]
+pub async fn get_deleted_clips(state: tauri::State<'_, DbState>, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<Clip>, String> {
+    let query = format!("SELECT {} FROM clips WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC, id DESC LIMIT ? OFFSET ?", CLIP_COLUMNS);
+    sqlx::query_as::<_, Clip>(&query)
+        .bind(limit.unwrap_or(100))
+        .bind(offset.unwrap_or(0))
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Takes a clip out of the trash, back to its place in history.
+#[tauri::command]
+pub async fn restore_clip(app_handle: AppHandle, state: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
+    let result = sqlx::query("UPDATE clips SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL")
+        .bind(&id)
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    if result.rows_affected() == 0 {
+        return Err("Clip is not in the trash".to_string());
+    }
+    state.recent.invalidate();
+    let _ = app_handle.emit("clipboard-changed", ());
+    let _ = app_handle.emit("trash-changed", ());
+    Ok(())
+}
+
+/// Deletes one trashed clip for good.
+#[tauri::command]
+pub async fn delete_clip_forever(app_handle: AppHandle, state: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
+    locking::ensure_unlocked(&state.pool, &id).await?;
+    delete_forever(&state.pool, std::slice::from_ref(&id)).await?;
+    state.recent.remove(&id);
+    let _ = app_handle.emit("trash-changed", ());
+    Ok(())
+}
+
+/// Deletes everything in the trash for good. Returns how many clips went.
+#[tauri::command]
+pub async fn empty_trash(app_handle: AppHandle, state: tauri::State<'_, DbState>) -> Result<usize, String> {
+    let deleted = delete_forever(&state.pool, &trashed_ids(&state.pool, None).await?).await?;
+    let _ = app_handle.emit("trash-changed", ());
+    Ok(deleted)
+}
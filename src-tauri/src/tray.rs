@@ -0,0 +1,201 @@
+// What a left click on the tray icon does, chosen with the `tray_left_click`
+// setting. Defaults to toggling the main window, the original behavior.
+// The tray menu lists the newest clips and is rebuilt on "clipboard-changed".
+
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use sqlx::{Pool, Sqlite};
+use serde::{Serialize, Deserialize};
+
+use crate::{capture, paste, restore, setting_value, window, Clip, DbState, CLIP_COLUMNS};
+
+pub const TRAY_ID: &str = "main";
+const MENU_CLIPS: usize = 5;
+const MENU_LABEL_CHARS: usize = 40;
+const CLIP_ITEM_PREFIX: &str = "clip:";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayAction {
+    ToggleWindow,
+    QuickPicker, // shows the window and asks the UI to focus search
+    CopyLastClip,
+    PauseCapture, // toggles pause
+}
+
+impl TrayAction {
+    fn parse(value: &str) -> Option<TrayAction> {
+        match value {
+            "toggle_window" => Some(TrayAction::ToggleWindow),
+            "quick_picker" => Some(TrayAction::QuickPicker),
+            "copy_last_clip" => Some(TrayAction::CopyLastClip),
+            "pause_capture" => Some(TrayAction::PauseCapture),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            TrayAction::ToggleWindow => "toggle_window",
+            TrayAction::QuickPicker => "quick_picker",
+            TrayAction::CopyLastClip => "copy_last_clip",
+            TrayAction::PauseCapture => "pause_capture",
+        }
+    }
+}
+
+pub async fn tray_action(pool: &Pool<Sqlite>) -> TrayAction {
+    setting_value(pool, "tray_left_click")
+        .await
+        .and_then(|v| TrayAction::parse(&v))
+        .unwrap_or(TrayAction::ToggleWindow)
+}
+
+/// Shows and focuses the main window, remembering the app it was opened from.
+pub fn show_window(app: &AppHandle) {
+    paste::remember_previous_app();
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn toggle_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        if window.is_visible().unwrap_or(false) {
+            let _ = window.hide();
+        } else {
+            show_window(app);
+        }
+    }
+}
+
+/// Shows the main window and asks the UI to focus search.
+pub fn open_quick_picker(app: &AppHandle) {
+    show_window(app);
+    let _ = app.emit("quick-picker", ());
+}
+
+/// Puts the most recent clip back on the clipboard.
+async fn copy_last_clip(pool: &Pool<Sqlite>) -> Result<(), String> {
+    let last: Option<(String,)> = sqlx::query_as("SELECT id FROM clips WHERE deleted_at IS NULL ORDER BY created_at DESC LIMIT 1")
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    match last {
+        Some((id,)) => restore::copy_clip(pool, &id).await,
+        None => Ok(()),
+    }
+}
+
+/// Runs the configured left-click action. Window actions run right away;
+/// anything that needs the database is spawned off the event loop.
+pub fn on_left_click(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = app.try_state::<DbState>() else {
+            toggle_window(&app);
+            return;
+        };
+        let result = match tray_action(&state.pool).await {
+            TrayAction::ToggleWindow => {
+                toggle_window(&app);
+                Ok(())
+            }
+            TrayAction::QuickPicker => {
+                open_quick_picker(&app);
+                Ok(())
+            }
+            TrayAction::CopyLastClip => copy_last_clip(&state.pool).await,
+            TrayAction::PauseCapture => capture::set_paused(&app, !capture::is_paused()).await,
+        };
+        if let Err(e) = result {
+            eprintln!("Tray action failed: {}", e);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_tray_action(state: tauri::State<'_, DbState>) -> Result<String, String> {
+    Ok(tray_action(&state.pool).await.as_str().to_string())
+}
+
+#[tauri::command]
+pub async fn set_tray_action(state: tauri::State<'_, DbState>, action: String) -> Result<(), String> {
+    let action = TrayAction::parse(&action).ok_or_else(|| format!("Unknown tray action: {}", action))?;
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES ('tray_left_click', ?)")
+        .bind(action.as_str())
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn menu_label(clip: &Clip) -> String {
+    if clip.is_sensitive {
+        return "••••••••".to_string();
+    }
+    match clip.clip_type.as_str() {
+        "image" => "Image".to_string(),
+        _ => {
+            let line = clip.content.trim().lines().next().unwrap_or_default();
+            let mut label: String = line.chars().take(MENU_LABEL_CHARS).collect();
+            if line.chars().count() > MENU_LABEL_CHARS {
+                label.push('…');
+            }
+            label
+        }
+    }
+}
+
+/// The tray menu: newest clips first, then the window items.
+pub fn build_menu(app: &AppHandle, clips: &[Clip]) -> tauri::Result<Menu<Wry>> {
+    let menu = Menu::new(app)?;
+    for clip in clips {
+        let id = format!("{}{}", CLIP_ITEM_PREFIX, clip.id);
+        menu.append(&MenuItem::with_id(app, id, menu_label(clip), true, None::<&str>)?)?;
+    }
+    if !clips.is_empty() {
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+    menu.append(&MenuItem::with_id(app, "show", "Open Klip", true, None::<&str>)?)?;
+    if let Some(items) = app.try_state::<window::TrayItems>() {
+        menu.append(&items.always_on_top)?;
+    }
+    menu.append(&MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?)?;
+    Ok(menu)
+}
+
+/// Refreshes the clips in the tray menu, from the recent-clips cache when it can answer.
+pub async fn rebuild_menu(app: &AppHandle) -> Result<(), String> {
+    let (Some(state), Some(tray)) = (app.try_state::<DbState>(), app.tray_by_id(TRAY_ID)) else {
+        return Ok(());
+    };
+    let clips = match state.recent.recent(&state.pool, MENU_CLIPS).await? {
+        Some(clips) => clips,
+        None => {
+            let query = format!("SELECT {} FROM clips WHERE deleted_at IS NULL ORDER BY created_at DESC, id DESC LIMIT ?", CLIP_COLUMNS);
+            sqlx::query_as::<_, Clip>(&query)
+                .bind(MENU_CLIPS as i64)
+                .fetch_all(&state.pool)
+                .await
+                .map_err(|e| e.to_string())?
+        }
+    };
+    let menu = build_menu(app, &clips).map_err(|e| e.to_string())?;
+    tray.set_menu(Some(menu)).map_err(|e| e.to_string())
+}
+
+/// Handles a click on one of the clips in the tray menu; returns false for other items.
+pub fn on_menu_clip(app: &AppHandle, item_id: &str) -> bool {
+    let Some(id) = item_id.strip_prefix(CLIP_ITEM_PREFIX) else { return false };
+    let app = app.clone();
+    let id = id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = app.try_state::<DbState>() else { return };
+        if let Err(e) = restore::copy_clip(&state.pool, &id).await {
+            eprintln!("Failed to copy clip from the tray: {}", e);
+        }
+    });
+    true
+}
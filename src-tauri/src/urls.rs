@@ -0,0 +1,127 @@
+// Folding URL clips that differ only by scheme, trailing slash or fragment
+// (`http://a.com/x/`, `https://a.com/x#top`) into one history entry: the
+// newest copy stands for the others, which stay reachable through
+// `get_url_variants`. Favorites and pinned clips are never folded away.
+
+use sqlx::{Pool, Sqlite};
+
+use crate::classify::is_url;
+use crate::{trash, Clip, DbState, CLIP_COLUMNS};
+
+/// List filter hiding every URL clip that has a newer variant.
+pub const FOLD_VARIANTS: &str = "(clips.canonical_url IS NULL OR clips.canonical_url = '' OR clips.is_favorite = 1 OR clips.pinned = 1 OR NOT EXISTS (
+    SELECT 1 FROM clips AS newer WHERE newer.canonical_url = clips.canonical_url AND newer.deleted_at IS NULL
+    AND (newer.created_at > clips.created_at OR (newer.created_at = clips.created_at AND newer.id > clips.id))))";
+
+/// Key shared by variants of the same link: lowercase host and the rest of
+/// the URL, without scheme, fragment or trailing slash. None for non-URLs.
+pub fn canonical_url(text: &str) -> Option<String> {
+    let text = text.trim();
+    if !is_url(text) {
+        return None;
+    }
+    let rest = text.split_once("://")?.1;
+    let rest = rest.split('#').next().unwrap_or(rest);
+    let (host, path) = match rest.find(['/', '?']) {
+        Some(i) => rest.split_at(i),
+        None => (rest, ""),
+    };
+    let host = host.to_lowercase();
+    let host = host.strip_suffix(":80").or_else(|| host.strip_suffix(":443")).unwrap_or(&host);
+    let (path, query) = match path.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (path, None),
+    };
+    let mut canonical = format!("{}{}", host, path.trim_end_matches('/'));
+    if let Some(query) = query.filter(|q| !q.is_empty()) {
+        canonical.push('?');
+        canonical.push_str(query);
+    }
+    Some(canonical)
+}
+
+/// Fills in `canonical_url` for text clips that don't have one yet ('' for
+/// those that aren't links, so they aren't looked at again), and gives links
+/// stored as plain text the "url" type. Run at startup.
+pub async fn backfill_canonical_urls(pool: &Pool<Sqlite>) -> Result<(), String> {
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT id, content FROM clips WHERE clip_type IN ('text', 'url') AND canonical_url IS NULL")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    for (id, content) in rows {
+        sqlx::query("UPDATE clips SET canonical_url = ? WHERE id = ?")
+            .bind(canonical_url(&content).unwrap_or_default())
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    sqlx::query("UPDATE clips SET clip_type = 'url' WHERE clip_type = 'text' AND canonical_url <> ''")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+/// Sets `url_variants` on folded clips of a page, so the UI can offer the others.
+pub async fn count_variants(pool: &Pool<Sqlite>, clips: &mut [Clip]) -> Result<(), String> {
+    let urls: Vec<&str> = clips.iter().filter_map(|c| c.canonical_url.as_deref()).filter(|u| !u.is_empty()).collect();
+    if urls.is_empty() {
+        return Ok(());
+    }
+    let sql = format!(
+        "SELECT canonical_url, COUNT(*) FROM clips WHERE canonical_url IN ({}) AND {} GROUP BY canonical_url HAVING COUNT(*) > 1",
+        vec!["?"; urls.len()].join(", "),
+        trash::NOT_DELETED
+    );
+    let mut query = sqlx::query_as::<_, (String, i64)>(&sql);
+    for url in urls {
+        query = query.bind(url);
+    }
+    let counts = query.fetch_all(pool).await.map_err(|e| e.to_string())?;
+    for clip in clips.iter_mut() {
+        clip.url_variants = counts.iter().find(|(url, _)| Some(url) == clip.canonical_url.as_ref()).map(|(_, count)| *count);
+    }
+    Ok(())
+}
+
+/// Every copy of the link `id` points at, newest first, including `id` itself.
+#[tauri::command]
+pub async fn get_url_variants(state: tauri::State<'_, DbState>, id: String) -> Result<Vec<Clip>, String> {
+    sqlx::query_as::<_, Clip>(&format!(
+        "SELECT {} FROM clips WHERE canonical_url = (SELECT canonical_url FROM clips WHERE id = ? AND canonical_url != '') AND deleted_at IS NULL ORDER BY created_at DESC, id DESC",
+        CLIP_COLUMNS
+    ))
+    .bind(&id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_scheme_slash_fragment_and_default_port() {
+        let expected = Some("a.com/x".to_string());
+        for url in ["http://a.com/x/", "https://A.com/x#top", "https://a.com:443/x", " http://a.com:80/x "] {
+            assert_eq!(canonical_url(url), expected, "{}", url);
+        }
+    }
+
+    #[test]
+    fn keeps_path_case_and_query() {
+        assert_eq!(canonical_url("https://a.com/Docs/?q=1#frag"), Some("a.com/Docs?q=1".to_string()));
+        assert_eq!(canonical_url("https://a.com?"), Some("a.com".to_string()));
+        assert_eq!(canonical_url("https://a.com:8080/"), Some("a.com:8080".to_string()));
+    }
+
+    #[test]
+    fn ignores_non_urls() {
+        assert_eq!(canonical_url("a.com/x"), None);
+        assert_eq!(canonical_url("ftp://a.com"), None);
+        assert_eq!(canonical_url("https://a.com/x y"), None);
+    }
+}
@@ -0,0 +1,40 @@
+use sqlx::{Pool, Sqlite};
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+
+use crate::DbState;
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct ClipUsage {
+    clip_id: String,
+    action: String, // "copy_text", "copy_image", "copy_files", "drag", "share" or "share_link"
+    used_at: String, // ISO 8601
+}
+
+pub async fn record_usage(pool: &Pool<Sqlite>, clip_id: &str, action: &str) -> Result<(), String> {
+    sqlx::query("INSERT INTO clip_usage (clip_id, action, used_at) VALUES (?, ?, ?)")
+        .bind(clip_id)
+        .bind(action)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Called by the UI when a clip is dragged out into another app.
+#[tauri::command]
+pub async fn record_drag(state: tauri::State<'_, DbState>, id: String) -> Result<(), String> {
+    record_usage(&state.pool, &id, "drag").await
+}
+
+#[tauri::command]
+pub async fn get_clip_usage(state: tauri::State<'_, DbState>, id: String) -> Result<Vec<ClipUsage>, String> {
+    let rows = sqlx::query_as::<_, ClipUsage>("SELECT clip_id, action, used_at FROM clip_usage WHERE clip_id = ? ORDER BY used_at DESC")
+        .bind(id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows)
+}
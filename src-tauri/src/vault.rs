@@ -0,0 +1,108 @@
+// Markdown vault export (Obsidian, Logseq, plain folders): one note per clip.
+
+use std::path::{Path, PathBuf};
+use std::fmt::Write as _;
+use chrono::prelude::*;
+
+use crate::export::{fetch_clips, ExportFilter};
+use crate::{setting_value, tags, Clip, DbState};
+
+const TITLE_CHARS: usize = 60;
+
+/// Note title from the first non-empty line, stripped of characters that
+/// are invalid in file names or break wiki-links.
+fn note_title(clip: &Clip) -> String {
+    let first_line = clip.content.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or("");
+    let title: String = first_line
+        .chars()
+        .filter(|c| !matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']'))
+        .take(TITLE_CHARS)
+        .collect();
+    let title = title.trim();
+    if title.is_empty() {
+        if clip.clip_type == "image" { "Image".to_string() } else { "Clip".to_string() }
+    } else {
+        title.to_string()
+    }
+}
+
+fn yaml_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Obsidian and Logseq tags can't contain spaces.
+fn note_tag(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+fn render_note(clip: &Clip, clip_tags: &[String], created: &DateTime<Local>, attachment: Option<&str>) -> String {
+    let mut tags = vec!["klip".to_string()];
+    if clip.is_favorite {
+        tags.push("favorite".to_string());
+    }
+    tags.extend(clip_tags.iter().map(|t| yaml_string(&note_tag(t))));
+
+    let mut note = String::from("---\n");
+    let _ = writeln!(note, "klip_id: {}", clip.id);
+    let _ = writeln!(note, "created: {}", created.format("%Y-%m-%dT%H:%M:%S"));
+    let _ = writeln!(note, "date: {}", created.format("%Y-%m-%d"));
+    let _ = writeln!(note, "type: {}", clip.clip_type);
+    let _ = writeln!(note, "tags: [{}]", tags.join(", "));
+    if let Some(url) = &clip.source_url {
+        let _ = writeln!(note, "source_url: {}", yaml_string(url));
+    }
+    if let Some(app) = &clip.source_app {
+        let _ = writeln!(note, "source_app: {}", yaml_string(app));
+    }
+    note.push_str("---\n\n");
+
+    if let Some(file) = attachment {
+        let _ = writeln!(note, "![[{}]]\n", file);
+    }
+    if !clip.content.is_empty() {
+        // Fence the body so Markdown in copied text doesn't reflow
+        let fence = if clip.content.contains("```") { "~~~~" } else { "```" };
+        let _ = writeln!(note, "{}\n{}\n{}", fence, clip.content.trim_end(), fence);
+    }
+    note
+}
+
+fn write_note(folder: &Path, clip: &Clip, clip_tags: &[String]) -> Result<(), String> {
+    let created = DateTime::parse_from_rfc3339(&clip.created_at)
+        .map(|d| d.with_timezone(&Local))
+        .unwrap_or_else(|_| Local::now());
+
+    let attachment = match (&clip.clip_type[..], &clip.image_path) {
+        ("image", Some(path)) => {
+            let attachments = folder.join("attachments");
+            std::fs::create_dir_all(&attachments).map_err(|e| e.to_string())?;
+            let file_name = format!("klip-{}.png", clip.id);
+            std::fs::copy(path, attachments.join(&file_name)).map_err(|e| e.to_string())?;
+            Some(file_name)
+        }
+        _ => None,
+    };
+
+    // The short id suffix keeps names unique and makes re-exports overwrite the same note
+    let file_name = format!("{} {} ({}).md", created.format("%Y-%m-%d"), note_title(clip), &clip.id[..8.min(clip.id.len())]);
+    std::fs::write(folder.join(file_name), render_note(clip, clip_tags, &created, attachment.as_deref())).map_err(|e| e.to_string())
+}
+
+/// Writes each matching clip as a Markdown note into `folder`, or the
+/// configured `vault_path` setting. Returns the number of notes written.
+#[tauri::command]
+pub async fn export_to_vault(state: tauri::State<'_, DbState>, filter: Option<ExportFilter>, folder: Option<String>) -> Result<usize, String> {
+    let folder = match folder.filter(|f| !f.is_empty()) {
+        Some(f) => Some(f),
+        None => setting_value(&state.pool, "vault_path").await.filter(|f| !f.is_empty()),
+    };
+    let folder = PathBuf::from(folder.ok_or_else(|| "No vault folder configured".to_string())?);
+    std::fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+
+    let clips = fetch_clips(&state.pool, &filter.unwrap_or_default()).await?;
+    for clip in &clips {
+        let clip_tags = tags::clip_tag_names(&state.pool, &clip.id).await?;
+        write_note(&folder, clip, &clip_tags)?;
+    }
+    Ok(clips.len())
+}
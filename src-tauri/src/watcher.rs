@@ -0,0 +1,154 @@
+// Clipboard change notifications for the monitor thread, so it only reads the
+// clipboard after something was copied instead of on every poll. Each
+// platform has a native watcher; when that can't be set up (e.g. Wayland
+// without XWayland) the monitor falls back to plain polling.
+
+use std::thread;
+
+use crate::settings;
+
+pub trait ClipboardWatcher {
+    /// Blocks until the clipboard may have changed. Spurious wake-ups are
+    /// fine: the monitor compares content before storing anything.
+    fn wait(&mut self);
+
+    /// Whether every wake-up follows an actual copy, so copying the same
+    /// content again can be told apart from nothing happening.
+    fn reports_every_copy(&self) -> bool {
+        true
+    }
+}
+
+/// Wakes up every poll interval; the original behavior.
+pub struct PollingWatcher;
+
+impl ClipboardWatcher for PollingWatcher {
+    fn wait(&mut self) {
+        thread::sleep(settings::poll_interval());
+    }
+
+    fn reports_every_copy(&self) -> bool {
+        false
+    }
+}
+
+/// Listens for WM_CLIPBOARDUPDATE on a message-only window. Must be created on
+/// the thread that waits, since the message queue is per thread.
+#[cfg(target_os = "windows")]
+struct FormatListenerWatcher {
+    hwnd: windows::Win32::Foundation::HWND,
+}
+
+#[cfg(target_os = "windows")]
+impl ClipboardWatcher for FormatListenerWatcher {
+    fn wait(&mut self) {
+        use windows::Win32::UI::WindowsAndMessaging::{GetMessageW, MSG, WM_CLIPBOARDUPDATE};
+
+        let mut msg = MSG::default();
+        // Only our one message is taken off the queue; -1 means the window is gone
+        let result = unsafe { GetMessageW(&mut msg, Some(self.hwnd), WM_CLIPBOARDUPDATE, WM_CLIPBOARDUPDATE) };
+        if result.0 == -1 {
+            eprintln!("Clipboard listener failed: {}", windows::core::Error::from_win32());
+            thread::sleep(settings::poll_interval());
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn native() -> Option<Box<dyn ClipboardWatcher>> {
+    use windows::core::w;
+    use windows::Win32::System::DataExchange::AddClipboardFormatListener;
+    use windows::Win32::UI::WindowsAndMessaging::{CreateWindowExW, HWND_MESSAGE, WINDOW_EX_STYLE, WINDOW_STYLE};
+
+    unsafe {
+        let hwnd = CreateWindowExW(WINDOW_EX_STYLE::default(), w!("STATIC"), w!(""), WINDOW_STYLE::default(), 0, 0, 0, 0, Some(HWND_MESSAGE), None, None, None).ok()?;
+        AddClipboardFormatListener(hwnd).ok()?;
+        Some(Box::new(FormatListenerWatcher { hwnd }))
+    }
+}
+
+/// macOS has no change notification, but `changeCount` is a cheap counter,
+/// so it's checked often and the clipboard itself is only read when it moves.
+#[cfg(target_os = "macos")]
+struct ChangeCountWatcher {
+    last: isize,
+}
+
+#[cfg(target_os = "macos")]
+const CHANGE_COUNT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+#[cfg(target_os = "macos")]
+impl ClipboardWatcher for ChangeCountWatcher {
+    fn wait(&mut self) {
+        loop {
+            thread::sleep(CHANGE_COUNT_INTERVAL);
+            let count = objc2_app_kit::NSPasteboard::generalPasteboard().changeCount();
+            if count != self.last {
+                self.last = count;
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn native() -> Option<Box<dyn ClipboardWatcher>> {
+    let last = objc2_app_kit::NSPasteboard::generalPasteboard().changeCount();
+    Some(Box::new(ChangeCountWatcher { last }))
+}
+
+/// XFixes selection-owner events for CLIPBOARD on a hidden X11 window. Works
+/// for Wayland apps too when XWayland is running, as it mirrors the selection.
+#[cfg(target_os = "linux")]
+struct XFixesWatcher {
+    conn: x11rb::rust_connection::RustConnection,
+}
+
+#[cfg(target_os = "linux")]
+impl ClipboardWatcher for XFixesWatcher {
+    fn wait(&mut self) {
+        use x11rb::connection::Connection;
+        use x11rb::protocol::Event;
+
+        loop {
+            match self.conn.wait_for_event() {
+                Ok(Event::XfixesSelectionNotify(_)) => return,
+                Ok(_) => continue,
+                Err(e) => {
+                    eprintln!("X11 clipboard watcher failed: {}", e);
+                    thread::sleep(settings::poll_interval());
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn native() -> Option<Box<dyn ClipboardWatcher>> {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xfixes::{ConnectionExt as _, SelectionEventMask};
+    use x11rb::protocol::xproto::{ConnectionExt as _, CreateWindowAux, WindowClass};
+
+    let (conn, screen) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots.get(screen)?.root;
+    let window = conn.generate_id().ok()?;
+    conn.create_window(0, window, root, 0, 0, 1, 1, 0, WindowClass::INPUT_ONLY, x11rb::COPY_FROM_PARENT, &CreateWindowAux::new())
+        .ok()?;
+    conn.xfixes_query_version(5, 0).ok()?.reply().ok()?;
+    let clipboard = conn.intern_atom(false, b"CLIPBOARD").ok()?.reply().ok()?.atom;
+    conn.xfixes_select_selection_input(window, clipboard, SelectionEventMask::SET_SELECTION_OWNER).ok()?;
+    conn.flush().ok()?;
+    Some(Box::new(XFixesWatcher { conn }))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn native() -> Option<Box<dyn ClipboardWatcher>> {
+    None
+}
+
+/// The platform watcher when available, polling otherwise. Call this on the
+/// thread that will wait on it.
+pub fn create() -> Box<dyn ClipboardWatcher> {
+    native().unwrap_or_else(|| Box::new(PollingWatcher))
+}
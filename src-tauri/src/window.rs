@@ -0,0 +1,59 @@
+// Main window behavior driven by settings: currently "always on top",
+// toggled from the tray or via `set_always_on_top`.
+
+use tauri::menu::CheckMenuItem;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::{setting_enabled, DbState};
+
+/// Tray menu items whose state the backend keeps in sync with settings.
+pub struct TrayItems {
+    pub always_on_top: CheckMenuItem<Wry>,
+}
+
+pub fn apply_always_on_top(app: &AppHandle, enabled: bool) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.set_always_on_top(enabled);
+    }
+    if let Some(items) = app.try_state::<TrayItems>() {
+        let _ = items.always_on_top.set_checked(enabled);
+    }
+}
+
+async fn save_always_on_top(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    let state = app.try_state::<DbState>().ok_or_else(|| "Database is not available".to_string())?;
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value) VALUES ('always_on_top', ?)")
+        .bind(enabled.to_string())
+        .execute(&state.pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Restores the saved setting at startup.
+pub async fn restore_always_on_top(app: &AppHandle) {
+    if let Some(state) = app.try_state::<DbState>() {
+        let enabled = setting_enabled(&state.pool, "always_on_top", false).await;
+        apply_always_on_top(app, enabled);
+    }
+}
+
+/// Called when the tray check item is clicked; it has already flipped its own state.
+pub fn on_tray_toggle(app: &AppHandle) {
+    let Some(items) = app.try_state::<TrayItems>() else { return };
+    let enabled = items.always_on_top.is_checked().unwrap_or(false);
+    apply_always_on_top(app, enabled);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = save_always_on_top(&app, enabled).await {
+            eprintln!("Failed to save always_on_top: {}", e);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn set_always_on_top(app_handle: AppHandle, enabled: bool) -> Result<(), String> {
+    apply_always_on_top(&app_handle, enabled);
+    save_always_on_top(&app_handle, enabled).await
+}
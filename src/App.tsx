@@ -1,8 +1,8 @@
-import { useState, useEffect } from "react";
+import { useState, useEffect, useRef } from "react";
 import { invoke, convertFileSrc } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { openUrl } from "@tauri-apps/plugin-opener";
-import { Search, Star, Clipboard, Calendar, X, Settings, ExternalLink, Image as ImageIcon, ScanText, Trash2 } from "lucide-react";
+import { Search, Star, Clipboard, Calendar, X, Settings, ExternalLink, Image as ImageIcon, ScanText, Trash2, Info, Share2, Pin, Lock, File } from "lucide-react";
 import clsx from "clsx";
 import { useTranslation } from "react-i18next";
 import SettingsModal from "./components/SettingsModal";
@@ -13,13 +13,54 @@ interface Clip {
   content: string;
   created_at: string;
   is_favorite: boolean;
-  clip_type: 'text' | 'image';
+  clip_type: 'text' | 'url' | 'image' | 'files';
   image_path?: string;
+  is_sensitive?: boolean;
+  source_url?: string;
+  image_width?: number;
+  image_height?: number;
+  dominant_color?: string;
+  thumbnail_path?: string;
+  window_title?: string;
+  source_app?: string;
+  source_app_icon?: string;
+  url_variants?: number;
+  detected_kind?: string;
+  link_title?: string;
+  link_favicon?: string;
+  rank?: number;
+  matched_in?: string[];
+  excerpt?: string;
+  pinned?: boolean;
+  locked?: boolean;
+}
+
+interface PurgeSummary {
+  clips: number;
+  bytes: number;
+  oldest?: string;
+  description: string;
+}
+
+interface ClipContext {
+  app?: string;
+  window_title?: string;
+  url?: string;
 }
 
 interface Toast {
   message: string;
   visible: boolean;
+  action?: { label: string; run: () => void };
+}
+
+// "files" clips store their paths as a JSON array
+function filePaths(content: string): string[] {
+  try {
+    return JSON.parse(content);
+  } catch {
+    return [];
+  }
 }
 
 function App() {
@@ -27,10 +68,19 @@ function App() {
   const [clips, setClips] = useState<Clip[]>([]);
   const [dates, setDates] = useState<string[]>([]);
   const [search, setSearch] = useState("");
+  const searchInputRef = useRef<HTMLInputElement>(null);
   const [selectedDate, setSelectedDate] = useState<string | null>(null);
   const [processingOcr, setProcessingOcr] = useState<string | null>(null);
   const [toast, setToast] = useState<Toast>({ message: "", visible: false });
   const [expandedClips, setExpandedClips] = useState<Set<string>>(new Set());
+  const [hasMore, setHasMore] = useState(true);
+  const [loadingMore, setLoadingMore] = useState(false);
+  const [totalCount, setTotalCount] = useState(0);
+  const [initError, setInitError] = useState<string | null>(null);
+  const [pendingRetention, setPendingRetention] = useState<PurgeSummary | null>(null);
+  const [details, setDetails] = useState<{ id: string; context: ClipContext | null } | null>(null);
+  const [descriptions, setDescriptions] = useState<Record<string, string>>({});
+  const [activeSnapshot, setActiveSnapshot] = useState<string | null>(null);
 
   const toggleExpanded = (id: string, e: React.MouseEvent) => {
     e.stopPropagation();
@@ -45,9 +95,9 @@ function App() {
     });
   };
 
-  const showToast = (message: string) => {
-    setToast({ message, visible: true });
-    setTimeout(() => setToast(prev => ({ ...prev, visible: false })), 3000);
+  const showToast = (message: string, action?: Toast["action"]) => {
+    setToast({ message, visible: true, action });
+    setTimeout(() => setToast(prev => ({ ...prev, visible: false })), action ? 6000 : 3000);
   };
 
   // Settings State
@@ -72,16 +122,55 @@ function App() {
 
   const fetchClips = async (searchText: string = "", dateFilter: string | null = null) => {
     try {
-      const result = await invoke<Clip[]>("get_clips", {
-        searchText: searchText || null,
-        dateFilter: dateFilter
-      });
+      const [result, count] = await Promise.all([
+        invoke<Clip[]>("get_clips", {
+          searchText: searchText || null,
+          dateFilter: dateFilter,
+          orderBy: searchText ? "relevance" : null
+        }),
+        invoke<number>("get_clip_count", {
+          searchText: searchText || null,
+          dateFilter: dateFilter
+        })
+      ]);
       setClips(result);
+      setTotalCount(count);
+      setHasMore(result.length < count);
     } catch (error) {
       console.error("Failed to fetch clips:", error);
     }
   };
 
+  // Infinite scroll: fetch the page after the last clip we already have
+  const loadMoreClips = async () => {
+    if (!hasMore || loadingMore || clips.length === 0) return;
+    const last = clips[clips.length - 1];
+    setLoadingMore(true);
+    try {
+      const result = await invoke<Clip[]>("get_clips", {
+        searchText: search || null,
+        dateFilter: selectedDate,
+        orderBy: search ? "relevance" : null,
+        cursorCreatedAt: last.created_at,
+        cursorRank: last.rank ?? null,
+        cursorId: last.id
+      });
+      setClips(prev => [...prev, ...result]);
+      setHasMore(result.length > 0 && clips.length + result.length < totalCount);
+    } catch (error) {
+      console.error("Failed to load more clips:", error);
+    } finally {
+      setLoadingMore(false);
+    }
+  };
+
+  const handleListScroll = (e: React.UIEvent<HTMLDivElement>) => {
+    const el = e.currentTarget;
+    if (el.scrollTop + el.clientHeight >= el.scrollHeight - 200) {
+      loadMoreClips();
+    }
+  };
+
   const fetchDates = async () => {
     try {
       const result = await invoke<string[]>("get_dates_with_clips");
@@ -91,18 +180,67 @@ function App() {
     }
   };
 
+  const handleToggleDetails = async (e: React.MouseEvent, id: string) => {
+    e.stopPropagation();
+    if (details?.id === id) {
+      setDetails(null);
+      return;
+    }
+    try {
+      const context = await invoke<ClipContext | null>("get_clip_context", { id });
+      setDetails({ id, context });
+    } catch (error) {
+      console.error("Failed to load clip context:", error);
+    }
+  };
+
   const handleDelete = async (id: string) => {
     try {
       await invoke("delete_clip", { id });
       fetchClips(search, selectedDate);
       fetchDates();
+      showToast(t('clip_deleted'), { label: t('undo'), run: () => handleRestore(id) });
     } catch (error) {
       console.error("Failed to delete clip", error);
     }
   };
 
+  const handleRestore = async (id: string) => {
+    try {
+      await invoke("restore_clip", { id });
+      setToast(prev => ({ ...prev, visible: false }));
+      fetchClips(search, selectedDate);
+      fetchDates();
+    } catch (error) {
+      console.error("Failed to restore clip", error);
+    }
+  };
+
+  const handleRetryInit = async (reset: boolean) => {
+    try {
+      await invoke(reset ? "reset_database" : "retry_init");
+      setInitError(null);
+      fetchClips(search, selectedDate);
+      fetchDates();
+    } catch (error) {
+      setInitError(String(error));
+    }
+  };
+
+  const handleConfirmRetention = async () => {
+    try {
+      await invoke("confirm_retention");
+      setPendingRetention(null);
+    } catch (error) {
+      console.error("Failed to apply retention", error);
+    }
+  };
+
   // Initial load
   useEffect(() => {
+    invoke<string | null>("get_init_status").then(setInitError).catch(console.error);
+    invoke<PurgeSummary | null>("get_pending_retention").then(setPendingRetention).catch(console.error);
+    invoke<string | null>("get_active_snapshot").then(setActiveSnapshot).catch(console.error);
     fetchClips(search, selectedDate);
     fetchDates();
 
@@ -112,8 +250,27 @@ function App() {
       fetchDates();
     });
 
+    // Global shortcut / tray quick picker: jump straight into search
+    const unlistenPicker = listen("quick-picker", () => {
+      searchInputRef.current?.focus();
+      searchInputRef.current?.select();
+    });
+
+    // An app spamming the clipboard is being ignored for now
+    const unlistenRateLimit = listen<{ app: string }>("capture-rate-limited", (event) => {
+      showToast(t('capture_rate_limited', { app: event.payload.app }));
+    });
+
+    // The list is being served from a frozen snapshot (or back to live history)
+    const unlistenSnapshot = listen<string | null>("snapshot-changed", (event) => {
+      setActiveSnapshot(event.payload);
+    });
+
     return () => {
       unlisten.then((f) => f());
+      unlistenPicker.then((f) => f());
+      unlistenRateLimit.then((f) => f());
+      unlistenSnapshot.then((f) => f());
     };
   }, []);
 
@@ -130,24 +287,89 @@ function App() {
     fetchClips(search, selectedDate);
   }, [selectedDate]);
 
+  // Screen-reader labels for the listed clips
+  useEffect(() => {
+    if (clips.length === 0) return;
+    invoke<{ id: string; description: string }[]>("describe_clips", { ids: clips.map(clip => clip.id) })
+      .then(result => setDescriptions(Object.fromEntries(result.map(d => [d.id, d.description]))))
+      .catch(console.error);
+  }, [clips]);
+
   const handleCopy = async (content: string, clip: Clip) => {
     if (clip.clip_type === 'image' && clip.image_path) {
       // For image clips, we might copy the text if the main container is clicked
       // Check if it has text content that was extracted
       if (clip.content) {
-        await invoke("copy_to_clipboard", { content: clip.content });
+        await invoke("copy_to_clipboard", { content: clip.content, id: clip.id });
         showToast(t('text_copied') || "Text copied");
       }
     } else {
-      await invoke("copy_to_clipboard", { content });
+      await invoke("copy_to_clipboard", { content, id: clip.id });
       showToast(t('text_copied') || "Text copied");
     }
   };
 
-  const handleCopyImage = async (e: React.MouseEvent, path: string) => {
+  // Double-click pastes into the app that was in front before Klip
+  const handlePaste = async (clip: Clip) => {
+    try {
+      await invoke("paste_clip", { id: clip.id });
+    } catch (err) {
+      console.error("Paste failed", err);
+      showToast(t('paste_failed') || "Paste failed");
+    }
+  };
+
+  // Drag a clip out into another app: text as text, links also as a URL,
+  // images as a file reference the target app can read
+  const handleDragStart = (e: React.DragEvent, clip: Clip) => {
+    if (clip.clip_type === 'image' && clip.image_path) {
+      const path = clip.image_path.replace(/\\/g, '/');
+      const fileUrl = `file://${path.startsWith('/') ? '' : '/'}${encodeURI(path)}`;
+      e.dataTransfer.setData('text/uri-list', fileUrl);
+      e.dataTransfer.setData('DownloadURL', `image/png:${clip.id}.png:${fileUrl}`);
+      if (clip.content) e.dataTransfer.setData('text/plain', clip.content);
+    } else {
+      e.dataTransfer.setData('text/plain', clip.content);
+      if (isUrl(clip.content)) e.dataTransfer.setData('text/uri-list', clip.content.trim());
+    }
+    e.dataTransfer.effectAllowed = 'copy';
+    invoke("record_drag", { id: clip.id }).catch(console.error);
+  };
+
+  const handleShare = async (e: React.MouseEvent, id: string) => {
+    e.stopPropagation();
+    try {
+      await invoke("share_clip", { id });
+    } catch (err) {
+      console.error("Failed to share clip", err);
+      showToast(t('share_failed') || "Sharing is not available");
+    }
+  };
+
+  const handleTogglePin = async (e: React.MouseEvent, clip: Clip) => {
     e.stopPropagation();
     try {
-      await invoke("copy_image_to_clipboard", { path });
+      await invoke(clip.pinned ? "unpin_clip" : "pin_clip", { id: clip.id });
+      fetchClips(search, selectedDate);
+    } catch (err) {
+      console.error("Failed to toggle pin", err);
+    }
+  };
+
+  const handleToggleLock = async (e: React.MouseEvent, clip: Clip) => {
+    e.stopPropagation();
+    try {
+      await invoke(clip.locked ? "unlock_clip" : "lock_clip", { id: clip.id });
+      fetchClips(search, selectedDate);
+    } catch (err) {
+      console.error("Failed to toggle lock", err);
+    }
+  };
+
+  const handleCopyImage = async (e: React.MouseEvent, path: string, id: string) => {
+    e.stopPropagation();
+    try {
+      await invoke("copy_image_to_clipboard", { path, id });
       showToast(t('image_copied') || "Image copied");
     } catch (err) {
       console.error("Failed to copy image", err);
@@ -247,6 +469,7 @@ function App() {
           <div className="relative flex-1">
             <Search className="absolute left-3 top-1/2 transform -translate-y-1/2 text-gray-400 w-4 h-4" />
             <input
+              ref={searchInputRef}
               type="text"
               placeholder={t('search_placeholder')}
               value={search}
@@ -267,8 +490,47 @@ function App() {
           )}
         </div>
 
+        {initError && (
+          <div className="m-2 p-3 rounded-md border border-red-800 bg-red-900/30 text-red-200 text-sm">
+            <p className="font-medium">{t('init_failed')}</p>
+            <p className="text-xs mt-1 opacity-80 break-all">{initError}</p>
+            <div className="flex gap-2 mt-2">
+              <button onClick={() => handleRetryInit(false)} className="px-2 py-1 rounded border border-red-700 hover:bg-red-800/50 text-xs">
+                {t('retry')}
+              </button>
+              <button onClick={() => handleRetryInit(true)} className="px-2 py-1 rounded border border-red-700 hover:bg-red-800/50 text-xs">
+                {t('reset_database')}
+              </button>
+            </div>
+          </div>
+        )}
+
+        {activeSnapshot && (
+          <div className="m-2 p-2 rounded-md border border-blue-800 bg-blue-900/30 text-blue-200 text-xs flex items-center justify-between gap-2">
+            <span>{t('snapshot_active', { name: activeSnapshot })}</span>
+            <button onClick={() => invoke("unload_snapshot").catch(console.error)} className="px-2 py-1 rounded border border-blue-700 hover:bg-blue-800/50">
+              {t('snapshot_unload')}
+            </button>
+          </div>
+        )}
+
+        {pendingRetention && (
+          <div className="m-2 p-3 rounded-md border border-yellow-800 bg-yellow-900/30 text-yellow-200 text-sm">
+            <p className="font-medium">{t('retention_pending')}</p>
+            <p className="text-xs mt-1 opacity-80">{pendingRetention.description}</p>
+            <div className="flex gap-2 mt-2">
+              <button onClick={handleConfirmRetention} className="px-2 py-1 rounded border border-yellow-700 hover:bg-yellow-800/50 text-xs">
+                {t('delete')}
+              </button>
+              <button onClick={() => setPendingRetention(null)} className="px-2 py-1 rounded border border-yellow-700 hover:bg-yellow-800/50 text-xs">
+                {t('not_now')}
+              </button>
+            </div>
+          </div>
+        )}
+
         {/* Clips List */}
-        <div className="flex-1 overflow-y-auto p-2 space-y-2">
+        <div className="flex-1 overflow-y-auto p-2 space-y-2" onScroll={handleListScroll}>
           {clips.length === 0 ? (
             <div className="text-center text-gray-500 mt-20">
               <Clipboard className="mx-auto w-12 h-12 mb-2 opacity-50" />
@@ -277,13 +539,18 @@ function App() {
             </div>
           ) : (
             clips.map((clip) => {
-              const isLink = isUrl(clip.content);
+              const isLink = clip.clip_type === 'url' || isUrl(clip.content);
               const isImage = clip.clip_type === 'image';
 
               return (
                 <div
                   key={clip.id}
+                  role="button"
+                  aria-label={descriptions[clip.id]}
+                  draggable
+                  onDragStart={(e) => handleDragStart(e, clip)}
                   onClick={() => handleCopy(clip.content, clip)}
+                  onDoubleClick={() => handlePaste(clip)}
                   className={clsx(
                     "group p-3 rounded-md cursor-pointer transition-colors border relative",
                     theme === 'dark'
@@ -299,13 +566,19 @@ function App() {
                           {/* Image Only */}
                           <div className="flex-none">
                             <img
-                              src={convertFileSrc(clip.image_path)}
+                              src={convertFileSrc(clip.thumbnail_path ?? clip.image_path)}
                               alt="Clipboard Image"
                               className="max-h-48 rounded border border-gray-600 object-contain bg-black/50"
                               loading="lazy"
+                              style={clip.image_width && clip.image_height ? {
+                                // Reserve the final box (max-h-48 = 192px) before the file decodes
+                                aspectRatio: `${clip.image_width} / ${clip.image_height}`,
+                                height: Math.min(192, clip.image_height),
+                                backgroundColor: clip.dominant_color,
+                              } : undefined}
                             />
                             <button
-                              onClick={(e) => handleCopyImage(e, clip.image_path!)}
+                              onClick={(e) => handleCopyImage(e, clip.image_path!, clip.id)}
                               className={clsx(
                                 "mt-2 w-full text-xs py-1 rounded border transition-colors flex items-center justify-center gap-1",
                                 theme === 'dark' ? "border-gray-600 hover:bg-gray-700 text-gray-300" : "border-gray-200 hover:bg-gray-100 text-gray-600"
@@ -321,6 +594,9 @@ function App() {
                             <div className="flex-1 min-w-0 flex flex-col">
                               <div className="flex items-center gap-2 mb-1">
                                 <span className="text-xs font-semibold text-gray-500 uppercase tracking-wider">{t('extracted_text') || "Extracted Text"}</span>
+                                {clip.matched_in?.includes('ocr') && (
+                                  <span className="text-[10px] px-1.5 py-0.5 rounded bg-blue-900/30 text-blue-300">{t('matched_ocr')}</span>
+                                )}
                               </div>
                               <p className={clsx("text-sm whitespace-pre-wrap font-mono line-clamp-[10] flex-1", theme === 'dark' ? "text-gray-300" : "text-gray-700")}>
                                 {clip.content}
@@ -343,12 +619,31 @@ function App() {
                         </div>
                       ) : (
                         <div>
+                          {clip.clip_type === 'files' ? (
+                            <ul className={clsx("text-sm space-y-0.5", theme === 'dark' ? "text-gray-100" : "text-gray-800")}>
+                              {filePaths(clip.content).map((path) => (
+                                <li key={path} className="flex items-center gap-1 truncate" title={path}>
+                                  <File className="w-3 h-3 flex-none text-gray-500" />
+                                  <span className="truncate">{path.split(/[\\/]/).pop()}</span>
+                                </li>
+                              ))}
+                            </ul>
+                          ) : (<>
+                          {clip.link_title && (
+                            <div className={clsx("mb-0.5 flex items-center gap-1 text-sm font-medium truncate", theme === 'dark' ? "text-gray-100" : "text-gray-800")} title={clip.link_title}>
+                              {clip.link_favicon && (
+                                <img src={convertFileSrc(clip.link_favicon)} alt="" className="w-4 h-4 flex-none" />
+                              )}
+                              <span className="truncate">{clip.link_title}</span>
+                            </div>
+                          )}
                           <p className={clsx(
                             "text-sm font-mono whitespace-pre-wrap break-all transition-all duration-200",
                             theme === 'dark' ? "text-gray-100" : "text-gray-800",
                             !expandedClips.has(clip.id) && clip.content.length > 300 && "line-clamp-6"
                           )}>
-                            {clip.content}
+                            {/* Long clips show the part that matched the search until expanded */}
+                            {!expandedClips.has(clip.id) && clip.content.length > 300 && clip.excerpt ? clip.excerpt : clip.content}
                           </p>
                           {clip.content.length > 300 && (
                             <button
@@ -358,6 +653,35 @@ function App() {
                               {expandedClips.has(clip.id) ? t('show_less') : t('show_more')}
                             </button>
                           )}
+                          </>)}
+                          {clip.source_url && (
+                            <button
+                              onClick={(e) => handleOpenUrl(e, clip.source_url!)}
+                              className="mt-1 flex items-center gap-1 text-xs text-gray-500 hover:text-blue-400 truncate max-w-full"
+                              title={clip.source_url}
+                            >
+                              <ExternalLink className="w-3 h-3 flex-none" />
+                              <span className="truncate">{clip.source_url}</span>
+                            </button>
+                          )}
+                          {clip.window_title && (
+                            <div className="mt-1 text-xs text-gray-500 truncate max-w-full" title={clip.window_title}>
+                              {clip.window_title}
+                            </div>
+                          )}
+                          {clip.source_app && (
+                            <div className="mt-1 flex items-center gap-1 text-xs text-gray-500 truncate max-w-full" title={clip.source_app}>
+                              {clip.source_app_icon && (
+                                <img src={convertFileSrc(clip.source_app_icon)} alt="" className="w-3 h-3 flex-none" />
+                              )}
+                              <span className="truncate">{clip.source_app}</span>
+                            </div>
+                          )}
+                          {clip.url_variants && (
+                            <div className="mt-1 text-xs text-gray-500">
+                              {t('url_variants', { count: clip.url_variants })}
+                            </div>
+                          )}
                         </div>
                       )}
                     </div>
@@ -393,6 +717,48 @@ function App() {
                       {/* Generic Actions */}
                       <div className="flex items-center gap-1">
                         <button
+                          onClick={(e) => handleToggleDetails(e, clip.id)}
+                          className={clsx(
+                            "p-1 rounded hover:bg-opacity-20 transition-all opacity-0 group-hover:opacity-100",
+                            theme === 'dark' ? "hover:bg-gray-400 text-gray-400" : "hover:bg-gray-200 text-gray-600"
+                          )}
+                          title={t('details')}
+                        >
+                          <Info className="w-3 h-3" />
+                        </button>
+                        <button
+                          onClick={(e) => handleShare(e, clip.id)}
+                          className={clsx(
+                            "p-1 rounded hover:bg-opacity-20 transition-all opacity-0 group-hover:opacity-100",
+                            theme === 'dark' ? "hover:bg-gray-400 text-gray-400" : "hover:bg-gray-200 text-gray-600"
+                          )}
+                          title={t('share')}
+                        >
+                          <Share2 className="w-3 h-3" />
+                        </button>
+                        <button
+                          onClick={(e) => handleTogglePin(e, clip)}
+                          className={clsx(
+                            "p-1 rounded hover:bg-opacity-20 transition-all",
+                            theme === 'dark' ? "hover:bg-gray-400" : "hover:bg-gray-200",
+                            clip.pinned ? "text-blue-400" : clsx("opacity-0 group-hover:opacity-100", theme === 'dark' ? "text-gray-400" : "text-gray-600")
+                          )}
+                          title={clip.pinned ? t('unpin') : t('pin')}
+                        >
+                          <Pin className="w-3 h-3" />
+                        </button>
+                        <button
+                          onClick={(e) => handleToggleLock(e, clip)}
+                          className={clsx(
+                            "p-1 rounded hover:bg-opacity-20 transition-all",
+                            theme === 'dark' ? "hover:bg-gray-400" : "hover:bg-gray-200",
+                            clip.locked ? "text-amber-500" : clsx("opacity-0 group-hover:opacity-100", theme === 'dark' ? "text-gray-400" : "text-gray-600")
+                          )}
+                          title={clip.locked ? t('unlock') : t('lock')}
+                        >
+                          <Lock className="w-3 h-3" />
+                        </button>
+                        {!clip.locked && <button
                           onClick={(e) => {
                             e.stopPropagation();
                             handleDelete(clip.id);
@@ -404,7 +770,7 @@ function App() {
                           title={t('delete')}
                         >
                           <Trash2 className="w-3 h-3" />
-                        </button>
+                        </button>}
                         {clip.is_favorite && <Star className="w-3 h-3 text-yellow-500 fill-current" />}
                       </div>
                     </div>
@@ -417,10 +783,28 @@ function App() {
                       ) : isLink ? "LINK" : "TEXT"}
                     </span>
                   </div>
+                  {details?.id === clip.id && (
+                    <div className={clsx("mt-2 p-2 rounded text-xs space-y-0.5", theme === 'dark' ? "bg-[#1e1e1e] text-gray-400" : "bg-gray-100 text-gray-600")}>
+                      {details.context ? (
+                        <>
+                          {details.context.app && <div>{t('context_app')}: {details.context.app}</div>}
+                          {details.context.window_title && <div className="truncate">{t('context_window')}: {details.context.window_title}</div>}
+                          {details.context.url && <div className="truncate">{t('context_url')}: {details.context.url}</div>}
+                        </>
+                      ) : (
+                        <div>{t('no_context')}</div>
+                      )}
+                    </div>
+                  )}
                 </div>
               )
             })
           )}
+          {clips.length > 0 && !hasMore && (
+            <div className="text-center text-xs text-gray-500 py-2">
+              {t('end_of_history', { count: totalCount })}
+            </div>
+          )}
         </div>
       </div >
 
@@ -440,6 +824,11 @@ function App() {
         )}
       >
         {toast.message}
+        {toast.action && (
+          <button onClick={toast.action.run} className="ml-3 font-semibold underline">
+            {toast.action.label}
+          </button>
+        )}
       </div>
     </div >
   );
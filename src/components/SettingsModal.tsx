@@ -14,6 +14,7 @@ interface SettingsModalProps {
 const SettingsModal: React.FC<SettingsModalProps> = ({ isOpen, onClose, theme, setTheme }) => {
     const { t, i18n } = useTranslation();
     const [retentionDays, setRetentionDays] = useState<string>("90");
+    const [ignoredApps, setIgnoredApps] = useState<string>("");
 
     useEffect(() => {
         if (isOpen) {
@@ -22,6 +23,9 @@ const SettingsModal: React.FC<SettingsModalProps> = ({ isOpen, onClose, theme, s
                     setRetentionDays((val as string) || "90");
                 })
                 .catch(console.error);
+            invoke<string[]>('get_ignored_apps')
+                .then((apps) => setIgnoredApps(apps.join("\n")))
+                .catch(console.error);
         }
     }, [isOpen]);
 
@@ -37,6 +41,12 @@ const SettingsModal: React.FC<SettingsModalProps> = ({ isOpen, onClose, theme, s
             .catch(console.error);
     };
 
+    const saveIgnoredApps = () => {
+        invoke<string[]>('set_ignored_apps', { apps: ignoredApps.split("\n") })
+            .then((apps) => setIgnoredApps(apps.join("\n")))
+            .catch(console.error);
+    };
+
     return (
         <div className="fixed inset-0 z-50 flex items-center justify-center bg-black/50 backdrop-blur-sm">
             <div
@@ -109,6 +119,23 @@ const SettingsModal: React.FC<SettingsModalProps> = ({ isOpen, onClose, theme, s
                     </div>
                 </div>
 
+                {/* Ignored Apps Section */}
+                <div className="mb-6">
+                    <label className="block text-sm font-medium mb-1 opacity-80">{t('ignored_apps')}</label>
+                    <p className="text-xs opacity-60 mb-2">{t('ignored_apps_hint')}</p>
+                    <textarea
+                        value={ignoredApps}
+                        onChange={(e) => setIgnoredApps(e.target.value)}
+                        onBlur={saveIgnoredApps}
+                        rows={3}
+                        placeholder="KeePass.exe"
+                        className={clsx(
+                            "w-full px-3 py-2 rounded-md text-sm border outline-none resize-none",
+                            theme === 'dark' ? "bg-[#2d2d2d] border-[#333] focus:border-blue-500" : "bg-gray-50 border-gray-200 focus:border-blue-500"
+                        )}
+                    />
+                </div>
+
                 {/* Language Section */}
                 <div className="mb-6">
                     <label className="block text-sm font-medium mb-3 opacity-80">{t('language')}</label>